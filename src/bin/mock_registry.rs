@@ -1,6 +1,14 @@
 use actix_web::{web, App, HttpResponse, HttpServer, Result};
+use prompthive::cloud_sync::{
+    decide_push, CloudPrompt, CloudPromptList, CloudSummary, PushRequest, PushResult, PushStatus,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Synced prompts, kept in memory for the life of the server
+type SyncedPrompts = web::Data<Mutex<BTreeMap<String, CloudPrompt>>>;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct SearchQuery {
@@ -192,20 +200,92 @@ async fn publish_package(package: web::Json<serde_json::Value>) -> Result<HttpRe
     })))
 }
 
+async fn list_prompts(prompts: SyncedPrompts) -> Result<HttpResponse> {
+    let prompts = prompts.lock().unwrap();
+    Ok(HttpResponse::Ok().json(CloudPromptList {
+        prompts: prompts.values().map(CloudSummary::from).collect(),
+    }))
+}
+
+async fn get_prompt(path: web::Path<String>, prompts: SyncedPrompts) -> Result<HttpResponse> {
+    let name = path.into_inner();
+    match prompts.lock().unwrap().get(&name) {
+        Some(prompt) => Ok(HttpResponse::Ok().json(prompt)),
+        None => Ok(HttpResponse::NotFound().json(json!({
+            "error": "Prompt not found"
+        }))),
+    }
+}
+
+async fn sync_push(request: web::Json<PushRequest>, prompts: SyncedPrompts) -> Result<HttpResponse> {
+    let mut prompts = prompts.lock().unwrap();
+    let mut results = Vec::new();
+
+    for incoming in &request.prompts {
+        let current = prompts.get(&incoming.name);
+        let status = decide_push(current, incoming, request.force);
+        let mut revision = current.and_then(|prompt| prompt.revision);
+
+        if matches!(status, PushStatus::Created | PushStatus::Updated) {
+            revision = Some(revision.unwrap_or(0) + 1);
+            prompts.insert(
+                incoming.name.clone(),
+                CloudPrompt {
+                    name: incoming.name.clone(),
+                    content: incoming.content.clone(),
+                    description: incoming.description.clone(),
+                    tags: incoming.tags.clone(),
+                    revision,
+                    updated_at: Some(chrono::Utc::now().to_rfc3339()),
+                },
+            );
+        }
+
+        results.push(PushResult {
+            name: incoming.name.clone(),
+            status,
+            revision,
+            error: None,
+        });
+    }
+
+    let conflicts = results
+        .iter()
+        .filter(|result| result.status == PushStatus::Conflict)
+        .count();
+    Ok(HttpResponse::Ok().json(json!({
+        "success": true,
+        "message": format!("{} prompt(s) processed, {} conflict(s)", results.len(), conflicts),
+        "results": results
+    })))
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    println!("🚀 Starting PromptHive Mock Registry on http://localhost:8080");
+    // Optional port argument so end-to-end tests can run their own server
+    let port: u16 = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(8080);
+
+    println!("🚀 Starting PromptHive Mock Registry on http://localhost:{}", port);
     println!("📦 Available test packages:");
     println!("   - test/essentials (productivity prompts)");
     println!("   - test/ai-tools (AI development prompts)");
     println!("   - team/shared (collaboration prompts)");
     println!();
     println!("🔍 Test with:");
-    println!("   PROMPTHIVE_REGISTRY_URL=http://localhost:8080 ph browse ai");
-    println!("   PROMPTHIVE_REGISTRY_URL=http://localhost:8080 ph install test/essentials");
+    println!("   PROMPTHIVE_REGISTRY_URL=http://localhost:{} ph browse ai", port);
+    println!("   PROMPTHIVE_REGISTRY_URL=http://localhost:{} ph install test/essentials", port);
+    println!("   PROMPTHIVE_REGISTRY_URL=http://localhost:{} ph sync status -v", port);
 
-    HttpServer::new(|| {
+    let prompts: SyncedPrompts = web::Data::new(Mutex::new(BTreeMap::new()));
+    HttpServer::new(move || {
         App::new()
+            .app_data(prompts.clone())
+            .route("/api/prompts", web::get().to(list_prompts))
+            .route("/api/prompts/{name:.*}", web::get().to(get_prompt))
+            .route("/api/sync/push", web::post().to(sync_push))
             .route("/api/v1/search", web::get().to(search_packages))
             .route("/api/v1/packages/{name:.*}", web::get().to(get_package))
             .route("/api/v1/packages", web::post().to(publish_package))
@@ -215,7 +295,7 @@ async fn main() -> std::io::Result<()> {
                     .to(|| async { HttpResponse::Ok().body("PromptHive Mock Registry v1.0") }),
             )
     })
-    .bind("127.0.0.1:8080")?
+    .bind(("127.0.0.1", port))?
     .run()
     .await
 }
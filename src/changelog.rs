@@ -0,0 +1,218 @@
+//! Semantic version bumps and per-prompt changelogs
+//!
+//! `ph version <name> --bump minor` computes the next version from the
+//! prompt's current one. Every version appends a section to the prompt's
+//! `CHANGELOG.md`, kept next to its revision log so it moves with renames.
+//! [`render_changelog`] and [`render_bank_changelog`] turn those sections into
+//! markdown for `ph changelog` and package publishing.
+
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::Storage;
+
+/// Which part of a semantic version to increment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+}
+
+impl FromStr for Bump {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "major" => Ok(Bump::Major),
+            "minor" => Ok(Bump::Minor),
+            "patch" => Ok(Bump::Patch),
+            _ => Err(anyhow!("Unknown bump '{}'. Use: major, minor, patch", s)),
+        }
+    }
+}
+
+/// Parse `1.2.3`, `v1.2` or `v2` into its numeric parts
+pub fn parse_semver(version: &str) -> Option<(u64, u64, u64)> {
+    let digits = version.strip_prefix('v').unwrap_or(version);
+    let mut parts = digits.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+    let patch = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+/// Highest semantic version among `versions`, ignoring names like `stable`
+pub fn latest_semver<'a>(versions: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    versions
+        .into_iter()
+        .filter_map(|v| parse_semver(v).map(|parsed| (parsed, v)))
+        .max_by_key(|(parsed, _)| *parsed)
+        .map(|(_, v)| v)
+}
+
+/// Next version after `current`, keeping a `v` prefix if it had one
+///
+/// A prompt without a version starts from `0.0.0`.
+pub fn bump_version(current: Option<&str>, bump: Bump) -> Result<String> {
+    let current = current.unwrap_or("0.0.0");
+    let (major, minor, patch) = parse_semver(current)
+        .ok_or_else(|| anyhow!("Current version '{}' is not a semantic version", current))?;
+    let (major, minor, patch) = match bump {
+        Bump::Major => (major + 1, 0, 0),
+        Bump::Minor => (major, minor + 1, 0),
+        Bump::Patch => (major, minor, patch + 1),
+    };
+    let prefix = if current.starts_with('v') { "v" } else { "" };
+    Ok(format!("{}{}.{}.{}", prefix, major, minor, patch))
+}
+
+/// One section of a prompt's changelog
+#[derive(Debug, Clone)]
+pub struct ChangelogEntry {
+    pub version: String,
+    pub parent_version: Option<String>,
+    pub revision: u64,
+    pub created_at: String,
+    pub message: Option<String>,
+}
+
+impl ChangelogEntry {
+    fn render(&self) -> String {
+        let date = self.created_at.get(..10).unwrap_or(&self.created_at);
+        let mut section = format!("## {} - {}\n\n", self.version, date);
+        if let Some(message) = &self.message {
+            section.push_str(message.trim());
+            section.push_str("\n\n");
+        }
+        match &self.parent_version {
+            Some(parent) => section.push_str(&format!("_r{}, after {}_\n", self.revision, parent)),
+            None => section.push_str(&format!("_r{}_\n", self.revision)),
+        }
+        section
+    }
+}
+
+impl Storage {
+    fn changelog_path(&self, name: &str) -> PathBuf {
+        self.revisions().history_dir(name).join("CHANGELOG.md")
+    }
+
+    /// Add a section to the top of a prompt's changelog
+    pub fn append_changelog(&self, name: &str, entry: &ChangelogEntry) -> Result<()> {
+        let path = self.changelog_path(name);
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        fs::create_dir_all(path.parent().unwrap())?;
+        let separator = if existing.is_empty() { "" } else { "\n" };
+        fs::write(&path, format!("{}{}{}", entry.render(), separator, existing))?;
+        Ok(())
+    }
+
+    /// Changelog sections of a prompt, newest first
+    ///
+    /// Prompts tagged before changelogs existed get sections built from their tags.
+    pub fn changelog_sections(&self, name: &str) -> Result<String> {
+        if let Ok(content) = fs::read_to_string(self.changelog_path(name)) {
+            return Ok(content);
+        }
+        let mut tags: Vec<_> = self
+            .revisions()
+            .tags(name)?
+            .into_iter()
+            .filter(|(tag, _)| !tag.starts_with("backup-"))
+            .collect();
+        tags.sort_by(|a, b| b.1.created_at.cmp(&a.1.created_at));
+        let sections: Vec<String> = tags
+            .into_iter()
+            .map(|(version, tag)| {
+                ChangelogEntry {
+                    version,
+                    parent_version: None,
+                    revision: tag.revision,
+                    created_at: tag.created_at,
+                    message: tag.message,
+                }
+                .render()
+            })
+            .collect();
+        Ok(sections.join("\n"))
+    }
+}
+
+/// Markdown changelog for one prompt
+pub fn render_changelog(storage: &Storage, name: &str) -> Result<String> {
+    let sections = storage.changelog_sections(name)?;
+    if sections.is_empty() {
+        return Ok(format!("# {}\n\nNo versions yet.\n", name));
+    }
+    Ok(format!("# {}\n\n{}", name, sections))
+}
+
+/// Markdown changelog for every versioned prompt in a bank
+pub fn render_bank_changelog(storage: &Storage, bank: &str) -> Result<String> {
+    let mut output = format!("# {}\n", bank);
+    let mut prompts = storage.list_bank_prompts(bank)?;
+    prompts.sort();
+    for name in prompts {
+        let sections = storage.changelog_sections(&name)?;
+        if sections.is_empty() {
+            continue;
+        }
+        // Nest each prompt's sections under its own heading
+        let nested: Vec<String> = sections
+            .lines()
+            .map(|line| match line.strip_prefix("## ") {
+                Some(heading) => format!("### {}", heading),
+                None => line.to_string(),
+            })
+            .collect();
+        output.push_str(&format!("\n## {}\n\n{}\n", name, nested.join("\n")));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump_version() {
+        assert_eq!(bump_version(None, Bump::Minor).unwrap(), "0.1.0");
+        assert_eq!(bump_version(Some("v1.2"), Bump::Patch).unwrap(), "v1.2.1");
+        assert_eq!(bump_version(Some("1.9.3"), Bump::Minor).unwrap(), "1.10.0");
+        assert_eq!(bump_version(Some("v1.9.3"), Bump::Major).unwrap(), "v2.0.0");
+        assert!(bump_version(Some("stable"), Bump::Patch).is_err());
+        assert_eq!(latest_semver(["stable", "v1.10", "v1.9.9"]), Some("v1.10"));
+    }
+
+    #[test]
+    fn test_changelog_sections_newest_first() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new_with_base(temp.path().to_path_buf()).unwrap();
+        let (metadata, _) = storage.parse_prompt_content("body").unwrap();
+        storage.write_prompt("ops/deploy", &metadata, "body").unwrap();
+
+        for (version, parent) in [("1.0.0", None), ("1.1.0", Some("1.0.0"))] {
+            let entry = ChangelogEntry {
+                version: version.to_string(),
+                parent_version: parent.map(str::to_string),
+                revision: 1,
+                created_at: "2026-10-18T12:00:00Z".to_string(),
+                message: Some(format!("Release {}", version)),
+            };
+            storage.append_changelog("ops/deploy", &entry).unwrap();
+        }
+
+        let rendered = render_changelog(&storage, "ops/deploy").unwrap();
+        assert!(rendered.starts_with("# ops/deploy\n\n## 1.1.0 - 2026-10-18\n\nRelease 1.1.0\n\n_r1, after 1.0.0_"));
+        assert!(rendered.find("1.1.0").unwrap() < rendered.find("## 1.0.0").unwrap());
+
+        let bank = render_bank_changelog(&storage, "ops").unwrap();
+        assert!(bank.contains("## ops/deploy\n\n### 1.1.0 - 2026-10-18"));
+    }
+}
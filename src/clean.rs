@@ -3,13 +3,40 @@
 //! Provides functionality to clean text content by removing ANSI escape sequences,
 //! box drawing characters, and other formatting artifacts to produce clean,
 //! readable text suitable for processing and storage.
+//!
+//! Cleaning is mode-aware. `plain` flattens everything to trimmed lines (the
+//! original behaviour), `code` only removes framing so indentation and box
+//! characters inside content survive, and `transcript` additionally drops the
+//! chrome of AI CLI sessions: `>` prompt markers, spinner lines and status
+//! hints. Fenced code blocks are left intact in every mode.
 
+use anyhow::{anyhow, Result};
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 // Box drawing characters to remove
-static BOX_CHARS: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"[│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌╯╰╱╲╳╭╮]").unwrap());
+static BOX_CHARS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"[│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌╯╰╱╲╳╭╮┃━┏┓┗┛]").unwrap()
+});
+
+// Characters used for the left/right edges of TUI panes
+const VERTICALS: &[char] = &['│', '┃', '║', '┆', '┊'];
+
+// Corners that open and close a bordered box
+const LEFT_CORNERS: &[char] = &['╭', '┌', '╔', '┏', '╰', '└', '╚', '┗'];
+const RIGHT_CORNERS: &[char] = &['╮', '┐', '╗', '┓', '╯', '┘', '╝', '┛'];
+
+// Spinner/progress lines from AI CLIs ("✻ Thinking… (esc to interrupt)")
+static SPINNER_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*[⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏✻✶✳✢✽·*]\s*\S.*(…|esc to interrupt)").unwrap()
+});
+
+// Status hints printed under the input box
+static HINT_LINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^(\?\s+for shortcuts|⏵⏵)").unwrap());
+
+// Prompt, assistant and tool-output markers at the start of a line
+static TURN_MARKER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*([>❯›]|⏺|●|⎿)(\s|$)").unwrap());
 
 // ANSI escape sequences
 static ANSI_ESCAPES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1b\[[0-9;]*[mGKHF]").unwrap());
@@ -18,36 +45,218 @@ static ANSI_ESCAPES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1b\[[0-9;]*[mGKHF
 static TUI_ARTIFACTS: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^\s*[│┃┆┊]\s*|\s*[│┃┆┊]\s*$").unwrap());
 
+/// How aggressively `clean_with` rewrites text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CleanMode {
+    /// Trim every line and strip box characters anywhere
+    #[default]
+    Plain,
+    /// Remove frames and gutters, keep indentation and content characters
+    Code,
+    /// Like `Code`, plus AI CLI prompts, spinners and status hints
+    Transcript,
+}
+
+impl std::str::FromStr for CleanMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "plain" => Ok(CleanMode::Plain),
+            "code" => Ok(CleanMode::Code),
+            "transcript" => Ok(CleanMode::Transcript),
+            _ => Err(anyhow!(
+                "Unknown clean mode '{}'. Use: plain, code, transcript",
+                s
+            )),
+        }
+    }
+}
+
+/// Options for `clean_with`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CleanOptions {
+    pub mode: CleanMode,
+    /// Keep blank lines as they are instead of dropping (plain) or collapsing them
+    pub keep_blank_lines: bool,
+}
+
 /// Clean text by removing TUI artifacts, box drawing characters, and ANSI escapes
 pub fn clean_text(input: &str) -> String {
-    let mut cleaned = input.to_string();
-
-    // Remove ANSI escape sequences first
-    cleaned = ANSI_ESCAPES.replace_all(&cleaned, "").to_string();
-
-    // Remove box drawing characters
-    cleaned = BOX_CHARS.replace_all(&cleaned, "").to_string();
-
-    // Clean up line-by-line TUI artifacts
-    cleaned = cleaned
-        .lines()
-        .map(|line| {
-            // Remove leading/trailing TUI borders
-            let line = TUI_ARTIFACTS.replace_all(line, "");
-            line.trim().to_string()
-        })
-        .filter(|line| !line.is_empty())
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    // Clean up excessive whitespace
-    cleaned = cleaned.trim().to_string();
-
-    // Replace multiple blank lines with single blank line
-    let multi_newline = Regex::new(r"\n{3,}").unwrap();
-    cleaned = multi_newline.replace_all(&cleaned, "\n\n").to_string();
-
-    cleaned
+    clean_with(input, &CleanOptions::default())
+}
+
+/// Clean text according to `options`
+pub fn clean_with(input: &str, options: &CleanOptions) -> String {
+    let stripped = ANSI_ESCAPES.replace_all(input, "");
+    let lines = unframe(&stripped, options.mode != CleanMode::Plain);
+
+    let mut output: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    for line in lines {
+        // Fenced code is kept verbatim, blank lines included
+        if is_fence_marker(line.trim_start()) {
+            in_fence = !in_fence;
+            output.push(line.trim_end().to_string());
+            continue;
+        }
+        if in_fence {
+            output.push(line);
+            continue;
+        }
+
+        let cleaned = match options.mode {
+            CleanMode::Plain => {
+                let line = TUI_ARTIFACTS.replace_all(&line, "");
+                BOX_CHARS.replace_all(&line, "").trim().to_string()
+            }
+            CleanMode::Code => line.trim_end().to_string(),
+            CleanMode::Transcript => match transcript_line(&line) {
+                Some(line) => line,
+                None => continue,
+            },
+        };
+
+        if cleaned.is_empty() && !options.keep_blank_lines {
+            let previous_blank = output.last().is_none_or(|l| l.is_empty());
+            if options.mode == CleanMode::Plain || previous_blank {
+                continue;
+            }
+        }
+        output.push(cleaned);
+    }
+
+    // Drop leading and trailing blank lines, but not the first line's indentation
+    let first = output.iter().position(|l| !l.trim().is_empty());
+    let last = output.iter().rposition(|l| !l.trim().is_empty());
+    match (first, last) {
+        (Some(first), Some(last)) => output[first..=last].join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn is_fence_marker(line: &str) -> bool {
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// Top or bottom border of a box, e.g. `╭──── title ────╮`
+fn is_frame_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with(LEFT_CORNERS)
+        && (trimmed.ends_with(RIGHT_CORNERS)
+            || BOX_CHARS.replace_all(trimmed, "").trim().is_empty())
+}
+
+/// Remove box borders and uniform gutters, splitting the text at each border
+///
+/// With `dedent`, each de-guttered segment also loses its common indentation,
+/// so code inside a pane keeps only its relative indentation.
+fn unframe(text: &str, dedent: bool) -> Vec<String> {
+    let mut output = Vec::new();
+    let mut segment: Vec<String> = Vec::new();
+    let mut in_fence = false;
+
+    for line in text.lines() {
+        let inner = line.trim_start().trim_start_matches(VERTICALS).trim_start();
+        if is_fence_marker(inner) {
+            in_fence = !in_fence;
+        } else if !in_fence && is_frame_line(line) {
+            strip_gutter(&mut segment, dedent);
+            output.append(&mut segment);
+            continue;
+        }
+        segment.push(line.to_string());
+    }
+    strip_gutter(&mut segment, dedent);
+    output.append(&mut segment);
+    output
+}
+
+/// Strip a left and/or right border shared by every non-blank line
+fn strip_gutter(segment: &mut [String], dedent: bool) {
+    let rows: Vec<usize> = (0..segment.len())
+        .filter(|&i| !segment[i].trim().is_empty())
+        .collect();
+    let Some(&first) = rows.first() else {
+        return;
+    };
+
+    let indent = segment[first].len() - segment[first].trim_start().len();
+    let left = rows.iter().all(|&i| {
+        let trimmed = segment[i].trim_start();
+        segment[i].len() - trimmed.len() == indent && trimmed.starts_with(VERTICALS)
+    });
+    if left {
+        for &i in &rows {
+            let trimmed = segment[i].trim_start();
+            let border = trimmed.chars().next().map_or(0, char::len_utf8);
+            segment[i] = trimmed[border..].to_string();
+        }
+        // The space after the border belongs to the gutter too
+        if rows
+            .iter()
+            .all(|&i| segment[i].is_empty() || segment[i].starts_with(' '))
+        {
+            for &i in &rows {
+                if !segment[i].is_empty() {
+                    segment[i].remove(0);
+                }
+            }
+        }
+    }
+
+    let rows: Vec<usize> = rows
+        .into_iter()
+        .filter(|&i| !segment[i].trim().is_empty())
+        .collect();
+    let right = !rows.is_empty()
+        && rows
+            .iter()
+            .all(|&i| segment[i].trim_end().ends_with(VERTICALS));
+    if right {
+        for &i in &rows {
+            let trimmed = segment[i].trim_end();
+            let border = trimmed.chars().last().map_or(0, char::len_utf8);
+            segment[i] = trimmed[..trimmed.len() - border].trim_end().to_string();
+        }
+    }
+
+    if dedent && (left || right) {
+        // Only ASCII indentation is removed, so draining never splits a char
+        let indent = |line: &str| line.bytes().take_while(|b| matches!(b, b' ' | b'\t')).count();
+        let common = rows
+            .iter()
+            .filter(|&&i| !segment[i].trim().is_empty())
+            .map(|&i| indent(&segment[i]))
+            .min()
+            .unwrap_or(0);
+        for line in segment.iter_mut() {
+            if line.trim().is_empty() {
+                line.clear();
+            } else {
+                let width = indent(line).min(common);
+                line.drain(..width);
+            }
+        }
+    }
+}
+
+/// Whether a line is AI CLI chrome: a spinner, a status hint or a horizontal rule
+pub fn is_cli_chrome(line: &str) -> bool {
+    let trimmed = line.trim();
+    let is_rule = !trimmed.is_empty() && trimmed.chars().all(|c| matches!(c, '─' | '━' | '═'));
+    is_rule || SPINNER_LINE.is_match(line) || HINT_LINE.is_match(trimmed)
+}
+
+/// Clean one transcript line, or `None` when the line is CLI chrome
+fn transcript_line(line: &str) -> Option<String> {
+    if is_cli_chrome(line) {
+        return None;
+    }
+    if let Some(marker) = TURN_MARKER.find(line) {
+        return Some(line[marker.end()..].trim_end().to_string());
+    }
+    Some(line.trim_end().to_string())
 }
 
 #[cfg(test)]
@@ -76,4 +285,61 @@ mod tests {
         let expected = "Test Message\nWith multiple\nlines";
         assert_eq!(clean_text(input), expected);
     }
+
+    #[test]
+    fn test_code_mode_keeps_indentation_and_content_boxes() {
+        let input = "  │ fn main() {          │\n  │     let bar = \"│\";   │\n  │                      │\n  │     // ───────────   │\n  │ }                    │";
+        let options = CleanOptions {
+            mode: CleanMode::Code,
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_with(input, &options),
+            "fn main() {\n    let bar = \"│\";\n\n    // ───────────\n}"
+        );
+
+        // Plain mode still flattens, but leaves fenced code alone
+        let fenced = "Intro\n\n```python\ndef f():\n\n    return 1\n```";
+        assert_eq!(clean_text(fenced), "Intro\n```python\ndef f():\n\n    return 1\n```");
+    }
+
+    #[test]
+    fn test_transcript_mode_drops_cli_chrome() {
+        let input = "╭──────────────────────────────╮
+│ > Write a haiku about rust   │
+╰──────────────────────────────╯
+
+⏺ Here it is:
+
+  Memory is safe
+
+✻ Thinking… (esc to interrupt)
+────────────────────────────────
+  ? for shortcuts";
+        let options = CleanOptions {
+            mode: CleanMode::Transcript,
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_with(input, &options),
+            "Write a haiku about rust\n\nHere it is:\n\n  Memory is safe"
+        );
+        assert!("html".parse::<CleanMode>().is_err());
+    }
+
+    #[test]
+    fn test_gutter_dedent_keeps_unicode_whitespace() {
+        let code = CleanOptions {
+            mode: CleanMode::Code,
+            ..Default::default()
+        };
+        // Non-ASCII whitespace is content, so the rows share no indentation
+        assert_eq!(clean_with("│ x │\n│\u{3000}y │\n", &code), " x\n\u{3000}y");
+
+        let transcript = CleanOptions {
+            mode: CleanMode::Transcript,
+            ..Default::default()
+        };
+        assert_eq!(clean_with("│ x\n│\u{a0}y\n", &transcript), " x\n\u{a0}y");
+    }
 }
@@ -15,6 +15,7 @@ use clap::{Parser, Subcommand};
 Usage: {usage}
 
 Options:
+      --exact    Require exact prompt names (no fuzzy matching)
   -h, --help     Print help
   -V, --version  Print version
 
@@ -31,18 +32,24 @@ CORE OPERATIONS:
 
 SEARCH & DISCOVERY:
   find, f         Search prompts with fuzzy matching
+  alias           Manage persistent short codes
+  similar         Find prompts with similar content
+  dedupe          Find clusters of near-duplicate prompts
   tui, t          Launch interactive TUI
 
 COMPOSITION & UTILITIES:
   compose, c      Compose multiple prompts together
   clean, x        Clean TUI artifacts and formatting
+  extract         Extract turns from AI chat transcripts
   diff            Compare two prompts
   merge           Merge changes between prompts
+  apply           Apply a patch saved from ph diff
   import          Import prompts from files
 
 VERSIONING:
   version         Create a version tag
   versions        Show version history
+  changelog       Render a prompt or bank changelog
   rollback        Rollback to previous version
   rename, r, mv   Rename a prompt
 
@@ -52,6 +59,7 @@ REGISTRY & SHARING:
   publish         Publish prompts to registry
   unpublish       Unpublish registry prompts
   share           Share prompts with others
+  redact          Check prompts for secrets before sharing
   login           Login to registry
   logout          Logout from registry
 
@@ -63,6 +71,7 @@ CLOUD & TEAMS:
 
 SYSTEM & CONFIG:
   config          Configure settings
+  doctor          Check storage for problems
   security        Security scanning
   perf            Measure performance
   health          System health monitoring
@@ -78,6 +87,18 @@ EXAMPLES:
 
 Run 'ph COMMAND --help' for more information on a command.")]
 pub struct Cli {
+    /// Require exact prompt names (no fuzzy matching)
+    #[arg(long = "exact", global = true)]
+    pub exact: bool,
+
+    /// Print JSON instead of text (same as --output-format json)
+    #[arg(long = "json", global = true)]
+    pub json: bool,
+
+    /// Output format for read commands: text, json, yaml, tsv
+    #[arg(long = "output-format", value_name = "FORMAT", global = true)]
+    pub output_format: Option<String>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -113,6 +134,9 @@ pub enum Commands {
         /// Auto-import and prepend directive files (comma-separated list)
         #[arg(long = "with", value_name = "FILES")]
         with_directives: Option<String>,
+        /// Replace API keys, tokens and other secrets with placeholders
+        #[arg(long)]
+        redact: bool,
     },
     /// Display prompt content
     #[command(alias = "s")]
@@ -278,6 +302,12 @@ pub enum Commands {
     Clean {
         /// Text to clean (optional - uses stdin if not provided)
         text: Option<String>,
+        /// Cleaning mode: plain (flatten), code (keep indentation), transcript (AI CLI sessions)
+        #[arg(long, value_name = "MODE", default_value = "plain")]
+        mode: String,
+        /// Keep blank lines instead of dropping or collapsing them
+        #[arg(long = "keep-blank-lines")]
+        keep_blank_lines: bool,
         /// Save cleaned text as a new prompt (auto-clipboard unless -q)
         #[arg(short = 's', value_name = "NAME")]
         save: Option<String>,
@@ -294,11 +324,43 @@ pub enum Commands {
         #[arg(short = 'q')]
         quiet: bool,
     },
+    /// Extract conversation turns from an AI chat transcript
+    Extract {
+        /// Transcript file: terminal capture, JSONL session or markdown export (stdin if omitted)
+        source: Option<String>,
+        /// Transcript format (auto, terminal, jsonl, markdown)
+        #[arg(long, default_value = "auto")]
+        format: String,
+        /// Only keep turns from these roles (user, assistant, system, tool)
+        #[arg(long, short = 'r', value_delimiter = ',')]
+        role: Vec<String>,
+        /// Turns to keep by position after role filtering (e.g. 1,3-5,-1)
+        #[arg(long, short = 't', allow_hyphen_values = true)]
+        turn: Option<String>,
+        /// List turns and their positions instead of extracting
+        #[arg(long, short = 'l')]
+        list: bool,
+        /// Save extracted text as a new prompt, recording the transcript as its source
+        #[arg(short = 's', value_name = "NAME")]
+        save: Option<String>,
+        /// Append extracted text to an existing prompt
+        #[arg(short = 'a', value_name = "NAME")]
+        append: Option<String>,
+        /// Copy to clipboard (explicit override)
+        #[arg(short = 'c')]
+        clipboard: bool,
+        /// Write to file (smart filename if no path provided)
+        #[arg(short = 'f', value_name = "PATH", num_args = 0..=1, default_missing_value = "")]
+        file: Option<String>,
+        /// Quiet mode - suppress clipboard and output messages
+        #[arg(short = 'q')]
+        quiet: bool,
+    },
     /// Compare two prompts and show differences
     Diff {
-        /// First prompt to compare
+        /// First prompt (or file path) to compare
         prompt1: String,
-        /// Second prompt to compare
+        /// Second prompt (or file path) to compare
         prompt2: String,
         /// Output format (unified, side-by-side, or brief)
         #[arg(short = 'f', long = "format", default_value = "unified")]
@@ -306,16 +368,30 @@ pub enum Commands {
         /// Context lines around differences
         #[arg(short = 'c', long = "context", default_value = "3")]
         context: usize,
+        /// Show changed words inline instead of whole lines
+        #[arg(long = "word-diff")]
+        word_diff: bool,
+        /// Also compare frontmatter fields (description, tags, version, custom keys)
+        #[arg(long = "metadata")]
+        metadata: bool,
+        /// Summarize insertions and deletions instead of showing the diff
+        #[arg(long = "stat", conflicts_with = "word_diff")]
+        stat: bool,
+        /// Open both sides in the configured difftool (`ph config difftool`)
+        #[arg(long = "tool", conflicts_with_all = ["word_diff", "stat", "output"])]
+        tool: bool,
         /// Save diff output to file
         #[arg(long = "output")]
         output: Option<String>,
     },
     /// Merge changes from one prompt into another
     Merge {
-        /// Source prompt (to copy from)
-        source: String,
+        /// Source prompt (to merge from)
+        #[arg(required_unless_present_any = ["continue_merge", "abort"])]
+        source: Option<String>,
         /// Target prompt (to merge into)
-        target: String,
+        #[arg(required_unless_present_any = ["continue_merge", "abort"])]
+        target: Option<String>,
         /// Create backup before merging
         #[arg(short = 'b', long = "backup")]
         backup: bool,
@@ -325,6 +401,32 @@ pub enum Commands {
         /// Interactive merge mode
         #[arg(short = 'i', long = "interactive")]
         interactive: bool,
+        /// Fail instead of writing conflict markers
+        #[arg(long = "no-conflicts")]
+        no_conflicts: bool,
+        /// Resolve in the configured mergetool (`ph config mergetool`)
+        #[arg(long = "tool", conflicts_with_all = ["interactive", "preview", "no_conflicts"])]
+        tool: bool,
+        /// Resolve the conflicts of an unfinished merge in the editor and finish it
+        #[arg(long = "continue", conflicts_with_all = ["abort", "source", "tool"])]
+        continue_merge: bool,
+        /// Drop an unfinished merge, leaving the target unchanged
+        #[arg(long = "abort", conflicts_with = "source")]
+        abort: bool,
+    },
+    /// Apply a unified diff saved with `ph diff --output`
+    Apply {
+        /// Patch file to apply
+        patch: String,
+        /// Prompt to patch (default: the prompt on the patch's `---` line)
+        #[arg(long = "to")]
+        to: Option<String>,
+        /// Choose hunks one at a time
+        #[arg(short = 'i', long = "interactive")]
+        interactive: bool,
+        /// Preview the patched prompt without writing it
+        #[arg(short = 'p', long = "preview")]
+        preview: bool,
     },
     // === VERSIONING ===
     /// Create a version tag for prompt
@@ -332,18 +434,30 @@ pub enum Commands {
         /// Name of the prompt to version
         name: String,
         /// Version tag (e.g. v1.0, stable, working)
-        tag: String,
+        #[arg(required_unless_present = "bump")]
+        tag: Option<String>,
+        /// Compute the next semantic version (major, minor or patch)
+        #[arg(long = "bump", conflicts_with = "tag")]
+        bump: Option<String>,
         /// Optional description for this version
         #[arg(short = 'm', long = "message")]
         message: Option<String>,
     },
     /// Show version history for a prompt
+    #[command(args_conflicts_with_subcommands = true)]
     Versions {
         /// Name of the prompt to show versions for
-        name: String,
+        name: Option<String>,
         /// Show detailed information
         #[arg(short = 'v', long = "verbose")]
         verbose: bool,
+        #[command(subcommand)]
+        action: Option<crate::commands::versioning::VersionsCommands>,
+    },
+    /// Render a prompt's or bank's changelog as markdown
+    Changelog {
+        /// Prompt name or bank name
+        name: String,
     },
     /// Rollback prompt to a previous version
     Rollback {
@@ -363,10 +477,39 @@ pub enum Commands {
         /// New name for the prompt
         new_name: String,
     },
+    /// Find prompts with content similar to a prompt
+    Similar {
+        /// Prompt to compare against the library
+        prompt: String,
+        /// Minimum similarity to report (0.0 - 1.0)
+        #[arg(short = 't', long = "threshold", default_value = "0.3")]
+        threshold: f64,
+        /// Maximum number of results
+        #[arg(short = 'n', long = "limit", default_value = "10")]
+        limit: usize,
+    },
+    /// Find clusters of near-duplicate prompts and offer to merge them
+    Dedupe {
+        /// Minimum similarity for two prompts to count as duplicates (0.0 - 1.0)
+        #[arg(short = 't', long = "threshold", default_value = "0.8")]
+        threshold: f64,
+    },
+    /// Manage persistent short codes for prompts
+    Alias {
+        #[command(subcommand)]
+        action: Option<crate::commands::aliases::AliasCommands>,
+    },
+    /// Check prompts for secrets and manage redaction false positives
+    Redact {
+        #[command(subcommand)]
+        action: Option<crate::commands::redact::RedactCommands>,
+    },
     /// Show usage statistics
     Stats,
     /// Show command history
     History {
+        #[command(subcommand)]
+        action: Option<crate::commands::history::HistoryCommands>,
         /// Number of recent entries to show
         #[arg(short = 'n', long = "limit", default_value = "10")]
         limit: usize,
@@ -424,6 +567,9 @@ pub enum Commands {
     Install {
         /// Package to install (e.g. @user/package)
         package: String,
+        /// Install even if the security scan finds critical issues
+        #[arg(long = "allow-insecure")]
+        allow_insecure: bool,
     },
     /// Publish prompts to registry
     Publish {
@@ -441,6 +587,9 @@ pub enum Commands {
         /// Bank/category for the prompt (default: personal)
         #[arg(short = 'b', long = "bank", default_value = "personal")]
         bank: String,
+        /// Publish even if the security scan finds critical issues
+        #[arg(long = "allow-insecure")]
+        allow_insecure: bool,
     },
     /// Unpublish registry prompts
     Unpublish {
@@ -494,6 +643,9 @@ pub enum Commands {
         /// Set expiration time in hours (default: never expires)
         #[arg(long = "expires")]
         expires: Option<u32>,
+        /// Share even if the security scan finds critical issues
+        #[arg(long = "allow-insecure")]
+        allow_insecure: bool,
     },
     /// Manage prompt suggestions
     Suggestions {
@@ -541,19 +693,31 @@ pub enum Commands {
         /// Configuration category
         category: String,
         /// Configuration action
+        #[arg(default_value = "show")]
         action: String,
     },
     /// Security scanning and audit
     Security {
+        /// Prompt to scan
+        prompt: Option<String>,
         /// Scan all prompts for security issues
         #[arg(long, short = 'a')]
         all: bool,
-        /// Output format (table, json)
+        /// Output format (table, json, sarif, junit)
         #[arg(long, short = 'f', default_value = "table")]
         format: String,
         /// Show only summary
         #[arg(long)]
         summary: bool,
+        /// Exit with status 1 when a finding is at or above this severity (low, medium, high, critical)
+        #[arg(long = "fail-on", value_name = "SEVERITY")]
+        fail_on: Option<String>,
+        /// Accept all current findings into the baseline
+        #[arg(long = "update-baseline")]
+        update_baseline: bool,
+        /// Also report findings recorded in the baseline
+        #[arg(long = "no-baseline")]
+        no_baseline: bool,
     },
 
     /// Manage template variables
@@ -580,6 +744,8 @@ pub enum Commands {
         #[arg(long = "verify")]
         verify: bool,
     },
+    /// Check storage for orphaned histories and stray backups
+    Doctor,
     /// System health monitoring
     Health {
         /// Output format (json, pretty, compact)
@@ -615,6 +781,65 @@ pub enum Commands {
     },
 }
 
+impl Commands {
+    /// Canonical subcommand name; aliases map to the command they stand for
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Use { .. } => "use",
+            Commands::Show { .. } => "show",
+            Commands::New { .. } => "new",
+            Commands::Edit { .. } => "edit",
+            Commands::Delete { .. } => "delete",
+            Commands::Ls { .. } => "ls",
+            Commands::Find { .. } => "find",
+            Commands::Compose { .. } => "compose",
+            Commands::Import { .. } => "import",
+            Commands::Clean { .. } => "clean",
+            Commands::Extract { .. } => "extract",
+            Commands::Diff { .. } => "diff",
+            Commands::Merge { .. } => "merge",
+            Commands::Apply { .. } => "apply",
+            Commands::Version { .. } => "version",
+            Commands::Versions { .. } => "versions",
+            Commands::Changelog { .. } => "changelog",
+            Commands::Rollback { .. } => "rollback",
+            Commands::Rename { .. } => "rename",
+            Commands::Similar { .. } => "similar",
+            Commands::Dedupe { .. } => "dedupe",
+            Commands::Alias { .. } => "alias",
+            Commands::Redact { .. } => "redact",
+            Commands::Stats => "stats",
+            Commands::History { .. } => "history",
+            Commands::Batch { .. } => "batch",
+            Commands::Search { .. } => "search",
+            Commands::Install { .. } => "install",
+            Commands::Publish { .. } => "publish",
+            Commands::Unpublish { .. } => "unpublish",
+            Commands::Browse { .. } => "browse",
+            Commands::Login { .. } => "login",
+            Commands::Logout => "logout",
+            Commands::Sync { .. } => "sync",
+            Commands::Teams { .. } => "teams",
+            Commands::Share { .. } => "share",
+            Commands::Suggestions { .. } => "suggestions",
+            Commands::Improve { .. } => "improve",
+            Commands::Bank { .. } => "bank",
+            Commands::Users { .. } => "users",
+            Commands::Subscription { .. } => "subscription",
+            Commands::Tui { .. } => "tui",
+            Commands::Completion { .. } => "completion",
+            Commands::Config { .. } => "config",
+            Commands::Security { .. } => "security",
+            Commands::Vars { .. } => "vars",
+            Commands::Web { .. } => "web",
+            Commands::Perf { .. } => "perf",
+            Commands::Doctor => "doctor",
+            Commands::Health { .. } => "health",
+            Commands::Values { .. } => "values",
+        }
+    }
+}
+
 #[derive(Subcommand)]
 pub enum VarsCommands {
     /// List all available template variables
@@ -636,3 +861,16 @@ pub enum VarsCommands {
     /// Show example usage of template variables
     Examples,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_name_is_canonical() {
+        let cli = Cli::try_parse_from(["ph", "mv", "old", "new"]).unwrap();
+        assert_eq!(cli.command.unwrap().name(), "rename");
+        let cli = Cli::try_parse_from(["ph", "doctor"]).unwrap();
+        assert_eq!(cli.command.unwrap().name(), "doctor");
+    }
+}
@@ -2,13 +2,201 @@
 //!
 //! Provides cross-platform clipboard access for reading from and writing to
 //! the system clipboard, enabling seamless integration with external applications.
+//!
+//! Copies go through a chain of backends: the native clipboard, then the
+//! `wl-copy`, `xclip` and `xsel` tools, then tmux's paste buffer, and finally
+//! the OSC 52 terminal escape, which works over SSH when the terminal supports
+//! it. The chain is built from the environment (`SSH_TTY`, `TMUX`,
+//! `WAYLAND_DISPLAY`, `DISPLAY`), or pinned to one backend with
+//! `ph config clipboard <backend>` (the `[clipboard]` section of config.toml).
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
 use copypasta::{ClipboardContext, ClipboardProvider};
+use is_terminal::IsTerminal;
+use serde::{Deserialize, Serialize};
+use std::fs;
 use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Terminals commonly drop OSC 52 payloads larger than this
+const OSC52_MAX_BYTES: usize = 100_000;
+
+/// A way of putting text on a clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardBackend {
+    /// Platform clipboard (macOS, Windows, X11)
+    Native,
+    WlCopy,
+    Xclip,
+    Xsel,
+    /// tmux paste buffer (`tmux load-buffer`), forwarded to the terminal when tmux allows it
+    Tmux,
+    /// OSC 52 escape sequence written to the terminal
+    Osc52,
+}
+
+impl ClipboardBackend {
+    pub const ALL: [ClipboardBackend; 6] = [
+        ClipboardBackend::Native,
+        ClipboardBackend::WlCopy,
+        ClipboardBackend::Xclip,
+        ClipboardBackend::Xsel,
+        ClipboardBackend::Tmux,
+        ClipboardBackend::Osc52,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ClipboardBackend::Native => "native",
+            ClipboardBackend::WlCopy => "wl-copy",
+            ClipboardBackend::Xclip => "xclip",
+            ClipboardBackend::Xsel => "xsel",
+            ClipboardBackend::Tmux => "tmux",
+            ClipboardBackend::Osc52 => "osc52",
+        }
+    }
+
+    /// Whether the backend can work in this environment
+    pub fn is_available(self, env: &ClipboardEnvironment) -> bool {
+        let graphical = env.wayland || env.x11 || cfg!(any(target_os = "macos", target_os = "windows"));
+        match self {
+            // Over SSH without a forwarded display, the native clipboard is the remote one
+            ClipboardBackend::Native => graphical && !(env.ssh && !env.wayland && !env.x11 && !cfg!(windows)),
+            ClipboardBackend::WlCopy => env.wayland && command_exists("wl-copy"),
+            ClipboardBackend::Xclip => env.x11 && command_exists("xclip"),
+            ClipboardBackend::Xsel => env.x11 && command_exists("xsel"),
+            ClipboardBackend::Tmux => env.tmux && command_exists("tmux"),
+            ClipboardBackend::Osc52 => env.terminal,
+        }
+    }
+
+    /// Copy `content`, failing if the backend is not usable
+    pub fn copy(self, content: &str) -> Result<()> {
+        match self {
+            ClipboardBackend::Native => {
+                let mut context = ClipboardContext::new()
+                    .map_err(|e| anyhow!("Native clipboard unavailable: {}", e))?;
+                context
+                    .set_contents(content.to_string())
+                    .map_err(|e| anyhow!("Native clipboard failed: {}", e))
+            }
+            ClipboardBackend::WlCopy => pipe_to("wl-copy", &[], content),
+            ClipboardBackend::Xclip => pipe_to("xclip", &["-selection", "clipboard"], content),
+            ClipboardBackend::Xsel => pipe_to("xsel", &["--clipboard", "--input"], content),
+            ClipboardBackend::Tmux => {
+                // -w also sets the terminal clipboard (tmux 3.2+); older versions reject it
+                pipe_to("tmux", &["load-buffer", "-w", "-"], content)
+                    .or_else(|_| pipe_to("tmux", &["load-buffer", "-"], content))
+            }
+            ClipboardBackend::Osc52 => write_osc52(content),
+        }
+    }
+}
+
+impl std::fmt::Display for ClipboardBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for ClipboardBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        ClipboardBackend::ALL
+            .into_iter()
+            .find(|b| b.name() == s.to_lowercase() || (s == "osc-52" && *b == ClipboardBackend::Osc52))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown clipboard backend '{}'. Use: auto, native, wl-copy, xclip, xsel, tmux, osc52",
+                    s
+                )
+            })
+    }
+}
+
+/// What the environment says about available clipboards
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClipboardEnvironment {
+    /// Connected over SSH (`SSH_TTY` or `SSH_CONNECTION`)
+    pub ssh: bool,
+    /// Running inside tmux (`TMUX`)
+    pub tmux: bool,
+    /// Wayland session (`WAYLAND_DISPLAY`)
+    pub wayland: bool,
+    /// X11 display, possibly forwarded (`DISPLAY`)
+    pub x11: bool,
+    /// A terminal is attached that can receive escape sequences
+    pub terminal: bool,
+}
+
+impl ClipboardEnvironment {
+    pub fn detect() -> Self {
+        let set = |name: &str| std::env::var(name).is_ok_and(|v| !v.is_empty());
+        Self {
+            ssh: set("SSH_TTY") || set("SSH_CONNECTION"),
+            tmux: set("TMUX"),
+            wayland: set("WAYLAND_DISPLAY"),
+            x11: set("DISPLAY"),
+            terminal: io::stderr().is_terminal() || fs::metadata("/dev/tty").is_ok(),
+        }
+    }
+
+    /// Backends to try, in order
+    ///
+    /// Over SSH the terminal-side backends come first, because a clipboard on
+    /// the remote machine is not the one the user is looking at.
+    pub fn backend_chain(&self) -> Vec<ClipboardBackend> {
+        let mut chain: Vec<ClipboardBackend> = ClipboardBackend::ALL
+            .into_iter()
+            .filter(|b| b.is_available(self))
+            .collect();
+        if self.ssh {
+            chain.sort_by_key(|b| !matches!(b, ClipboardBackend::Tmux | ClipboardBackend::Osc52));
+        }
+        chain
+    }
+}
+
+/// Settings from the `[clipboard]` section of config.toml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClipboardConfig {
+    /// Pinned backend name; detected from the environment when unset or "auto"
+    #[serde(default)]
+    pub backend: Option<String>,
+}
+
+impl ClipboardConfig {
+    /// Read the `[clipboard]` section from `config.toml` in the base directory
+    pub fn load(base_dir: &Path) -> Self {
+        fs::read_to_string(base_dir.join("config.toml"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Value>().ok())
+            .and_then(|config| config.get("clipboard").cloned())
+            .and_then(|table| table.try_into().ok())
+            .unwrap_or_default()
+    }
+
+    /// The pinned backend, if any
+    ///
+    /// `PROMPTHIVE_CLIPBOARD` overrides the config file.
+    pub fn pinned(&self) -> Result<Option<ClipboardBackend>> {
+        let name = std::env::var("PROMPTHIVE_CLIPBOARD")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .or_else(|| self.backend.clone());
+        match name.as_deref() {
+            None | Some("auto") => Ok(None),
+            Some(name) => name.parse().map(Some),
+        }
+    }
+}
 
 pub struct Clipboard {
-    context: Option<ClipboardContext>,
+    backends: Vec<ClipboardBackend>,
+    last_backend: Option<ClipboardBackend>,
 }
 
 impl Default for Clipboard {
@@ -19,9 +207,41 @@ impl Default for Clipboard {
 
 impl Clipboard {
     pub fn new() -> Self {
-        // Try to initialize clipboard, but don't fail if it's not available
-        let context = ClipboardContext::new().ok();
-        Self { context }
+        // Use the pinned backend if configured, otherwise detect the chain
+        let config = crate::Storage::new()
+            .map(|storage| ClipboardConfig::load(storage.base_dir()))
+            .unwrap_or_default();
+        let backends = match config.pinned() {
+            Ok(Some(backend)) => vec![backend],
+            _ => ClipboardEnvironment::detect().backend_chain(),
+        };
+        Self::with_backends(backends)
+    }
+
+    /// Use an explicit list of backends, tried in order
+    pub fn with_backends(backends: Vec<ClipboardBackend>) -> Self {
+        Self {
+            backends,
+            last_backend: None,
+        }
+    }
+
+    /// Backends that will be tried, in order
+    pub fn backends(&self) -> &[ClipboardBackend] {
+        &self.backends
+    }
+
+    /// Backend that performed the last successful copy
+    pub fn last_backend(&self) -> Option<ClipboardBackend> {
+        self.last_backend
+    }
+
+    /// Suffix naming a non-native backend for confirmations, e.g. " via osc52"
+    pub fn via(&self) -> String {
+        match self.last_backend {
+            Some(backend) if backend != ClipboardBackend::Native => format!(" via {}", backend),
+            _ => String::new(),
+        }
     }
 
     pub fn copy_or_pipe(&mut self, content: &str, is_tty: bool) -> Result<()> {
@@ -36,14 +256,13 @@ impl Clipboard {
     }
 
     pub fn copy_to_clipboard(&mut self, content: &str) -> Result<bool> {
-        if let Some(ref mut ctx) = self.context {
-            match ctx.set_contents(content.to_string()) {
-                Ok(_) => Ok(true), // Clipboard copy successful
-                Err(_) => Ok(false), // Clipboard failed
+        for backend in &self.backends {
+            if backend.copy(content).is_ok() {
+                self.last_backend = Some(*backend);
+                return Ok(true); // Clipboard copy successful
             }
-        } else {
-            Ok(false) // Clipboard not available
         }
+        Ok(false) // No backend worked
     }
 
     pub fn write_to_stdout(&self, content: &str) -> Result<()> {
@@ -56,6 +275,61 @@ impl Clipboard {
     }
 }
 
+/// Whether an executable is on PATH
+fn command_exists(name: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| dir.join(name).is_file())
+    })
+}
+
+fn pipe_to(program: &str, args: &[&str], content: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program))?;
+
+    child
+        .stdin
+        .take()
+        .context("Failed to open stdin")?
+        .write_all(content.as_bytes())?;
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}", program, status));
+    }
+    Ok(())
+}
+
+/// OSC 52 sequence setting the clipboard, wrapped for tmux passthrough if needed
+pub fn osc52_sequence(content: &str, in_tmux: bool) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(content);
+    let sequence = format!("\x1b]52;c;{}\x07", encoded);
+    if in_tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+fn write_osc52(content: &str) -> Result<()> {
+    if content.len() > OSC52_MAX_BYTES {
+        return Err(anyhow!("Content too large for OSC 52"));
+    }
+    let sequence = osc52_sequence(content, std::env::var("TMUX").is_ok());
+
+    // Write to the controlling terminal so piped stdout stays clean
+    match fs::OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(mut tty) => tty.write_all(sequence.as_bytes())?,
+        Err(_) if io::stderr().is_terminal() => io::stderr().write_all(sequence.as_bytes())?,
+        Err(_) => return Err(anyhow!("No terminal for OSC 52")),
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,4 +340,36 @@ mod tests {
         // Should not panic even if clipboard is not available
         // Test passes - clipboard functionality tested
     }
+
+    #[test]
+    fn test_backend_chain_follows_environment() {
+        // Over SSH in tmux without a display: tmux first, then OSC 52
+        let ssh = ClipboardEnvironment {
+            ssh: true,
+            tmux: true,
+            terminal: true,
+            ..Default::default()
+        };
+        let chain = ssh.backend_chain();
+        assert!(!chain.contains(&ClipboardBackend::Native));
+        assert_eq!(chain.last(), Some(&ClipboardBackend::Osc52));
+
+        // Headless without a terminal: nothing to try
+        let headless = ClipboardEnvironment::default();
+        let chain = headless.backend_chain();
+        assert!(!chain.contains(&ClipboardBackend::Osc52));
+        assert!(!chain.contains(&ClipboardBackend::WlCopy));
+
+        assert_eq!("OSC52".parse::<ClipboardBackend>().unwrap(), ClipboardBackend::Osc52);
+        assert!("pbcopy".parse::<ClipboardBackend>().is_err());
+    }
+
+    #[test]
+    fn test_osc52_sequence_and_tmux_passthrough() {
+        assert_eq!(osc52_sequence("hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(
+            osc52_sequence("hi", true),
+            "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\"
+        );
+    }
 }
@@ -0,0 +1,346 @@
+//! Cloud sync state and the sync protocol
+//!
+//! `ph sync` remembers, per prompt, the content hash and cloud revision it
+//! last agreed on with the registry (`.sync/cloud.json`). Comparing both sides
+//! against that base tells a change made here from one made in the cloud, so
+//! one-sided changes fast-forward and only real two-sided edits are conflicts.
+//!
+//! # Protocol
+//!
+//! Requests carry the API key in `X-API-Key`. Prompt bodies are compared with
+//! [`prompt_hash`], which both the client and the server can compute.
+//!
+//! - `GET /api/prompts` lists the account's prompts as a [`CloudPromptList`].
+//!   Its hashes and revisions are enough to classify every prompt, so bodies
+//!   are only fetched for prompts that get pulled.
+//! - `GET /api/prompts/{name}` returns a [`CloudPrompt`], or 404.
+//! - `POST /api/sync/push` takes a [`PushRequest`] and answers with a
+//!   [`PushResponse`] holding one [`PushResult`] per prompt. The server
+//!   decides each prompt with [`decide_push`]: a prompt is accepted when it is
+//!   new, identical, forced, or built on the current revision
+//!   (`base_revision`); anything else is a conflict and is left untouched.
+//!   Accepted changes bump the revision, starting at 1.
+//!
+//! Servers that don't keep revisions still work: pushes also carry the hash
+//! of the cloud copy they were made on top of (`base_hash`), and a prompt
+//! without a revision is compared against that instead.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+
+/// Hash of everything sync carries for a prompt
+///
+/// Surrounding whitespace in the body doesn't count, matching how prompts are
+/// stored. Each tag is terminated on its own so a tag containing a comma
+/// can't pass for two.
+pub fn prompt_hash(content: &str, description: &str, tags: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(description.as_bytes());
+    hasher.update([0]);
+    for tag in tags {
+        hasher.update(tag.as_bytes());
+        hasher.update([0]);
+    }
+    hasher.update([0]);
+    hasher.update(content.trim().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// A prompt as stored in the cloud
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudPrompt {
+    #[serde(default)]
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Bumped by the server on every accepted change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+impl CloudPrompt {
+    pub fn hash(&self) -> String {
+        prompt_hash(&self.content, &self.description, &self.tags)
+    }
+}
+
+impl From<&CloudPrompt> for CloudSummary {
+    fn from(prompt: &CloudPrompt) -> Self {
+        Self {
+            name: prompt.name.clone(),
+            description: prompt.description.clone(),
+            revision: prompt.revision,
+            hash: prompt.hash(),
+            updated_at: prompt.updated_at.clone(),
+        }
+    }
+}
+
+/// One prompt in the `GET /api/prompts` list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudSummary {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u64>,
+    /// [`prompt_hash`] of the stored prompt; empty if the server doesn't send it
+    #[serde(default)]
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// Response to `GET /api/prompts`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudPromptList {
+    pub prompts: Vec<CloudSummary>,
+}
+
+/// One prompt in a push
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushPrompt {
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Cloud revision this change was made on top of; `None` for new prompts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_revision: Option<u64>,
+    /// [`prompt_hash`] of that cloud copy, for servers without revisions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_hash: Option<String>,
+}
+
+impl PushPrompt {
+    pub fn hash(&self) -> String {
+        prompt_hash(&self.content, &self.description, &self.tags)
+    }
+}
+
+/// Body of `POST /api/sync/push`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushRequest {
+    pub prompts: Vec<PushPrompt>,
+    /// Overwrite cloud changes instead of reporting conflicts
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// What the server did with one pushed prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushStatus {
+    Created,
+    Updated,
+    Unchanged,
+    Conflict,
+    Error,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushResult {
+    pub name: String,
+    pub status: PushStatus,
+    /// Revision the prompt is at after the push
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response to `POST /api/sync/push`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushResponse {
+    #[serde(default)]
+    pub success: bool,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub results: Vec<PushResult>,
+}
+
+/// The server's rule for one pushed prompt, given what it currently stores
+pub fn decide_push(current: Option<&CloudPrompt>, incoming: &PushPrompt, force: bool) -> PushStatus {
+    let Some(current) = current else {
+        return PushStatus::Created;
+    };
+    let current_hash = current.hash();
+    let on_top = match current.revision {
+        Some(revision) => incoming.base_revision == Some(revision),
+        None => incoming.base_hash.as_deref() == Some(current_hash.as_str()),
+    };
+    if current_hash == incoming.hash() {
+        PushStatus::Unchanged
+    } else if force || on_top {
+        PushStatus::Updated
+    } else {
+        PushStatus::Conflict
+    }
+}
+
+/// Where a prompt stands relative to its last sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudState {
+    /// Both sides match
+    InSync,
+    /// Only the local copy changed (or only exists locally); push fast-forwards
+    LocalChange,
+    /// Only the cloud copy changed (or only exists there); pull fast-forwards
+    RemoteChange,
+    /// Both sides changed since the last sync and disagree
+    Conflict,
+}
+
+/// Classify a prompt from the base, local and cloud hashes
+///
+/// A missing side counts as "needs a copy" rather than a deletion. Without a
+/// base, two different copies are a conflict: nothing says which one is newer.
+pub fn classify(base: Option<&str>, local: Option<&str>, remote: Option<&str>) -> CloudState {
+    match (local, remote) {
+        (Some(local), Some(remote)) if local == remote => CloudState::InSync,
+        (None, None) => CloudState::InSync,
+        (Some(_), None) => CloudState::LocalChange,
+        (None, Some(_)) => CloudState::RemoteChange,
+        (Some(local), Some(remote)) => match base {
+            Some(base) if remote == base => CloudState::LocalChange,
+            Some(base) if local == base => CloudState::RemoteChange,
+            _ => CloudState::Conflict,
+        },
+    }
+}
+
+/// The state both sides agreed on at the last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncBase {
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u64>,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// Sync bases for every prompt, stored in `.sync/cloud.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncBases {
+    prompts: BTreeMap<String, SyncBase>,
+}
+
+impl SyncBases {
+    pub fn load(storage: &crate::Storage) -> Result<Self> {
+        let path = storage.base_dir().join(".sync").join("cloud.json");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read sync state from {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| "Failed to parse cloud sync state JSON")
+    }
+
+    pub fn save(&self, storage: &crate::Storage) -> Result<()> {
+        let sync_dir = storage.base_dir().join(".sync");
+        fs::create_dir_all(&sync_dir)
+            .with_context(|| format!("Failed to create sync directory {:?}", sync_dir))?;
+        let path = sync_dir.join("cloud.json");
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write sync state to {:?}", path))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SyncBase> {
+        self.prompts.get(name)
+    }
+
+    /// Remember that both sides now hold `hash`
+    pub fn record(&mut self, name: &str, hash: String, revision: Option<u64>) {
+        self.prompts.insert(
+            name.to_string(),
+            SyncBase {
+                hash,
+                revision,
+                synced_at: Utc::now(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_against_base() {
+        use CloudState::*;
+        let cases = [
+            (Some("a"), Some("a"), Some("a"), InSync),
+            (None, Some("b"), Some("b"), InSync),
+            (Some("a"), Some("b"), Some("a"), LocalChange),
+            (Some("a"), Some("a"), Some("b"), RemoteChange),
+            (Some("a"), Some("b"), Some("c"), Conflict),
+            (None, Some("b"), Some("c"), Conflict),
+            (None, Some("b"), None, LocalChange),
+            (Some("a"), None, Some("a"), RemoteChange),
+            (Some("a"), None, None, InSync),
+            (None, None, None, InSync),
+        ];
+        for (base, local, remote, expected) in cases {
+            assert_eq!(classify(base, local, remote), expected, "{:?}", (base, local, remote));
+        }
+    }
+
+    #[test]
+    fn test_decide_push_needs_current_revision() {
+        let stored = CloudPrompt {
+            name: "notes".to_string(),
+            content: "cloud".to_string(),
+            description: String::new(),
+            tags: Vec::new(),
+            revision: Some(3),
+            updated_at: None,
+        };
+        let push = |content: &str, base_revision| PushPrompt {
+            name: "notes".to_string(),
+            content: content.to_string(),
+            description: String::new(),
+            tags: Vec::new(),
+            base_revision,
+            base_hash: None,
+        };
+
+        assert_eq!(decide_push(None, &push("new", None), false), PushStatus::Created);
+        assert_eq!(decide_push(Some(&stored), &push(" cloud\n", Some(1)), false), PushStatus::Unchanged);
+        assert_eq!(decide_push(Some(&stored), &push("local", Some(3)), false), PushStatus::Updated);
+        assert_eq!(decide_push(Some(&stored), &push("local", Some(2)), false), PushStatus::Conflict);
+        assert_eq!(decide_push(Some(&stored), &push("local", None), false), PushStatus::Conflict);
+        assert_eq!(decide_push(Some(&stored), &push("local", Some(2)), true), PushStatus::Updated);
+
+        // Without revisions, the cloud copy the change was built on decides
+        let unversioned = CloudPrompt { revision: None, ..stored.clone() };
+        let on_top_of = |base_hash: &str| PushPrompt {
+            base_hash: Some(base_hash.to_string()),
+            ..push("local", None)
+        };
+        assert_eq!(decide_push(Some(&unversioned), &on_top_of(&stored.hash()), false), PushStatus::Updated);
+        assert_eq!(decide_push(Some(&unversioned), &on_top_of("stale"), false), PushStatus::Conflict);
+        assert_eq!(decide_push(Some(&unversioned), &push("local", None), false), PushStatus::Conflict);
+    }
+
+    #[test]
+    fn test_prompt_hash_keeps_tags_apart() {
+        let tags = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        assert_ne!(prompt_hash("x", "", &tags(&["a,b"])), prompt_hash("x", "", &tags(&["a", "b"])));
+        assert_ne!(prompt_hash("x", "", &tags(&[])), prompt_hash("x", "", &tags(&[""])));
+        assert_eq!(prompt_hash(" x\n", "", &tags(&["a"])), prompt_hash("x", "", &tags(&["a"])));
+    }
+}
@@ -0,0 +1,127 @@
+// Short code commands - persistent aliases for prompts
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::*;
+use std::time::Instant;
+
+use crate::{ShortCodeRegistry, Storage};
+
+#[derive(Subcommand)]
+pub enum AliasCommands {
+    /// Assign a custom short code to a prompt
+    Set {
+        /// Short code to assign
+        code: String,
+        /// Prompt to assign it to (exact name)
+        prompt: String,
+    },
+    /// List all short codes
+    #[command(alias = "ls")]
+    List,
+    /// Remove a short code (a new one is generated on next listing)
+    #[command(alias = "rm")]
+    Remove {
+        /// Short code to remove
+        code: String,
+    },
+}
+
+pub fn handle_alias(
+    storage: &Storage,
+    action: &Option<AliasCommands>,
+    start: Instant,
+) -> Result<()> {
+    match action {
+        Some(AliasCommands::Set { code, prompt }) => handle_alias_set(storage, code, prompt, start),
+        Some(AliasCommands::Remove { code }) => handle_alias_remove(storage, code, start),
+        Some(AliasCommands::List) | None => handle_alias_list(storage, start),
+    }
+}
+
+fn handle_alias_set(storage: &Storage, code: &str, prompt: &str, start: Instant) -> Result<()> {
+    // Assigning a code rewrites the registry, so never act on a fuzzy match
+    let resolved = super::common::resolve_prompt_for_write(storage, prompt, "alias")?;
+
+    if code != resolved && storage.prompt_exists(code) {
+        eprintln!(
+            "Error: '{}' is already a prompt name and cannot be used as a short code",
+            code
+        );
+        std::process::exit(1);
+    }
+
+    let mut registry = ShortCodeRegistry::load_for(storage, &storage.list_prompts()?)?;
+    let previous = registry.code_for(&resolved).map(|c| c.to_string());
+
+    if let Err(e) = registry.assign(code, &resolved, true) {
+        eprintln!("Error: {}", e);
+        eprintln!("Remove it first with: {} alias rm {}", "ph".bold(), code);
+        std::process::exit(1);
+    }
+    registry.save(storage)?;
+
+    match previous {
+        Some(old) if old != code => println!(
+            "✓ {} → {} (was {}) ({}ms)",
+            code.bold(),
+            resolved,
+            old.dimmed(),
+            start.elapsed().as_millis()
+        ),
+        _ => println!(
+            "✓ {} → {} ({}ms)",
+            code.bold(),
+            resolved,
+            start.elapsed().as_millis()
+        ),
+    }
+
+    Ok(())
+}
+
+fn handle_alias_remove(storage: &Storage, code: &str, start: Instant) -> Result<()> {
+    let mut registry = ShortCodeRegistry::load(storage)?;
+
+    match registry.remove_code(code) {
+        Some(entry) => {
+            registry.save(storage)?;
+            println!(
+                "✓ Removed {} from {} ({}ms)",
+                code.bold(),
+                entry.prompt,
+                start.elapsed().as_millis()
+            );
+            Ok(())
+        }
+        None => {
+            eprintln!("Error: No short code '{}'", code);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn handle_alias_list(storage: &Storage, start: Instant) -> Result<()> {
+    let registry = ShortCodeRegistry::load_for(storage, &storage.list_prompts()?)?;
+
+    println!(
+        "🔖 {} ({}ms)\n",
+        "Short codes:".green(),
+        start.elapsed().as_millis()
+    );
+
+    if registry.codes.is_empty() {
+        println!(
+            "  No prompts yet. Create one with: {} new <name>",
+            "ph".bold()
+        );
+        return Ok(());
+    }
+
+    for (code, entry) in &registry.codes {
+        let marker = if entry.custom { " (custom)" } else { "" };
+        println!("  {:<8} {}{}", code.bold(), entry.prompt, marker.dimmed());
+    }
+
+    Ok(())
+}
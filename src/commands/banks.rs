@@ -1,12 +1,13 @@
 use anyhow::{Context, Result};
 use clap::Subcommand;
 use colored::*;
+use serde::Serialize;
 use std::time::Instant;
 // use toml;
 use urlencoding;
 
 use super::common;
-use crate::{RegistryClient, Storage};
+use crate::{IoOptions, RegistryClient, Storage};
 
 #[derive(Subcommand)]
 pub enum BankCommands {
@@ -60,11 +61,36 @@ pub enum BankCommands {
     },
 }
 
+/// One bank in `ph bank list --json`
+#[derive(Serialize)]
+struct BankItem {
+    name: String,
+    description: String,
+    private: bool,
+    prompt_count: u64,
+    member_count: u64,
+    created_at: Option<String>,
+}
+
+impl BankItem {
+    fn from_json(bank: &serde_json::Value) -> Self {
+        Self {
+            name: bank["name"].as_str().unwrap_or("Unknown").to_string(),
+            description: bank["description"].as_str().unwrap_or("").to_string(),
+            private: bank["private"].as_bool().unwrap_or(false),
+            prompt_count: bank["prompt_count"].as_u64().unwrap_or(0),
+            member_count: bank["member_count"].as_u64().unwrap_or(0),
+            created_at: bank["created_at"].as_str().map(str::to_string),
+        }
+    }
+}
+
 // Helper functions moved to common module
 
 pub async fn handle_banks(
     _storage: &Storage,
     action: &Option<BankCommands>,
+    io_options: &IoOptions,
     start: Instant,
 ) -> Result<()> {
     // Check if user has API key (Pro feature required for private banks)
@@ -91,8 +117,10 @@ pub async fn handle_banks(
         Some(BankCommands::Delete { bank, force }) => {
             handle_bank_delete(&client, bank, *force, start).await
         }
-        Some(BankCommands::List { private }) => handle_bank_list(&client, *private, start).await,
-        None => handle_bank_list(&client, false, start).await,
+        Some(BankCommands::List { private }) => {
+            handle_bank_list(&client, *private, io_options, start).await
+        }
+        None => handle_bank_list(&client, false, io_options, start).await,
     }
 }
 
@@ -438,8 +466,13 @@ async fn handle_bank_delete(
 async fn handle_bank_list(
     client: &RegistryClient,
     private_only: bool,
+    io_options: &IoOptions,
     start: Instant,
 ) -> Result<()> {
+    if io_options.is_structured() {
+        return handle_bank_list_structured(client, private_only, io_options).await;
+    }
+
     if private_only {
         println!("🏦 Listing your private banks...");
     } else {
@@ -563,3 +596,31 @@ async fn handle_bank_list(
     );
     Ok(())
 }
+
+async fn handle_bank_list_structured(
+    client: &RegistryClient,
+    private_only: bool,
+    io_options: &IoOptions,
+) -> Result<()> {
+    let query_string = if private_only { "?private=true" } else { "" };
+
+    let data = match client.get(&format!("/api/banks{}", query_string)).await {
+        Ok(response) => response
+            .json::<serde_json::Value>()
+            .await
+            .context("Failed to parse server response")?,
+        Err(e) => {
+            eprintln!("Error: Failed to list banks: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let Some(banks) = data.get("banks").and_then(|b| b.as_array()) else {
+        let message = data["error"].as_str().unwrap_or("response has no 'banks' array");
+        eprintln!("Error: Failed to list banks: {}", message);
+        std::process::exit(1);
+    };
+
+    let items: Vec<BankItem> = banks.iter().map(BankItem::from_json).collect();
+    io_options.emit(&items)
+}
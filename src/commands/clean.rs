@@ -1,4 +1,5 @@
-use crate::{clean::clean_text, IoOptions, Storage};
+use crate::clean::{clean_with, CleanMode, CleanOptions};
+use crate::{IoOptions, Storage};
 use anyhow::Result;
 use std::io::{self, Read};
 use std::time::Instant;
@@ -6,9 +7,19 @@ use std::time::Instant;
 /// Handle the clean command - remove TUI artifacts and formatting from text
 pub fn handle_clean(
     text: Option<&str>,
+    mode: &str,
+    keep_blank_lines: bool,
     io_options: &IoOptions,
     start: Instant,
 ) -> Result<()> {
+    let mode: CleanMode = match mode.parse() {
+        Ok(mode) => mode,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Get input text - either from argument or stdin
     let input = if let Some(text) = text {
         text.to_string()
@@ -19,8 +30,14 @@ pub fn handle_clean(
         buffer
     };
 
-    // Clean the text using the existing clean_text function
-    let cleaned = clean_text(&input);
+    // Clean the text in the requested mode
+    let cleaned = clean_with(
+        &input,
+        &CleanOptions {
+            mode,
+            keep_blank_lines,
+        },
+    );
 
     // Initialize storage for I/O operations
     let storage = Storage::new()?;
@@ -2,12 +2,26 @@ use anyhow::Result;
 use colored::*;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use is_terminal::IsTerminal;
 
 use super::configuration::load_api_key;
-use crate::{MatchResult, Matcher, Prompt, Storage};
+use crate::{
+    exact_matching_enabled, split_version, with_version, MatchResult, Matcher, Prompt,
+    ResolutionPolicy, ShortCodeRegistry, Storage,
+};
 
 /// Resolve prompt name using fuzzy matching, supports bank/prompt syntax
 pub fn resolve_prompt_name(storage: &Storage, query: &str) -> Result<String> {
+    // Resolve the name part of `name@version` and keep the selector
+    if let (base, Some(selector)) = split_version(query) {
+        let resolved = resolve_prompt_name(storage, base)?;
+        return Ok(with_version(&resolved, Some(selector)));
+    }
+
+    if exact_matching_enabled() {
+        return resolve_with_policy(storage, query, ResolutionPolicy::Exact, "use");
+    }
+
     // Check if query contains bank syntax (bank/prompt)
     if query.contains('/') {
         let parts: Vec<&str> = query.splitn(2, '/').collect();
@@ -50,30 +64,7 @@ pub fn resolve_prompt_name(storage: &Storage, query: &str) -> Result<String> {
     }
 
     // Regular prompt resolution (no bank specified)
-    let prompt_names = storage.list_prompts()?;
-    let mut prompts = Vec::new();
-
-    for prompt_name in &prompt_names {
-        if let Ok((metadata, _)) = storage.read_prompt(prompt_name) {
-            prompts.push(Prompt {
-                name: prompt_name.clone(),
-                short_code: Matcher::generate_short_code(
-                    prompt_name,
-                    &prompts
-                        .iter()
-                        .map(|p: &Prompt| p.short_code.clone())
-                        .collect::<Vec<_>>(),
-                ),
-                description: metadata.description,
-                version: metadata.version,
-                created_at: metadata.created_at,
-                updated_at: metadata.updated_at,
-                git_hash: metadata.git_hash,
-            });
-        }
-    }
-
-    let matcher = Matcher::new(prompts);
+    let matcher = Matcher::new(load_match_candidates(storage)?);
     match matcher.find(query) {
         MatchResult::Exact(prompt) => Ok(prompt.name),
         MatchResult::Multiple(suggestions) => {
@@ -95,6 +86,116 @@ pub fn resolve_prompt_name(storage: &Storage, query: &str) -> Result<String> {
     }
 }
 
+/// Resolve a prompt for a command that overwrites or deletes it
+///
+/// Only an exact name is accepted silently. A single fuzzy candidate is offered
+/// for confirmation when running interactively; otherwise the command is refused
+/// and the candidates are listed so it can be re-run with the exact name.
+pub fn resolve_prompt_for_write(storage: &Storage, query: &str, action: &str) -> Result<String> {
+    resolve_with_policy(storage, query, ResolutionPolicy::for_write(), action)
+}
+
+fn resolve_with_policy(
+    storage: &Storage,
+    query: &str,
+    policy: ResolutionPolicy,
+    action: &str,
+) -> Result<String> {
+    // Versions are history; writes always go to the live prompt
+    if let (base, Some(selector)) = split_version(query) {
+        eprintln!(
+            "Error: Cannot {} '{}': versions are read-only (use `ph rollback {} {}` to restore it)",
+            action, query, base, selector
+        );
+        std::process::exit(1);
+    }
+
+    // Fast path: exact names never need the full prompt list
+    if storage.prompt_exists(query) {
+        return Ok(query.to_string());
+    }
+
+    let matcher = Matcher::new(load_match_candidates(storage)?);
+    match matcher.resolve(query, policy) {
+        MatchResult::Exact(prompt) => Ok(prompt.name),
+        MatchResult::Multiple(candidates) => {
+            if policy == ResolutionPolicy::Confirm && candidates.len() == 1 && is_interactive() {
+                let candidate = &candidates[0];
+                eprint!(
+                    "No prompt named '{}'. Did you mean '{}'? Continue with {} [y/N] ",
+                    query,
+                    candidate.name.bold(),
+                    action
+                );
+
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if answer.trim().eq_ignore_ascii_case("y") {
+                    return Ok(candidate.name.clone());
+                }
+
+                eprintln!("Cancelled");
+                std::process::exit(1);
+            }
+
+            eprintln!(
+                "{}: No prompt named '{}'. Refusing to {} a fuzzy match.",
+                "Error".red(),
+                query,
+                action
+            );
+            eprintln!("Did you mean:");
+            for prompt in candidates {
+                eprintln!(
+                    "  {:<12} ({}) - {}",
+                    prompt.name.bold(),
+                    prompt.short_code.dimmed(),
+                    prompt.description
+                );
+            }
+            eprintln!("Re-run with the exact prompt name to continue.");
+            std::process::exit(1);
+        }
+        MatchResult::None => {
+            eprintln!("Error: No prompt found matching '{}'", query);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Build the matcher candidate list for every prompt in storage
+pub fn load_match_candidates(storage: &Storage) -> Result<Vec<Prompt>> {
+    let prompt_names = storage.list_prompts()?;
+    let short_codes = ShortCodeRegistry::load_for(storage, &prompt_names)?;
+    let mut prompts = Vec::new();
+
+    for prompt_name in &prompt_names {
+        if let Ok((metadata, _)) = storage.read_prompt(prompt_name) {
+            prompts.push(Prompt {
+                name: prompt_name.clone(),
+                short_code: short_codes
+                    .code_for(prompt_name)
+                    .unwrap_or_default()
+                    .to_string(),
+                description: metadata.description,
+                version: metadata.version,
+                created_at: metadata.created_at,
+                updated_at: metadata.updated_at,
+                git_hash: metadata.git_hash,
+            });
+        }
+    }
+
+    Ok(prompts)
+}
+
+/// Whether we can ask the user a question (TTY on stdin and stderr, not in tests)
+pub(crate) fn is_interactive() -> bool {
+    std::env::var("PROMPTHIVE_TEST_MODE").is_err()
+        && std::io::stdin().is_terminal()
+        && std::io::stderr().is_terminal()
+}
+
 /// Check if user has valid API key for Pro features
 pub fn require_api_key(feature_name: &str) -> Result<String> {
     match load_api_key() {
@@ -9,7 +9,10 @@ use std::path::{Path, PathBuf};
 use std::time::Instant;
 use toml;
 
-use crate::{Storage, TelemetryCollector};
+use crate::{
+    ClipboardBackend, ClipboardConfig, ClipboardEnvironment, HistoryConfig, HistoryContent,
+    RetentionConfig, Storage, TelemetryCollector,
+};
 
 /// Editor configuration settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +37,110 @@ impl Default for EditorConfig {
     }
 }
 
+/// External diff or merge tool settings (`[difftool]` and `[mergetool]`)
+///
+/// Arguments may name the files with `$LOCAL` (the target), `$REMOTE` (the
+/// other side), `$BASE` (the merge base) and `$MERGED` (the file the merged
+/// result is read back from), as git's difftool and mergetool do. Without
+/// placeholders the files are appended in that order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolConfig {
+    /// Tool command (e.g., "difft", "meld", "nvim")
+    pub command: String,
+    /// Command line arguments
+    pub args: Vec<String>,
+    /// Preset name if using a preset (e.g., "meld", "vimdiff")
+    pub preset: Option<String>,
+}
+
+impl ToolConfig {
+    fn new(preset: &str, command: &str, args: &[&str]) -> Self {
+        Self {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            preset: Some(preset.to_string()),
+        }
+    }
+
+    /// Arguments with placeholders replaced by `files` (`("LOCAL", path)`, ...)
+    pub fn arguments(&self, files: &[(&str, &Path)]) -> Vec<String> {
+        let placeholder = |arg: &str| files.iter().any(|(name, _)| arg.contains(&format!("${}", name)));
+        let mut args: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| {
+                files.iter().fold(arg.clone(), |arg, (name, path)| {
+                    arg.replace(&format!("${}", name), &path.to_string_lossy())
+                })
+            })
+            .collect();
+        if !self.args.iter().any(|arg| placeholder(arg)) {
+            args.extend(files.iter().map(|(_, path)| path.to_string_lossy().to_string()));
+        }
+        args
+    }
+}
+
+pub const DIFFTOOL_PRESETS: &[&str] = &["difftastic", "delta", "meld", "vimdiff", "nvimdiff", "vscode"];
+pub const MERGETOOL_PRESETS: &[&str] = &["meld", "vimdiff", "nvimdiff", "vscode", "kdiff3"];
+
+/// Built-in difftool settings
+pub fn difftool_preset(preset: &str) -> Option<ToolConfig> {
+    let config = match preset {
+        "difftastic" => ToolConfig::new(preset, "difft", &["$LOCAL", "$REMOTE"]),
+        "delta" => ToolConfig::new(preset, "delta", &["$LOCAL", "$REMOTE"]),
+        "meld" => ToolConfig::new(preset, "meld", &["$LOCAL", "$REMOTE"]),
+        "vimdiff" => ToolConfig::new(preset, "vim", &["-R", "-d", "$LOCAL", "$REMOTE"]),
+        "nvimdiff" => ToolConfig::new(preset, "nvim", &["-R", "-d", "$LOCAL", "$REMOTE"]),
+        "vscode" => ToolConfig::new(preset, "code", &["--wait", "--diff", "$LOCAL", "$REMOTE"]),
+        _ => return None,
+    };
+    Some(config)
+}
+
+/// Built-in mergetool settings
+pub fn mergetool_preset(preset: &str) -> Option<ToolConfig> {
+    let vimdiff = ["-f", "-d", "-c", "wincmd J", "$MERGED", "$LOCAL", "$BASE", "$REMOTE"];
+    let config = match preset {
+        "meld" => ToolConfig::new(preset, "meld", &["$LOCAL", "$BASE", "$REMOTE", "--output", "$MERGED"]),
+        "vimdiff" => ToolConfig::new(preset, "vim", &vimdiff),
+        "nvimdiff" => ToolConfig::new(preset, "nvim", &vimdiff),
+        "vscode" => ToolConfig::new(
+            preset,
+            "code",
+            &["--wait", "--merge", "$LOCAL", "$REMOTE", "$BASE", "$MERGED"],
+        ),
+        "kdiff3" => ToolConfig::new(preset, "kdiff3", &["$BASE", "$LOCAL", "$REMOTE", "-o", "$MERGED"]),
+        _ => return None,
+    };
+    Some(config)
+}
+
+/// Preset names for `difftool` or `mergetool`
+pub fn tool_presets(kind: &str) -> &'static [&'static str] {
+    if kind == "difftool" {
+        DIFFTOOL_PRESETS
+    } else {
+        MERGETOOL_PRESETS
+    }
+}
+
+fn tool_preset(kind: &str, preset: &str) -> Option<ToolConfig> {
+    if kind == "difftool" {
+        difftool_preset(preset)
+    } else {
+        mergetool_preset(preset)
+    }
+}
+
+/// Prompt name resolution settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MatchingConfig {
+    /// Require exact prompt names for every command (same as `--exact`)
+    #[serde(default)]
+    pub exact: bool,
+}
+
 pub fn handle_config(
     telemetry: &mut Option<TelemetryCollector>,
     category: &str,
@@ -113,10 +220,189 @@ pub fn handle_config(
                 println!("  Preset: {}", "Custom".yellow());
             }
         }
+        ("matching", "show") => {
+            let config = load_matching_config()?;
+            println!("🎯 Prompt Matching:");
+            if config.exact {
+                println!("  Mode: {} (fuzzy matches are never used)", "exact".green());
+            } else {
+                println!(
+                    "  Mode: {} (delete, rename, merge and rollback still require exact names)",
+                    "fuzzy".cyan()
+                );
+            }
+        }
+        ("matching", "exact") => {
+            save_matching_config(&MatchingConfig { exact: true })?;
+            println!("✓ Exact matching enabled for every command");
+        }
+        ("matching", "fuzzy") => {
+            save_matching_config(&MatchingConfig { exact: false })?;
+            println!("✓ Fuzzy matching enabled for read-only commands");
+        }
+        ("history", "show") => {
+            let config = load_history_config()?;
+            println!("📜 History:");
+            let mode = match config.content {
+                HistoryContent::None => "none (commands and timing only)",
+                HistoryContent::Preview => "preview (first 100 characters of input/output)",
+                HistoryContent::Full => "full (input, variables and rendered output)",
+            };
+            println!("  Content: {}", mode.cyan());
+            println!("  Field cap: {} bytes", config.max_field_bytes);
+            println!(
+                "  Rotation: {} bytes per log, {} rotated logs kept",
+                config.max_file_bytes, config.max_files
+            );
+        }
+        ("history", mode @ ("full" | "preview" | "none")) => {
+            let content = match mode {
+                "full" => HistoryContent::Full,
+                "preview" => HistoryContent::Preview,
+                _ => HistoryContent::None,
+            };
+            let config = HistoryConfig {
+                content,
+                ..load_history_config()?
+            };
+            save_history_config(&config)?;
+            println!("✓ History content set to '{}'", mode.green());
+        }
+        ("clipboard", "show") => {
+            let config = load_clipboard_config()?;
+            let env = ClipboardEnvironment::detect();
+            println!("📋 Clipboard:");
+            let flag = |on: bool| if on { "yes".green() } else { "no".dimmed() };
+            println!(
+                "  Environment: ssh {}, tmux {}, wayland {}, x11 {}, terminal {}",
+                flag(env.ssh),
+                flag(env.tmux),
+                flag(env.wayland),
+                flag(env.x11),
+                flag(env.terminal)
+            );
+            let chain: Vec<&str> = env.backend_chain().iter().map(|b| b.name()).collect();
+            if chain.is_empty() {
+                println!("  Detected chain: {}", "none (output goes to stdout)".yellow());
+            } else {
+                println!("  Detected chain: {}", chain.join(" → ").cyan());
+            }
+            match config.pinned()? {
+                Some(backend) => println!("  Backend: {} (pinned)", backend.name().green()),
+                None => println!("  Backend: {}", "auto".green()),
+            }
+        }
+        ("clipboard", "auto") => {
+            save_clipboard_config(&ClipboardConfig { backend: None })?;
+            println!("✓ Clipboard backend detected automatically");
+        }
+        ("clipboard", backend) => {
+            let backend: ClipboardBackend = match backend.parse() {
+                Ok(backend) => backend,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if !backend.is_available(&ClipboardEnvironment::detect()) {
+                println!(
+                    "{} '{}' does not look usable in this environment",
+                    "⚠️".yellow(),
+                    backend.name()
+                );
+            }
+            save_clipboard_config(&ClipboardConfig {
+                backend: Some(backend.name().to_string()),
+            })?;
+            println!("✓ Clipboard backend pinned to '{}'", backend.name().green());
+        }
+        ("versions", "show") => {
+            let config = load_retention_config()?;
+            println!("🗂️  Version retention:");
+            println!("  Rules: {}", config.describe().cyan());
+            println!(
+                "  Keep last: {}",
+                config.keep_last.map_or("off".to_string(), |n| n.to_string())
+            );
+            println!(
+                "  Max age: {}",
+                config.max_age_days.map_or("off".to_string(), |d| format!("{}d", d))
+            );
+            println!(
+                "  Keep tagged: {}",
+                if config.keep_tagged { "on" } else { "off" }
+            );
+        }
+        ("versions", rule) => {
+            let mut config = load_retention_config()?;
+            let (key, value) = rule.split_once(' ').unwrap_or((rule, ""));
+            if let Err(e) = config.set(key, value.trim()) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            save_retention_config(&config)?;
+            println!("✓ Version retention: {}", config.describe().green());
+        }
         ("editor", "reset") => {
             reset_editor_config()?;
             println!("✓ Editor configuration reset to defaults");
         }
+        (kind @ ("difftool" | "mergetool"), "show") => match load_tool_config(kind)? {
+            Some(config) => {
+                println!("🔧 {}:", kind);
+                println!("  Command: {}", config.command.cyan());
+                if !config.args.is_empty() {
+                    println!("  Arguments: {}", config.args.join(" ").cyan());
+                }
+                match &config.preset {
+                    Some(preset) => println!("  Preset: {}", preset.green()),
+                    None => println!("  Preset: {}", "Custom".yellow()),
+                }
+            }
+            None => println!("🔧 {}: {}", kind, "Not configured".yellow()),
+        },
+        (kind @ ("difftool" | "mergetool"), "reset") => {
+            remove_tool_config(kind)?;
+            println!("✓ {} configuration removed", kind);
+        }
+        (kind @ ("difftool" | "mergetool"), action) => {
+            let presets = tool_presets(kind);
+            if let Some(preset) = action.strip_prefix("preset ") {
+                let Some(config) = tool_preset(kind, preset.trim()) else {
+                    eprintln!(
+                        "Error: Unknown {} preset '{}'. Available presets: {}",
+                        kind,
+                        preset,
+                        presets.join(", ")
+                    );
+                    std::process::exit(1);
+                };
+                save_tool_config(kind, &config)?;
+                println!("✓ {} preset set to '{}'", kind, preset.green());
+            } else if let Some(command_str) = action.strip_prefix("command ") {
+                let cleaned = command_str.trim_matches('"').trim_matches('\'');
+                let parts: Vec<&str> = cleaned.split_whitespace().collect();
+                if parts.is_empty() {
+                    eprintln!("Error: {} command cannot be empty", kind);
+                    std::process::exit(1);
+                }
+                let config = ToolConfig {
+                    command: parts[0].to_string(),
+                    args: parts[1..].iter().map(|s| s.to_string()).collect(),
+                    preset: None,
+                };
+                save_tool_config(kind, &config)?;
+                println!("✓ {} command set to '{}'", kind, cleaned.green());
+            } else {
+                eprintln!("Error: Unknown {} action '{}'", kind, action);
+                eprintln!("Available {} actions:", kind);
+                eprintln!("  show                     # Show current configuration");
+                eprintln!("  reset                    # Remove the configuration");
+                eprintln!("  preset <name>            # Set preset ({})", presets.join(", "));
+                eprintln!("  command \"<cmd> [args]\"   # Set custom command ($LOCAL, $REMOTE, $BASE, $MERGED)");
+                std::process::exit(1);
+            }
+        }
         _ => {
             // Handle editor preset and command specially since they have additional arguments
             if category == "editor" {
@@ -153,6 +439,11 @@ pub fn handle_config(
                 eprintln!("  claude: discover");
                 eprintln!("  env: show");
                 eprintln!("  editor: show, reset, preset <name>, command \"<cmd> [args]\"");
+                eprintln!("  difftool, mergetool: show, reset, preset <name>, command \"<cmd> [args]\"");
+                eprintln!("  matching: show, exact, fuzzy");
+                eprintln!("  history: show, full, preview, none");
+                eprintln!("  clipboard: show, auto, native, wl-copy, xclip, xsel, tmux, osc52");
+                eprintln!("  versions: show, \"keep-last <N|off>\", \"max-age <days|off>\", \"keep-tagged <on|off>\"");
                 std::process::exit(1);
             }
         }
@@ -387,6 +678,134 @@ pub fn save_editor_config(editor_config: &EditorConfig) -> Result<()> {
     save_config(&config)
 }
 
+/// Load prompt matching configuration from config.toml
+pub fn load_matching_config() -> Result<MatchingConfig> {
+    let config = load_config()?;
+
+    if let Some(matching_table) = config.get("matching") {
+        if let Ok(matching_config) = matching_table.clone().try_into::<MatchingConfig>() {
+            return Ok(matching_config);
+        }
+    }
+
+    Ok(MatchingConfig::default())
+}
+
+/// Save prompt matching configuration to config.toml
+pub fn save_matching_config(matching_config: &MatchingConfig) -> Result<()> {
+    let mut config = load_config().unwrap_or_else(|_| toml::Value::Table(Default::default()));
+
+    let matching_toml =
+        toml::Value::try_from(matching_config).context("Failed to serialize matching config")?;
+
+    if let toml::Value::Table(ref mut table) = config {
+        table.insert("matching".to_string(), matching_toml);
+    }
+
+    save_config(&config)
+}
+
+/// Load history configuration from config.toml
+pub fn load_history_config() -> Result<HistoryConfig> {
+    let config = load_config()?;
+
+    if let Some(history_table) = config.get("history") {
+        if let Ok(history_config) = history_table.clone().try_into::<HistoryConfig>() {
+            return Ok(history_config);
+        }
+    }
+
+    Ok(HistoryConfig::default())
+}
+
+/// Save history configuration to config.toml
+pub fn save_history_config(history_config: &HistoryConfig) -> Result<()> {
+    let mut config = load_config().unwrap_or_else(|_| toml::Value::Table(Default::default()));
+
+    let history_toml =
+        toml::Value::try_from(history_config).context("Failed to serialize history config")?;
+
+    if let toml::Value::Table(ref mut table) = config {
+        table.insert("history".to_string(), history_toml);
+    }
+
+    save_config(&config)
+}
+
+/// Load clipboard configuration from config.toml
+pub fn load_clipboard_config() -> Result<ClipboardConfig> {
+    let storage = Storage::new()?;
+    Ok(ClipboardConfig::load(storage.base_dir()))
+}
+
+/// Save clipboard configuration to config.toml
+pub fn save_clipboard_config(clipboard_config: &ClipboardConfig) -> Result<()> {
+    let mut config = load_config().unwrap_or_else(|_| toml::Value::Table(Default::default()));
+
+    let clipboard_toml = toml::Value::try_from(clipboard_config)
+        .context("Failed to serialize clipboard config")?;
+
+    if let toml::Value::Table(ref mut table) = config {
+        table.insert("clipboard".to_string(), clipboard_toml);
+    }
+
+    save_config(&config)
+}
+
+/// Load version retention rules from config.toml
+pub fn load_retention_config() -> Result<RetentionConfig> {
+    let storage = Storage::new()?;
+    Ok(RetentionConfig::load(storage.base_dir()))
+}
+
+/// Save version retention rules to config.toml
+pub fn save_retention_config(retention_config: &RetentionConfig) -> Result<()> {
+    let mut config = load_config().unwrap_or_else(|_| toml::Value::Table(Default::default()));
+
+    let versions_toml = toml::Value::try_from(retention_config)
+        .context("Failed to serialize version retention config")?;
+
+    if let toml::Value::Table(ref mut table) = config {
+        table.insert("versions".to_string(), versions_toml);
+    }
+
+    save_config(&config)
+}
+
+/// Load the `[difftool]` or `[mergetool]` table from config.toml
+pub fn load_tool_config(kind: &str) -> Result<Option<ToolConfig>> {
+    let config = load_config()?;
+
+    if let Some(tool_table) = config.get(kind) {
+        if let Ok(tool_config) = tool_table.clone().try_into::<ToolConfig>() {
+            return Ok(Some(tool_config));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Save the `[difftool]` or `[mergetool]` table to config.toml
+pub fn save_tool_config(kind: &str, tool_config: &ToolConfig) -> Result<()> {
+    let mut config = load_config().unwrap_or_else(|_| toml::Value::Table(Default::default()));
+
+    let tool_toml = toml::Value::try_from(tool_config).context("Failed to serialize tool config")?;
+
+    if let toml::Value::Table(ref mut table) = config {
+        table.insert(kind.to_string(), tool_toml);
+    }
+
+    save_config(&config)
+}
+
+fn remove_tool_config(kind: &str) -> Result<()> {
+    let mut config = load_config()?;
+    if let toml::Value::Table(ref mut table) = config {
+        table.remove(kind);
+    }
+    save_config(&config)
+}
+
 /// Set editor preset (vscode, nvim, vim, nano, zed)
 pub fn set_editor_preset(preset: &str) -> Result<()> {
     let editor_config = match preset {
@@ -531,4 +950,22 @@ mod tests {
         // Just ensure it doesn't panic
         show_environment_variables();
     }
+
+    #[test]
+    fn test_tool_arguments_fill_placeholders() {
+        let (local, remote) = (Path::new("/tmp/a.md"), Path::new("/tmp/b.md"));
+        let files = [("LOCAL", local), ("REMOTE", remote)];
+
+        let vimdiff = difftool_preset("vimdiff").unwrap();
+        assert_eq!(vimdiff.arguments(&files), ["-R", "-d", "/tmp/a.md", "/tmp/b.md"]);
+
+        // Files are appended when a custom command has no placeholders
+        let custom = ToolConfig {
+            command: "mytool".to_string(),
+            args: vec!["--side-by-side".to_string()],
+            preset: None,
+        };
+        assert_eq!(custom.arguments(&files), ["--side-by-side", "/tmp/a.md", "/tmp/b.md"]);
+        assert!(mergetool_preset("difftastic").is_none());
+    }
 }
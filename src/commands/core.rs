@@ -2,7 +2,8 @@
 
 use crate::PromptMetadata;
 use crate::{
-    clean, edit, HistoryEntry, HistoryTracker, IoOptions, MatchResult, Matcher, Prompt, Storage,
+    clean, edit, is_file_reference, split_version, with_version, HistoryEntry, HistoryTracker,
+    IoOptions, MatchResult, Matcher, ResolutionPolicy, ShortCodeRegistry, Storage,
 };
 #[cfg(feature = "compose")]
 use crate::{parse_prompt_list, Composer};
@@ -11,6 +12,7 @@ use chrono;
 use colored::*;
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use is_terminal::IsTerminal;
+use serde::Serialize;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
@@ -19,6 +21,38 @@ use std::time::Instant;
 
 // Core commands like use, new, edit, show, delete, ls, find, rename
 
+/// One prompt in `ph ls --json`
+#[derive(Serialize)]
+struct PromptListItem {
+    name: String,
+    short_code: Option<String>,
+    description: String,
+    tags: Vec<String>,
+}
+
+/// One match in `ph find --json`, best match first
+#[derive(Serialize)]
+struct FindMatch {
+    name: String,
+    description: String,
+    score: i64,
+}
+
+/// A prompt in `ph show --json`
+#[derive(Serialize)]
+struct ShowOutput {
+    name: String,
+    description: String,
+    tags: Vec<String>,
+    version: Option<String>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    path: String,
+    content: String,
+}
+
 #[allow(dead_code)]
 pub fn handle_use(
     storage: &Storage,
@@ -34,38 +68,19 @@ pub fn handle_use(
     let input_text = input.unwrap_or("").to_string();
     use std::io::Read;
 
-    // Use fast resolution path that avoids loading all prompts
+    // Use fast resolution path that avoids loading all prompts; a version
+    // selector (name@v1.0, name@~2) reads history instead of the live file
+    let (query, selector) = split_version(name);
     let resolved_name = match storage.resolve_prompt_fast(name) {
         Ok(name) => name,
         Err(_) => {
             // Fall back to full fuzzy matching only if fast path fails
-            let prompt_names = storage.list_prompts()?;
-            let mut prompts = Vec::new();
-
-            for prompt_name in &prompt_names {
-                if let Ok((metadata, _)) = storage.read_prompt(prompt_name) {
-                    prompts.push(Prompt {
-                        name: prompt_name.clone(),
-                        short_code: Matcher::generate_short_code(
-                            prompt_name,
-                            &prompts
-                                .iter()
-                                .map(|p: &Prompt| p.short_code.clone())
-                                .collect::<Vec<_>>(),
-                        ),
-                        description: metadata.description,
-                        version: metadata.version,
-                        created_at: metadata.created_at,
-                        updated_at: metadata.updated_at,
-                        git_hash: metadata.git_hash,
-                    });
-                }
-            }
+            let prompts = super::common::load_match_candidates(storage)?;
 
             // Find matching prompt
             let matcher = Matcher::new(prompts);
-            match matcher.find(name) {
-                MatchResult::Exact(prompt) => prompt.name,
+            match matcher.resolve(query, ResolutionPolicy::for_read()) {
+                MatchResult::Exact(prompt) => with_version(&prompt.name, selector),
                 _ => {
                     // Record failed history
                     let entry = HistoryEntry::new(
@@ -97,10 +112,7 @@ pub fn handle_use(
 
         for directive_file in directive_files {
             // Check if it's a file path or a prompt name
-            if directive_file.starts_with('~')
-                || directive_file.starts_with('/')
-                || directive_file.contains('.')
-            {
+            if is_file_reference(directive_file) {
                 // It's a file path - expand and read
                 let expanded_path = shellexpand::tilde(directive_file);
                 let path = PathBuf::from(expanded_path.as_ref());
@@ -154,6 +166,14 @@ pub fn handle_use(
 
     // Process template variables and input
     let mut composer = Composer::new(storage.clone());
+    let vars = if history_tracker.stores_full_content() {
+        composer
+            .template_processor()
+            .resolve_variables(&body)
+            .unwrap_or_default()
+    } else {
+        Default::default()
+    };
     body = composer
         .template_processor()
         .process(&body, &input_content)?;
@@ -163,23 +183,40 @@ pub fn handle_use(
         body = edit::edit_content(&body)?;
     }
 
-    // Apply unified I/O using IoOptions with prompt name for sync
-    io_options.apply_unified_io_with_prompt(storage, &body, "Executed prompt result", Some(&resolved_name), start)?;
+    // Handle --redact flag
+    if io_options.redact {
+        let redaction = crate::redaction::redact_prompt(storage, &resolved_name, &body)?;
+        if !redaction.is_clean() && !io_options.quiet {
+            eprintln!(
+                "🔒 Redacted {} secret(s): {}",
+                redaction.secrets.len(),
+                redaction.summary()
+            );
+        }
+        body = redaction.content;
+    }
+
+    // Apply unified I/O using IoOptions with prompt name for sync; old versions never sync back
+    let sync_name = selector.is_none().then_some(resolved_name.as_str());
+    io_options.apply_unified_io_with_prompt(storage, &body, "Executed prompt result", sync_name, start)?;
 
     // Record successful history
     let entry = HistoryEntry::new(
         command,
-        &input_text,
+        &input_content,
         &body,
         true,
         start.elapsed().as_millis(),
-    );
+    )
+    .with_prompt(&resolved_name)
+    .with_vars(vars);
     let _ = history_tracker.record(entry); // Don't fail if history fails
 
     Ok(())
 }
 
 #[allow(dead_code)]
+#[allow(clippy::too_many_arguments)]
 pub fn handle_new(
     storage: &Storage,
     arg1: &str,
@@ -272,6 +309,7 @@ pub fn handle_new(
         version: None,
         git_hash: None,
         parent_version: None,
+        source: None,
     };
 
     let body = if prompt_content.is_empty() {
@@ -282,13 +320,28 @@ pub fn handle_new(
 
     storage.write_prompt(&prompt_name, &metadata, &body)?;
 
+    if !io_options.quiet {
+        super::similarity::warn_near_duplicates(storage, &prompt_name, &body);
+    }
+
+    // Assign a persistent short code right away so it never shifts later
+    let short_codes = ShortCodeRegistry::load_for(storage, &storage.list_prompts()?)?;
+
     if !io_options.quiet {
         // Linear-style success message - clean and minimal
-        println!(
-            "✓ Created {} ({}ms)",
-            prompt_name,
-            start.elapsed().as_millis()
-        );
+        match short_codes.code_for(&prompt_name) {
+            Some(code) => println!(
+                "✓ Created {} [{}] ({}ms)",
+                prompt_name,
+                code,
+                start.elapsed().as_millis()
+            ),
+            None => println!(
+                "✓ Created {} ({}ms)",
+                prompt_name,
+                start.elapsed().as_millis()
+            ),
+        }
     }
 
     // If content provided, apply unified I/O with prompt name for sync
@@ -317,7 +370,7 @@ pub fn handle_new(
             // Smart default: ./prompt-name.md
             let file_name = if prompt_name.starts_with('@') {
                 // Strip team prefix: @team/api-design -> api-design.md
-                prompt_name.split('/').last().unwrap_or(&prompt_name)
+                prompt_name.split('/').next_back().unwrap_or(&prompt_name)
             } else {
                 &prompt_name
             };
@@ -334,7 +387,7 @@ pub fn handle_new(
             }
         };
         
-        match sync_manager.sync_prompt(&prompt_name, Some(local_path.clone())) {
+        match sync_manager.sync_prompt(&prompt_name, Some(local_path.clone()), false) {
             Ok(created_path) => {
                 if !io_options.quiet {
                     println!("🔄 Created bidirectional sync:");
@@ -393,11 +446,15 @@ pub fn handle_edit(storage: &Storage, name: &str, start: Instant) -> Result<()>
         start.elapsed().as_millis()
     );
 
-    // Launch editor
+    // Launch editor, recording revisions on both sides of the edit
+    storage.snapshot_revision(&resolved_name)?;
     Command::new(&editor)
         .arg(&prompt_path)
         .status()
         .with_context(|| format!("Failed to launch editor '{}'", editor))?;
+    if prompt_path.exists() {
+        storage.record_revision(&resolved_name)?;
+    }
 
     Ok(())
 }
@@ -411,37 +468,30 @@ pub fn handle_show(
     start: Instant,
 ) -> Result<()> {
     // Get all prompts
-    let prompt_names = storage.list_prompts()?;
-    let mut prompts = Vec::new();
-
-    for prompt_name in &prompt_names {
-        if let Ok((metadata, _)) = storage.read_prompt(prompt_name) {
-            prompts.push(Prompt {
-                name: prompt_name.clone(),
-                short_code: Matcher::generate_short_code(
-                    prompt_name,
-                    &prompts
-                        .iter()
-                        .map(|p: &Prompt| p.short_code.clone())
-                        .collect::<Vec<_>>(),
-                ),
-                description: metadata.description,
-                version: metadata.version,
-                created_at: metadata.created_at,
-                updated_at: metadata.updated_at,
-                git_hash: metadata.git_hash,
-            });
-        }
-    }
+    let prompts = super::common::load_match_candidates(storage)?;
 
-    // Find matching prompt
+    // Find matching prompt, keeping any version selector
+    let (query, selector) = split_version(name);
     let matcher = Matcher::new(prompts);
-    let result = matcher.find(name);
+    let result = matcher.resolve(query, ResolutionPolicy::for_read());
 
     match result {
-        MatchResult::Exact(prompt) => {
+        MatchResult::Exact(mut prompt) => {
+            if selector.is_some() && edit {
+                eprintln!("Error: Cannot edit '{}': versions are read-only", name);
+                std::process::exit(1);
+            }
+            prompt.name = with_version(&prompt.name, selector);
+
             // Read and display the prompt content
-            let (metadata, body) = storage.read_prompt(&prompt.name)?;
+            let (metadata, body) = match storage.read_prompt(&prompt.name) {
+                Ok(prompt) => prompt,
+                Err(e) if selector.is_some() => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                Err(e) => return Err(e),
+            };
 
             let content = body;
 
@@ -460,6 +510,18 @@ pub fn handle_show(
                 // Re-read the content after editing
                 let (metadata, body) = storage.read_prompt(&prompt.name)?;
                 io_options.apply_display_io(&body, &prompt.name, &metadata.description, start)?;
+            } else if io_options.is_structured() {
+                io_options.emit(&ShowOutput {
+                    path: storage.prompt_path(&prompt.name).to_string_lossy().to_string(),
+                    name: prompt.name,
+                    description: metadata.description,
+                    tags: metadata.tags.unwrap_or_default(),
+                    version: metadata.version,
+                    created_at: metadata.created_at,
+                    updated_at: metadata.updated_at,
+                    source: metadata.source,
+                    content,
+                })?;
             } else {
                 io_options.apply_display_io(&content, &prompt.name, &metadata.description, start)?;
             }
@@ -475,8 +537,8 @@ pub fn handle_show(
 
 #[allow(dead_code)]
 pub fn handle_delete(storage: &Storage, name: &str, start: Instant) -> Result<()> {
-    // Use fuzzy matching to resolve the prompt name
-    let resolved_name = resolve_prompt_name(storage, name)?;
+    // Deleting requires an exact name (or a confirmed fuzzy candidate)
+    let resolved_name = super::common::resolve_prompt_for_write(storage, name, "delete")?;
     let prompt_path = storage.prompt_path(&resolved_name);
 
     // Read the prompt to show description
@@ -508,6 +570,12 @@ pub fn handle_delete(storage: &Storage, name: &str, start: Instant) -> Result<()
     // Delete the file
     fs::remove_file(&prompt_path)?;
 
+    // Release the prompt's short code
+    let mut short_codes = ShortCodeRegistry::load(storage).unwrap_or_default();
+    if short_codes.remove_prompt(&resolved_name).is_some() {
+        short_codes.save(storage)?;
+    }
+
     // Linear-style deletion confirmation
     println!(
         "✓ Deleted {} ({}ms)",
@@ -521,6 +589,23 @@ pub fn handle_delete(storage: &Storage, name: &str, start: Instant) -> Result<()
 #[allow(dead_code)]
 pub fn handle_ls(storage: &Storage, io_options: &IoOptions, start: Instant) -> Result<()> {
     let prompts = storage.list_prompts()?;
+    let short_codes = ShortCodeRegistry::load_for(storage, &prompts)?;
+
+    if io_options.is_structured() {
+        let items: Vec<PromptListItem> = prompts
+            .iter()
+            .filter_map(|name| {
+                let (metadata, _) = storage.read_prompt(name).ok()?;
+                Some(PromptListItem {
+                    name: name.clone(),
+                    short_code: short_codes.code_for(name).map(str::to_string),
+                    description: metadata.description,
+                    tags: metadata.tags.unwrap_or_default(),
+                })
+            })
+            .collect();
+        return io_options.emit(&items);
+    }
 
     // Build content string for I/O operations
     let mut content = String::new();
@@ -530,7 +615,11 @@ pub fn handle_ls(storage: &Storage, io_options: &IoOptions, start: Instant) -> R
     } else {
         for prompt_name in &prompts {
             if let Ok((metadata, _)) = storage.read_prompt(prompt_name) {
-                content.push_str(&format!("{:<20} - {}\n", prompt_name, metadata.description));
+                let code = short_codes.code_for(prompt_name).unwrap_or("");
+                content.push_str(&format!(
+                    "{:<20} {:<6} - {}\n",
+                    prompt_name, code, metadata.description
+                ));
             }
         }
         content = content.trim_end().to_string(); // Remove trailing newline
@@ -555,7 +644,13 @@ pub fn handle_ls(storage: &Storage, io_options: &IoOptions, start: Instant) -> R
         } else {
             for prompt_name in prompts {
                 if let Ok((metadata, _)) = storage.read_prompt(&prompt_name) {
-                    println!("  {:<20} - {}", prompt_name.bold(), metadata.description);
+                    let code = short_codes.code_for(&prompt_name).unwrap_or("");
+                    println!(
+                        "  {:<20} {:<6} - {}",
+                        prompt_name.bold(),
+                        code.dimmed(),
+                        metadata.description
+                    );
                 }
             }
         }
@@ -571,29 +666,7 @@ pub fn handle_find(
     io_options: &IoOptions,
     start: Instant,
 ) -> Result<()> {
-    let prompt_names = storage.list_prompts()?;
-    let mut prompts = Vec::new();
-
-    // Build prompt list with metadata
-    for prompt_name in &prompt_names {
-        if let Ok((metadata, _)) = storage.read_prompt(prompt_name) {
-            prompts.push(Prompt {
-                name: prompt_name.clone(),
-                short_code: Matcher::generate_short_code(
-                    prompt_name,
-                    &prompts
-                        .iter()
-                        .map(|p: &Prompt| p.short_code.clone())
-                        .collect::<Vec<_>>(),
-                ),
-                description: metadata.description,
-                version: metadata.version,
-                created_at: metadata.created_at,
-                updated_at: metadata.updated_at,
-                git_hash: metadata.git_hash,
-            });
-        }
-    }
+    let prompts = super::common::load_match_candidates(storage)?;
 
     // Use fuzzy matcher for search
     let fuzzy = SkimMatcherV2::default();
@@ -617,7 +690,19 @@ pub fn handle_find(
         .collect();
 
     // Sort by score (highest first)
-    matches.sort_by(|a, b| b.1.cmp(&a.1));
+    matches.sort_by_key(|m| std::cmp::Reverse(m.1));
+
+    if io_options.is_structured() {
+        let items: Vec<FindMatch> = matches
+            .iter()
+            .map(|(prompt, score)| FindMatch {
+                name: prompt.name.clone(),
+                description: prompt.description.clone(),
+                score: *score,
+            })
+            .collect();
+        return io_options.emit(&items);
+    }
 
     // Build content string for I/O operations
     let content = if matches.is_empty() {
@@ -661,8 +746,8 @@ pub fn handle_rename(
     new_name: &str,
     start: Instant,
 ) -> Result<()> {
-    // Resolve old name with fuzzy matching
-    let resolved_old = resolve_prompt_name(storage, old_name)?;
+    // Renaming requires an exact name (or a confirmed fuzzy candidate)
+    let resolved_old = super::common::resolve_prompt_for_write(storage, old_name, "rename")?;
 
     // Check if new name already exists
     if storage.prompt_exists(new_name) {
@@ -673,12 +758,24 @@ pub fn handle_rename(
     // Read the old prompt
     let (metadata, body) = storage.read_prompt(&resolved_old)?;
 
-    // Write to new location
+    // Write to new location, taking the version history along
+    storage.move_revisions(&resolved_old, new_name)?;
     storage.write_prompt(new_name, &metadata, &body)?;
 
     // Delete old prompt
     storage.delete_prompt(&resolved_old)?;
 
+    // Keep the short code with the renamed prompt
+    let mut short_codes = ShortCodeRegistry::load(storage).unwrap_or_default();
+    short_codes.rename_prompt(&resolved_old, new_name);
+    short_codes.save(storage)?;
+
+    // Redaction false positives follow the prompt too
+    let mut allow_list = crate::RedactionAllowList::load(storage).unwrap_or_default();
+    if allow_list.rename_prompt(&resolved_old, new_name) {
+        allow_list.save(storage)?;
+    }
+
     // Linear-style rename confirmation
     println!(
         "✓ Renamed {} → {} ({}ms)",
@@ -718,91 +815,7 @@ fn generate_prompt_name(content: &str) -> String {
 // Helper function to resolve a prompt name using fuzzy matching
 #[allow(dead_code)]
 pub fn resolve_prompt_name(storage: &Storage, query: &str) -> Result<String> {
-    // Check if query contains bank syntax (bank/prompt)
-    if query.contains('/') {
-        let parts: Vec<&str> = query.splitn(2, '/').collect();
-        if parts.len() == 2 {
-            let bank = parts[0];
-            let prompt = parts[1];
-
-            // First try exact match
-            let bank_prompt = format!("{}/{}", bank, prompt);
-            if storage.prompt_exists(&bank_prompt) {
-                return Ok(bank_prompt);
-            }
-
-            // Then try fuzzy matching within the bank
-            let bank_prompts = storage.list_bank_prompts(bank)?;
-            if !bank_prompts.is_empty() {
-                let fuzzy = SkimMatcherV2::default();
-                let mut best_match = None;
-                let mut best_score = 0;
-
-                for bank_prompt_name in &bank_prompts {
-                    // Extract just the prompt name part for matching
-                    let prompt_part = bank_prompt_name
-                        .split('/')
-                        .next_back()
-                        .unwrap_or(bank_prompt_name);
-                    if let Some(score) = fuzzy.fuzzy_match(prompt_part, prompt) {
-                        if score > best_score {
-                            best_score = score;
-                            best_match = Some(bank_prompt_name.clone());
-                        }
-                    }
-                }
-
-                if let Some(matched) = best_match {
-                    return Ok(matched);
-                }
-            }
-        }
-    }
-
-    // Regular prompt resolution (no bank specified)
-    let prompt_names = storage.list_prompts()?;
-    let mut prompts = Vec::new();
-
-    for prompt_name in &prompt_names {
-        if let Ok((metadata, _)) = storage.read_prompt(prompt_name) {
-            prompts.push(Prompt {
-                name: prompt_name.clone(),
-                short_code: Matcher::generate_short_code(
-                    prompt_name,
-                    &prompts
-                        .iter()
-                        .map(|p: &Prompt| p.short_code.clone())
-                        .collect::<Vec<_>>(),
-                ),
-                description: metadata.description,
-                version: metadata.version,
-                created_at: metadata.created_at,
-                updated_at: metadata.updated_at,
-                git_hash: metadata.git_hash,
-            });
-        }
-    }
-
-    let matcher = Matcher::new(prompts);
-    match matcher.find(query) {
-        MatchResult::Exact(prompt) => Ok(prompt.name),
-        MatchResult::Multiple(suggestions) => {
-            eprintln!("Error: Multiple matches. Did you mean:");
-            for prompt in suggestions {
-                eprintln!(
-                    "  {:<12} ({}) - {}",
-                    prompt.name.bold(),
-                    prompt.short_code.dimmed(),
-                    prompt.description
-                );
-            }
-            std::process::exit(1);
-        }
-        MatchResult::None => {
-            eprintln!("Error: No prompt found matching '{}'", query);
-            std::process::exit(1);
-        }
-    }
+    super::common::resolve_prompt_name(storage, query)
 }
 
 #[cfg(feature = "import")]
@@ -823,6 +836,11 @@ pub fn handle_import(
         importer.import_from_path_enhanced(path, custom_name, force, version, skip, update)?;
 
     result.display();
+    for name in result.imported() {
+        if let Ok((_, body)) = storage.read_prompt(name) {
+            super::similarity::warn_near_duplicates(storage, name, &body);
+        }
+    }
     println!(
         "\n⏱️  {} ({}ms)",
         result.summary().green(),
@@ -1,43 +1,249 @@
 // Diff and merge commands - extracted from main.rs
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
-use super::common::resolve_prompt_name;
-use crate::Storage;
+use super::common::{resolve_prompt_for_write, resolve_prompt_name};
+use super::configuration::{load_tool_config, tool_presets};
+use crate::merge::{has_conflict_markers, merge2, merge3, MergeLabels, MergeResult};
+use crate::patch::{self, apply_hunks, diff_words, parse_patch, Hunk, HunkLine, Patch, WordSpan};
+use crate::revisions::content_hash;
+use crate::{edit, is_file_reference, split_version, PromptMetadata, Storage};
 
+/// One side of a diff: a prompt (possibly a version of one) or a file
+struct DiffSide {
+    label: String,
+    body: String,
+    /// Frontmatter fields, including keys PromptHive does not know
+    fields: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl DiffSide {
+    fn load(storage: &Storage, query: &str) -> Result<Self> {
+        let path = PathBuf::from(shellexpand::tilde(query).as_ref());
+        if is_file_reference(query) && path.is_file() {
+            let raw = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let (_, body) = storage.parse_prompt_content(&raw)?;
+            return Ok(DiffSide {
+                label: query.to_string(),
+                body,
+                fields: frontmatter_fields(&raw),
+            });
+        }
+
+        let name = resolve_prompt_name(storage, query)?;
+        let (_, body) = storage.read_prompt(&name)?;
+        let raw = match split_version(&name) {
+            (base, Some(selector)) => storage
+                .read_revision(base, selector)?
+                .map(|(_, content)| content)
+                .unwrap_or_default(),
+            (base, None) => fs::read_to_string(storage.prompt_path(base))?,
+        };
+        Ok(DiffSide {
+            label: name,
+            body,
+            fields: frontmatter_fields(&raw),
+        })
+    }
+}
+
+/// Frontmatter as a map, read the way [`Storage`] finds it
+fn frontmatter_fields(raw: &str) -> BTreeMap<String, serde_yaml::Value> {
+    let lines: Vec<&str> = raw.lines().collect();
+    let end = lines.iter().skip(1).position(|&line| line == "---");
+    let (Some(&"---"), Some(end)) = (lines.first(), end) else {
+        return BTreeMap::new();
+    };
+    let mapping: serde_yaml::Mapping =
+        serde_yaml::from_str(&lines[1..=end].join("\n")).unwrap_or_default();
+    mapping
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.as_str()?.to_string(), value)))
+        // Bookkeeping that differs between any two prompts
+        .filter(|(key, _)| !matches!(key.as_str(), "id" | "created_at" | "updated_at"))
+        .collect()
+}
+
+/// How one frontmatter field differs
+#[derive(Debug, PartialEq)]
+enum FieldChange {
+    Added(String),
+    Removed(String),
+    Changed(String, String),
+    /// List fields such as tags, compared by their items
+    Items {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
+fn yaml_display(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::String(text) => text.clone(),
+        serde_yaml::Value::Sequence(items) => {
+            format!("[{}]", items.iter().map(yaml_display).collect::<Vec<_>>().join(", "))
+        }
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string(),
+    }
+}
+
+fn metadata_changes(
+    old: &BTreeMap<String, serde_yaml::Value>,
+    new: &BTreeMap<String, serde_yaml::Value>,
+) -> Vec<(String, FieldChange)> {
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let change = match (old.get(key), new.get(key)) {
+                (Some(a), Some(b)) if a == b => return None,
+                (Some(serde_yaml::Value::Sequence(a)), Some(serde_yaml::Value::Sequence(b))) => {
+                    FieldChange::Items {
+                        added: b.iter().filter(|v| !a.contains(v)).map(yaml_display).collect(),
+                        removed: a.iter().filter(|v| !b.contains(v)).map(yaml_display).collect(),
+                    }
+                }
+                (Some(a), Some(b)) => FieldChange::Changed(yaml_display(a), yaml_display(b)),
+                (Some(a), None) => FieldChange::Removed(yaml_display(a)),
+                (None, Some(b)) => FieldChange::Added(yaml_display(b)),
+                (None, None) => return None,
+            };
+            Some((key.clone(), change))
+        })
+        .collect()
+}
+
+/// Frontmatter differences as a section ahead of the body diff
+fn render_metadata_changes(changes: &[(String, FieldChange)], color: bool) -> String {
+    let paint = |text: String, painted: ColoredString| {
+        if color {
+            painted.to_string()
+        } else {
+            text
+        }
+    };
+    let mut result = paint("@@ metadata @@".to_string(), "@@ metadata @@".cyan());
+    result.push('\n');
+    if changes.is_empty() {
+        result.push_str("  (no differences)\n");
+    }
+    for (key, change) in changes {
+        let line = match change {
+            FieldChange::Added(value) => {
+                let line = format!("+ {}: {}", key, value);
+                paint(line.clone(), line.green())
+            }
+            FieldChange::Removed(value) => {
+                let line = format!("- {}: {}", key, value);
+                paint(line.clone(), line.red())
+            }
+            FieldChange::Changed(old, new) => format!(
+                "  {}: {} → {}",
+                key,
+                paint(old.clone(), old.red()),
+                paint(new.clone(), new.green())
+            ),
+            FieldChange::Items { added, removed } => {
+                let mut items: Vec<String> = added
+                    .iter()
+                    .map(|item| format!("+{}", item))
+                    .map(|item| paint(item.clone(), item.green()))
+                    .collect();
+                items.extend(
+                    removed
+                        .iter()
+                        .map(|item| format!("-{}", item))
+                        .map(|item| paint(item.clone(), item.red())),
+                );
+                format!("  {}: {}", key, items.join(" "))
+            }
+        };
+        result.push_str(&line);
+        result.push('\n');
+    }
+    result
+}
+
+/// Write one side of a diff or merge for an external tool
+fn tool_file(dir: &Path, name: &str, side: &str, content: &str) -> Result<PathBuf> {
+    let stem = Path::new(name)
+        .file_stem()
+        .map_or_else(|| name.to_string(), |stem| stem.to_string_lossy().to_string());
+    let path = dir.join(format!("{}.{}.md", stem, side));
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Run the configured difftool or mergetool on `files`
+fn run_tool(kind: &str, files: &[(&str, &Path)]) -> Result<std::process::ExitStatus> {
+    let Some(config) = load_tool_config(kind)? else {
+        eprintln!("Error: No {} configured", kind);
+        eprintln!(
+            "Set one with `ph config {} \"preset <name>\"` ({}) or `ph config {} \"command <cmd> [args]\"`",
+            kind,
+            tool_presets(kind).join(", "),
+            kind
+        );
+        std::process::exit(1);
+    };
+    std::process::Command::new(&config.command)
+        .args(config.arguments(files))
+        .status()
+        .with_context(|| format!("Failed to run {} '{}'", kind, config.command))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_diff(
     storage: &Storage,
     prompt1: &str,
     prompt2: &str,
     format: &str,
     context: usize,
+    word_diff: bool,
+    metadata: bool,
+    stat: bool,
+    tool: bool,
     output: Option<&str>,
     start: Instant,
 ) -> Result<()> {
-    // Resolve prompt names with fuzzy matching
-    let resolved_prompt1 = resolve_prompt_name(storage, prompt1)?;
-    let resolved_prompt2 = resolve_prompt_name(storage, prompt2)?;
+    // Resolve prompt names with fuzzy matching, or read files
+    let side1 = DiffSide::load(storage, prompt1)?;
+    let side2 = DiffSide::load(storage, prompt2)?;
 
-    // Read prompt contents
-    let (_, content1) = storage.read_prompt(&resolved_prompt1)?;
-    let (_, content2) = storage.read_prompt(&resolved_prompt2)?;
+    if tool {
+        // Diff tools exit non-zero when the files differ, so the status is not an error
+        let dir = tempfile::tempdir()?;
+        let local = tool_file(dir.path(), &side1.label, "LOCAL", &side1.body)?;
+        let remote = tool_file(dir.path(), &side2.label, "REMOTE", &side2.body)?;
+        run_tool("difftool", &[("LOCAL", &local), ("REMOTE", &remote)])?;
+        println!("⏱️  Diff completed ({}ms)", start.elapsed().as_millis());
+        return Ok(());
+    }
+    let (name1, content1) = (side1.label.as_str(), side1.body.as_str());
+    let (name2, content2) = (side2.label.as_str(), side2.body.as_str());
+    // Plain markers when writing a file or when colour is off (e.g. piped output)
+    let color = output.is_none() && colored::control::SHOULD_COLORIZE.should_colorize();
+    let changes = metadata.then(|| metadata_changes(&side1.fields, &side2.fields));
 
     // Generate diff based on format
     let diff_output = match format {
-        "unified" => generate_unified_diff(
-            &resolved_prompt1,
-            &content1,
-            &resolved_prompt2,
-            &content2,
-            context,
-        ),
-        "side-by-side" | "side" => {
-            generate_side_by_side_diff(&resolved_prompt1, &content1, &resolved_prompt2, &content2)
-        }
-        "brief" => generate_brief_diff(&resolved_prompt1, &content1, &resolved_prompt2, &content2),
+        _ if stat => generate_stat(name1, content1, name2, content2, changes.as_deref()),
+        "unified" if word_diff => generate_word_diff(name1, content1, name2, content2, context, color),
+        // Saved diffs stay free of colour codes so `ph apply` can read them back
+        "unified" if !color => Patch::between(name1, content1, name2, content2, context).render(),
+        "unified" => generate_unified_diff(name1, content1, name2, content2, context),
+        "side-by-side" | "side" => generate_side_by_side_diff(name1, content1, name2, content2),
+        "brief" => generate_brief_diff(name1, content1, name2, content2),
         _ => {
             eprintln!(
                 "Error: Unsupported diff format '{}'. Supported: unified, side-by-side, brief",
@@ -46,6 +252,10 @@ pub fn handle_diff(
             std::process::exit(1);
         }
     };
+    let diff_output = match &changes {
+        Some(changes) if !stat => format!("{}{}", render_metadata_changes(changes, color), diff_output),
+        _ => diff_output,
+    };
 
     // Output result
     if let Some(output_file) = output {
@@ -63,51 +273,350 @@ pub fn handle_diff(
     Ok(())
 }
 
+/// A merge stopped on conflicts, waiting for `ph merge --continue` or `--abort`
+#[derive(Serialize, Deserialize)]
+struct MergeState {
+    source: String,
+    target: String,
+    /// Hash of the target file when the merge started
+    target_hash: String,
+    /// Frontmatter the merged prompt is written with
+    metadata: PromptMetadata,
+    conflicts: usize,
+    backup: bool,
+}
+
+/// Where an unfinished merge keeps its state and conflicted body
+fn merge_dir(storage: &Storage) -> PathBuf {
+    storage.base_dir().join("merge")
+}
+
+fn load_merge_state(storage: &Storage) -> Result<Option<MergeState>> {
+    let path = merge_dir(storage).join("state.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let state = serde_json::from_str(&fs::read_to_string(&path)?)
+        .with_context(|| format!("Corrupt merge state in {}", path.display()))?;
+    Ok(Some(state))
+}
+
+/// The version both prompts descend from
+struct MergeBase {
+    label: String,
+    body: String,
+}
+
+/// Find a common ancestor of `source` and `target`
+///
+/// An explicit `parent_version` on either side wins when the other prompt has
+/// that tag. Otherwise the newest revision of the target whose body also
+/// appears in the source's history (up to a selected version) is used.
+fn find_merge_base(
+    storage: &Storage,
+    source: &str,
+    source_metadata: &PromptMetadata,
+    target: &str,
+    target_metadata: &PromptMetadata,
+) -> Result<Option<MergeBase>> {
+    let (source_name, source_selector) = split_version(source);
+    let source_bodies = history_bodies(storage, source_name, source_selector)?;
+    let target_bodies = history_bodies(storage, target, None)?;
+
+    // A parent_version names a version of the other prompt only when it says
+    // so (`name@tag`); a bare tag is usually the prompt's own previous version,
+    // so it only counts if that body is also in the prompt's own history
+    let lineage = [
+        (target, source_metadata.parent_version.as_deref(), &source_bodies),
+        (source_name, target_metadata.parent_version.as_deref(), &target_bodies),
+    ];
+    for (name, parent, own_bodies) in lineage {
+        let Some(parent) = parent else {
+            continue;
+        };
+        let (parent_name, parent_selector) = split_version(parent);
+        let qualified = match parent_selector {
+            Some(selector) if parent_name == name => Some(selector),
+            Some(_) => continue,
+            None => None,
+        };
+        if let Some((_, content)) = storage.read_revision(name, qualified.unwrap_or(parent))? {
+            let (_, body) = storage.parse_prompt_content(&content)?;
+            if qualified.is_some() || own_bodies.contains(&body) {
+                return Ok(Some(MergeBase {
+                    label: format!("{}@{}", name, qualified.unwrap_or(parent)),
+                    body,
+                }));
+            }
+        }
+    }
+
+    let store = storage.revisions();
+    for revision in store.log(target)?.iter().rev() {
+        let (_, body) = storage.parse_prompt_content(&store.read_blob(&revision.hash)?)?;
+        if source_bodies.contains(&body) {
+            return Ok(Some(MergeBase {
+                label: format!("{}@{}", target, revision.label()),
+                body,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Bodies of every revision of a prompt, up to `selector` when given
+fn history_bodies(storage: &Storage, name: &str, selector: Option<&str>) -> Result<HashSet<String>> {
+    let store = storage.revisions();
+    let mut log = store.log(name)?;
+    if let Some(selector) = selector {
+        if let Some(selected) = store.find(name, selector)? {
+            log.retain(|revision| revision.number <= selected.number);
+        }
+    }
+    let mut bodies = HashSet::new();
+    for revision in &log {
+        let (_, body) = storage.parse_prompt_content(&store.read_blob(&revision.hash)?)?;
+        bodies.insert(body);
+    }
+    Ok(bodies)
+}
+
+/// Target metadata with the union of both prompts' tags
+fn merge_metadata(ours: &PromptMetadata, theirs: &PromptMetadata) -> PromptMetadata {
+    let mut merged = ours.clone();
+    let mut tags = ours.tags.clone().unwrap_or_default();
+    for tag in theirs.tags.iter().flatten() {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    merged.tags = (!tags.is_empty()).then_some(tags);
+    merged
+}
+
+/// Tag the target's current revision so the merge can be undone with rollback
+fn backup_target(storage: &Storage, target: &str) -> Result<()> {
+    storage.snapshot_revision(target)?;
+    let store = storage.revisions();
+    if let Some(head) = store.head(target)? {
+        let backup_tag = format!("backup-{}", chrono::Utc::now().timestamp());
+        store.set_tag(target, &backup_tag, head.number, Some("Pre-merge backup"))?;
+        println!(
+            "💾 Backup created: {}",
+            format!("{}@{}", target, backup_tag).dimmed()
+        );
+    }
+    Ok(())
+}
+
+fn target_hash(storage: &Storage, target: &str) -> Result<String> {
+    Ok(content_hash(&fs::read_to_string(storage.prompt_path(target))?))
+}
+
+/// Options for `ph merge`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeOptions {
+    /// Tag the target's current revision before writing
+    pub backup: bool,
+    /// Show the result without writing it
+    pub preview: bool,
+    /// Pick hunks one by one
+    pub interactive: bool,
+    /// Fail instead of parking a conflicted merge
+    pub no_conflicts: bool,
+    /// Resolve with the configured mergetool
+    pub tool: bool,
+}
+
+/// How a merge ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeOutcome {
+    /// The target was written, was already up to date, or was previewed
+    Done,
+    /// The merge has conflicts; `parked` when it waits for `ph merge --continue`
+    Conflicts { count: usize, parked: bool },
+    /// Another merge is waiting for `--continue` or `--abort`
+    InProgress { source: String, target: String },
+    /// The mergetool failed or returned an unusable result; nothing was written
+    Rejected,
+}
+
 pub fn handle_merge(
     storage: &Storage,
     source_prompt: &str,
     target_prompt: &str,
-    backup: bool,
-    preview: bool,
-    interactive: bool,
+    options: MergeOptions,
     start: Instant,
 ) -> Result<()> {
+    match merge_prompts(storage, source_prompt, target_prompt, options, start)? {
+        MergeOutcome::Done => Ok(()),
+        MergeOutcome::Conflicts { count, parked: false } => {
+            eprintln!(
+                "Error: {} conflict(s) merging {} into {}; nothing was changed",
+                count, source_prompt, target_prompt
+            );
+            std::process::exit(1);
+        }
+        MergeOutcome::InProgress { source, target } => {
+            eprintln!("Error: A merge of '{}' into '{}' is in progress", source, target);
+            eprintln!("Finish it with `ph merge --continue` or `ph merge --abort`");
+            std::process::exit(1);
+        }
+        MergeOutcome::Conflicts { .. } | MergeOutcome::Rejected => std::process::exit(1),
+    }
+}
+
+/// Merge `source_prompt` into `target_prompt`, reporting how it ended instead of exiting
+pub fn merge_prompts(
+    storage: &Storage,
+    source_prompt: &str,
+    target_prompt: &str,
+    options: MergeOptions,
+    start: Instant,
+) -> Result<MergeOutcome> {
+    let MergeOptions {
+        backup,
+        preview,
+        interactive,
+        no_conflicts,
+        tool,
+    } = options;
+    if let Some(state) = load_merge_state(storage)? {
+        return Ok(MergeOutcome::InProgress {
+            source: state.source,
+            target: state.target,
+        });
+    }
+
     // Resolve prompt names
     let resolved_source = resolve_prompt_name(storage, source_prompt)?;
-    let resolved_target = resolve_prompt_name(storage, target_prompt)?;
+    let resolved_target = resolve_prompt_for_write(storage, target_prompt, "merge into")?;
 
-    // Read source content
     let (source_metadata, source_content) = storage.read_prompt(&resolved_source)?;
     let (target_metadata, target_content) = storage.read_prompt(&resolved_target)?;
 
+    if interactive {
+        merge_interactively(
+            storage,
+            &resolved_source,
+            &source_metadata,
+            &source_content,
+            &resolved_target,
+            &target_metadata,
+            &target_content,
+            backup,
+            preview,
+            start,
+        )?;
+        return Ok(MergeOutcome::Done);
+    }
+
+    // Three-way when the prompts share history, otherwise keep both sides' additions
+    let base = find_merge_base(
+        storage,
+        &resolved_source,
+        &source_metadata,
+        &resolved_target,
+        &target_metadata,
+    )?;
+    let labels = MergeLabels {
+        ours: &resolved_target,
+        theirs: &resolved_source,
+    };
+    let result = match &base {
+        Some(base) => merge3(&base.body, &target_content, &source_content, labels),
+        None => merge2(&target_content, &source_content, labels),
+    };
+    let metadata = merge_metadata(&target_metadata, &source_metadata);
+    match &base {
+        Some(base) => println!("🔀 Merge base: {}", base.label.cyan()),
+        None => println!("🔀 No common ancestor; keeping additions from both prompts"),
+    }
+
+    if tool {
+        return merge_with_tool(
+            storage,
+            &resolved_source,
+            &source_content,
+            &resolved_target,
+            &target_content,
+            base.as_ref(),
+            &result,
+            metadata,
+            backup,
+            start,
+        );
+    }
+
     if preview {
-        // Show what would be merged
         println!("📋 Merge Preview:");
         println!(
             "Source: {} -> Target: {}",
             resolved_source.bold(),
             resolved_target.bold()
         );
-        println!("\nTarget content would be replaced with:");
-        println!("{}", source_content.dimmed());
+        println!("\nTarget content would become:");
+        println!("{}", result.content.dimmed());
+        if result.conflicts > 0 {
+            println!("⚠ {} conflict(s)", result.conflicts);
+        }
         println!("⏱️  Preview completed ({}ms)", start.elapsed().as_millis());
-        return Ok(());
+        return Ok(MergeOutcome::Done);
     }
 
-    if interactive {
-        eprintln!("Error: Interactive merge not yet implemented");
-        std::process::exit(1);
+    if result.conflicts > 0 && no_conflicts {
+        return Ok(MergeOutcome::Conflicts {
+            count: result.conflicts,
+            parked: false,
+        });
     }
 
-    // Create backup if requested
-    if backup {
-        let backup_name = format!("{}.backup", resolved_target);
-        storage.write_prompt(&backup_name, &target_metadata, &target_content)?;
-        println!("💾 Backup created: {}", backup_name.dimmed());
+    if result.conflicts > 0 {
+        // Park the conflicted body; the target stays untouched until --continue
+        let dir = merge_dir(storage);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("MERGE.md"), &result.content)?;
+        let state = MergeState {
+            source: resolved_source.clone(),
+            target: resolved_target.clone(),
+            target_hash: target_hash(storage, &resolved_target)?,
+            metadata,
+            conflicts: result.conflicts,
+            backup,
+        };
+        fs::write(dir.join("state.json"), serde_json::to_string_pretty(&state)?)?;
+
+        eprintln!(
+            "⚠ {} conflict(s) merging {} into {}",
+            result.conflicts,
+            resolved_source.yellow(),
+            resolved_target.yellow()
+        );
+        eprintln!("   Conflicted prompt: {}", dir.join("MERGE.md").display());
+        eprintln!("   Resolve in your editor: {}", "ph merge --continue".cyan());
+        eprintln!("   Give up:                {}", "ph merge --abort".cyan());
+        return Ok(MergeOutcome::Conflicts {
+            count: result.conflicts,
+            parked: true,
+        });
+    }
+
+    if result.content == target_content && metadata.tags == target_metadata.tags {
+        println!(
+            "✓ {} already contains {} ({}ms)",
+            resolved_target.green(),
+            resolved_source.green(),
+            start.elapsed().as_millis()
+        );
+        return Ok(MergeOutcome::Done);
     }
 
-    // Perform merge (simple content replacement for now)
-    storage.write_prompt(&resolved_target, &source_metadata, &source_content)?;
+    if backup {
+        backup_target(storage, &resolved_target)?;
+    }
+    let mut metadata = metadata;
+    metadata.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    storage.write_prompt(&resolved_target, &metadata, &result.content)?;
 
     println!(
         "✓ Merged {} into {} ({}ms)",
@@ -116,10 +625,410 @@ pub fn handle_merge(
         start.elapsed().as_millis()
     );
 
+    Ok(MergeOutcome::Done)
+}
+
+/// Why a body returned by a mergetool cannot be written, if it cannot
+fn merged_body_problem(content: &str) -> Option<&'static str> {
+    if has_conflict_markers(content) {
+        Some("conflict markers remain in the merged result")
+    } else if content.trim().is_empty() {
+        Some("the merged result is empty")
+    } else if content.lines().next() == Some("---") {
+        Some("the merged result has frontmatter; metadata is merged separately")
+    } else {
+        None
+    }
+}
+
+/// Let the configured mergetool produce the merged body, then check it before writing
+#[allow(clippy::too_many_arguments)]
+fn merge_with_tool(
+    storage: &Storage,
+    source: &str,
+    source_content: &str,
+    target: &str,
+    target_content: &str,
+    base: Option<&MergeBase>,
+    merged: &MergeResult,
+    metadata: PromptMetadata,
+    backup: bool,
+    start: Instant,
+) -> Result<MergeOutcome> {
+    let dir = tempfile::tempdir()?;
+    let local = tool_file(dir.path(), target, "LOCAL", target_content)?;
+    let remote = tool_file(dir.path(), source, "REMOTE", source_content)?;
+    let base_file = tool_file(dir.path(), target, "BASE", base.map_or("", |base| &base.body))?;
+    // Start from the automatic merge so only the conflicts are left to resolve
+    let merged_file = tool_file(dir.path(), target, "MERGED", &merged.content)?;
+
+    println!("🔧 Opening mergetool ({} conflict(s))", merged.conflicts);
+    let status = run_tool(
+        "mergetool",
+        &[
+            ("LOCAL", &local),
+            ("REMOTE", &remote),
+            ("BASE", &base_file),
+            ("MERGED", &merged_file),
+        ],
+    )?;
+    if !status.success() {
+        eprintln!("Error: mergetool exited with {}; {} was not changed", status, target);
+        return Ok(MergeOutcome::Rejected);
+    }
+
+    let content = fs::read_to_string(&merged_file)?;
+    if let Some(problem) = merged_body_problem(&content) {
+        eprintln!("Error: {}; {} was not changed", problem, target);
+        return Ok(MergeOutcome::Rejected);
+    }
+    let content = content.trim_end();
+    if content == target_content.trim_end() && metadata.tags == storage.read_prompt_metadata(target)?.tags {
+        println!(
+            "✓ {} is unchanged ({}ms)",
+            target.green(),
+            start.elapsed().as_millis()
+        );
+        return Ok(MergeOutcome::Done);
+    }
+
+    if backup {
+        backup_target(storage, target)?;
+    }
+    let mut metadata = metadata;
+    metadata.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    storage.write_prompt(target, &metadata, content)?;
+
+    println!(
+        "✓ Merged {} into {} with mergetool ({}ms)",
+        source.green(),
+        target.green(),
+        start.elapsed().as_millis()
+    );
+    Ok(MergeOutcome::Done)
+}
+
+/// Finish a conflicted merge, opening the conflicted body in the editor if needed
+pub fn handle_merge_continue(storage: &Storage, start: Instant) -> Result<()> {
+    let Some(state) = load_merge_state(storage)? else {
+        eprintln!("Error: No merge in progress");
+        std::process::exit(1);
+    };
+    let dir = merge_dir(storage);
+    let merge_file = dir.join("MERGE.md");
+
+    if !storage.prompt_exists(&state.target)
+        || target_hash(storage, &state.target)? != state.target_hash
+    {
+        eprintln!(
+            "Error: '{}' changed since the merge started; run `ph merge --abort` and merge again",
+            state.target
+        );
+        std::process::exit(1);
+    }
+
+    let mut content = fs::read_to_string(&merge_file)?;
+    if has_conflict_markers(&content) {
+        content = edit::edit_content(&content)?;
+        fs::write(&merge_file, &content)?;
+    }
+    if has_conflict_markers(&content) {
+        eprintln!(
+            "Error: Conflict markers remain in {}",
+            merge_file.display()
+        );
+        eprintln!("Resolve them with `ph merge --continue`, or give up with `ph merge --abort`");
+        std::process::exit(1);
+    }
+
+    if state.backup {
+        backup_target(storage, &state.target)?;
+    }
+    let mut metadata = state.metadata;
+    metadata.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    storage.write_prompt(&state.target, &metadata, &content)?;
+    fs::remove_dir_all(&dir)?;
+
+    println!(
+        "✓ Merged {} into {}, {} conflict(s) resolved ({}ms)",
+        state.source.green(),
+        state.target.green(),
+        state.conflicts,
+        start.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+/// Drop an unfinished merge; the target was never modified
+pub fn handle_merge_abort(storage: &Storage, start: Instant) -> Result<()> {
+    let Some(state) = load_merge_state(storage)? else {
+        eprintln!("Error: No merge in progress");
+        std::process::exit(1);
+    };
+    fs::remove_dir_all(merge_dir(storage))?;
+    println!(
+        "✓ Aborted merge of {} into {}; {} is unchanged ({}ms)",
+        state.source,
+        state.target,
+        state.target,
+        start.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+/// Walk the hunks between target and source, as `git add -p` does
+#[allow(clippy::too_many_arguments)]
+fn merge_interactively(
+    storage: &Storage,
+    source: &str,
+    source_metadata: &PromptMetadata,
+    source_content: &str,
+    target: &str,
+    target_metadata: &PromptMetadata,
+    target_content: &str,
+    backup: bool,
+    preview: bool,
+    start: Instant,
+) -> Result<()> {
+    let hunks = patch::hunks(target_content, source_content, 3);
+    if hunks.is_empty() {
+        println!(
+            "✓ {} already contains {} ({}ms)",
+            target.green(),
+            source.green(),
+            start.elapsed().as_millis()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "🔀 {} hunk(s) from {} to review for {}",
+        hunks.len(),
+        source.cyan(),
+        target.cyan()
+    );
+    let accepted = select_hunks(hunks, target)?;
+    if accepted.is_empty() {
+        println!(
+            "No hunks applied; {} is unchanged ({}ms)",
+            target,
+            start.elapsed().as_millis()
+        );
+        return Ok(());
+    }
+    let content = apply_hunks(target_content, &accepted)?;
+    let mut metadata = merge_metadata(target_metadata, source_metadata);
+
+    if preview {
+        println!("📋 Merge Preview:");
+        println!("\nTarget content would become:");
+        println!("{}", content.dimmed());
+        println!("⏱️  Preview completed ({}ms)", start.elapsed().as_millis());
+        return Ok(());
+    }
+
+    if backup {
+        backup_target(storage, target)?;
+    }
+    metadata.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    storage.write_prompt(target, &metadata, &content)?;
+
+    println!(
+        "✓ Merged {} hunk(s) from {} into {} ({}ms)",
+        accepted.len(),
+        source.green(),
+        target.green(),
+        start.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+const HUNK_HELP: &str = "y - apply this hunk
+n - skip this hunk
+q - quit; apply the hunks accepted so far
+a - apply this hunk and all later hunks
+d - skip this hunk and all later hunks
+e - edit this hunk in your editor
+s - split this hunk into smaller hunks
+? - print help";
+
+/// Ask about each hunk in turn and return the ones to apply
+fn select_hunks(hunks: Vec<Hunk>, target: &str) -> Result<Vec<Hunk>> {
+    let mut pending: VecDeque<Hunk> = hunks.into();
+    let mut accepted = Vec::new();
+    let mut answered = 0;
+
+    while let Some(hunk) = pending.pop_front() {
+        let splittable = hunk.split().len() > 1;
+        print!("\n{}", colored_hunk(&hunk));
+        print!(
+            "({}/{}) Apply this hunk to {} [y,n,q,a,d,e{},?]? ",
+            answered + 1,
+            answered + 1 + pending.len(),
+            target.bold(),
+            if splittable { ",s" } else { "" }
+        );
+        std::io::stdout().flush()?;
+
+        let mut input = String::new();
+        if std::io::stdin().read_line(&mut input)? == 0 {
+            println!();
+            break;
+        }
+        match input.trim() {
+            "y" => {
+                accepted.push(hunk);
+                answered += 1;
+            }
+            "n" => answered += 1,
+            "q" | "d" => break,
+            "a" => {
+                accepted.push(hunk);
+                accepted.extend(pending.drain(..));
+                break;
+            }
+            "s" if splittable => {
+                let parts = hunk.split();
+                println!("Split into {} hunks.", parts.len());
+                for part in parts.into_iter().rev() {
+                    pending.push_front(part);
+                }
+            }
+            "e" => match edit_hunk(&hunk)? {
+                Some(edited) => {
+                    accepted.push(edited);
+                    answered += 1;
+                }
+                None => pending.push_front(hunk),
+            },
+            _ => {
+                println!("{}", HUNK_HELP.dimmed());
+                pending.push_front(hunk);
+            }
+        }
+    }
+    Ok(accepted)
+}
+
+/// Let the user rewrite a hunk; `None` when the edit is abandoned or no longer fits
+fn edit_hunk(hunk: &Hunk) -> Result<Option<Hunk>> {
+    let text = format!(
+        "{}# Manual hunk edit mode\n\
+         # To skip a '-' line, turn it into a ' ' line.\n\
+         # To skip a '+' line, delete it.\n\
+         # Lines starting with # are removed. Delete everything to cancel.\n",
+        hunk.render()
+    );
+    let edited = edit::edit_content(&text)?;
+
+    let mut lines = Vec::new();
+    for line in edited.lines() {
+        if line.starts_with('#') || line.starts_with("@@") {
+            continue;
+        }
+        match HunkLine::parse(line) {
+            Some(parsed) => lines.push(parsed),
+            None => {
+                eprintln!("⚠ Not a diff line: {}", line);
+                return Ok(None);
+            }
+        }
+    }
+    let original = hunk.old_lines().len();
+    let mut edited = Hunk {
+        old_start: hunk.old_start,
+        new_start: hunk.new_start,
+        lines,
+    };
+    // Blank lines the editor left at the end are not context
+    while edited.old_lines().len() > original
+        && edited.lines.last() == Some(&HunkLine::Context(String::new()))
+    {
+        edited.lines.pop();
+    }
+
+    if edited.lines.is_empty() {
+        return Ok(None);
+    }
+    if edited.old_lines() != hunk.old_lines() {
+        eprintln!("⚠ The edited hunk does not apply: keep its ' ' and '-' lines as they were");
+        return Ok(None);
+    }
+    Ok(Some(edited))
+}
+
+/// Handle the apply command - apply a unified diff saved from `ph diff`
+pub fn handle_apply(
+    storage: &Storage,
+    patch_file: &str,
+    to: Option<&str>,
+    interactive: bool,
+    preview: bool,
+    start: Instant,
+) -> Result<()> {
+    let text = match fs::read_to_string(patch_file) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error: Cannot read patch '{}': {}", patch_file, e);
+            std::process::exit(1);
+        }
+    };
+    let patch = match parse_patch(&text) {
+        Ok(patch) => patch,
+        Err(e) => {
+            eprintln!("Error: Invalid patch '{}': {}", patch_file, e);
+            std::process::exit(1);
+        }
+    };
+    let Some(target) = to.map(str::to_string).or(patch.old_name) else {
+        eprintln!("Error: Patch '{}' does not name a prompt; pass --to <prompt>", patch_file);
+        std::process::exit(1);
+    };
+
+    let resolved_target = resolve_prompt_for_write(storage, &target, "apply a patch to")?;
+    let (mut metadata, content) = storage.read_prompt(&resolved_target)?;
+
+    let hunks = if interactive {
+        select_hunks(patch.hunks, &resolved_target)?
+    } else {
+        patch.hunks
+    };
+    if hunks.is_empty() {
+        println!(
+            "No hunks applied; {} is unchanged ({}ms)",
+            resolved_target,
+            start.elapsed().as_millis()
+        );
+        return Ok(());
+    }
+    let patched = match apply_hunks(&content, &hunks) {
+        Ok(patched) => patched,
+        Err(e) => {
+            eprintln!("Error: {}; {} was not changed", e, resolved_target);
+            std::process::exit(1);
+        }
+    };
+
+    if preview {
+        println!("📋 Patch Preview:");
+        println!("\n{} would become:", resolved_target.bold());
+        println!("{}", patched.dimmed());
+        println!("⏱️  Preview completed ({}ms)", start.elapsed().as_millis());
+        return Ok(());
+    }
+
+    metadata.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    storage.write_prompt(&resolved_target, &metadata, &patched)?;
+    println!(
+        "✓ Applied {} hunk(s) to {} ({}ms)",
+        hunks.len(),
+        resolved_target.green(),
+        start.elapsed().as_millis()
+    );
     Ok(())
 }
 
-fn generate_unified_diff(
+pub(crate) fn generate_unified_diff(
     name1: &str,
     content1: &str,
     name2: &str,
@@ -131,111 +1040,126 @@ fn generate_unified_diff(
     result.push_str(&format!("--- {}\n", name1));
     result.push_str(&format!("+++ {}\n", name2));
 
-    let lines1: Vec<&str> = content1.lines().collect();
-    let lines2: Vec<&str> = content2.lines().collect();
+    for hunk in patch::hunks(content1, content2, context) {
+        result.push_str(&colored_hunk(&hunk));
+    }
 
-    // Simple line-by-line comparison
-    let mut i = 0;
-    let mut j = 0;
-    let mut hunk_start1: usize = 0;
-    let mut hunk_start2: usize = 0;
-    let mut hunk_lines = Vec::new();
-
-    while i < lines1.len() || j < lines2.len() {
-        if i < lines1.len() && j < lines2.len() && lines1[i] == lines2[j] {
-            // Lines match
-            if !hunk_lines.is_empty() {
-                // Add context before
-                let context_start = hunk_start1.saturating_sub(context);
-                for ctx_i in context_start..hunk_start1 {
-                    if ctx_i < lines1.len() {
-                        hunk_lines.insert(0, format!(" {}", lines1[ctx_i]));
-                    }
-                }
+    result
+}
 
-                // Add context after
-                let mut added_context = 0usize;
-                while added_context < context
-                    && i + added_context < lines1.len()
-                    && j + added_context < lines2.len()
-                {
-                    if lines1[i + added_context] == lines2[j + added_context] {
-                        hunk_lines.push(format!(" {}", lines1[i + added_context]));
-                        added_context += 1;
-                    } else {
-                        break;
-                    }
-                }
+/// A hunk with removed lines in red and added lines in green
+fn colored_hunk(hunk: &Hunk) -> String {
+    let mut result = format!("{}\n", hunk.header().cyan());
+    for line in &hunk.lines {
+        let rendered = line.render();
+        let rendered = match line {
+            HunkLine::Delete(_) => rendered.red().to_string(),
+            HunkLine::Insert(_) => rendered.green().to_string(),
+            HunkLine::Context(_) => rendered,
+        };
+        result.push_str(&rendered);
+        result.push('\n');
+    }
+    result
+}
 
-                // Output hunk
-                let hunk_len1 = hunk_lines.iter().filter(|l| !l.starts_with('+')).count();
-                let hunk_len2 = hunk_lines.iter().filter(|l| !l.starts_with('-')).count();
-                result.push_str(&format!(
-                    "@@ -{},{} +{},{} @@\n",
-                    context_start + 1,
-                    hunk_len1,
-                    hunk_start2 + 1,
-                    hunk_len2
-                ));
-
-                for line in &hunk_lines {
-                    if line.starts_with('-') {
-                        result.push_str(&format!("{}\n", line.red()));
-                    } else if line.starts_with('+') {
-                        result.push_str(&format!("{}\n", line.green()));
-                    } else {
-                        result.push_str(&format!("{}\n", line));
-                    }
-                }
+/// Unified hunks with changed lines refined to words
+///
+/// Without colour, removed words read `[-like this-]` and added ones `{+like this+}`.
+fn generate_word_diff(
+    name1: &str,
+    content1: &str,
+    name2: &str,
+    content2: &str,
+    context: usize,
+    color: bool,
+) -> String {
+    let mut result = format!("--- {}\n+++ {}\n", name1, name2);
+    let render = |removed: &mut Vec<&str>, added: &mut Vec<&str>, result: &mut String| {
+        if removed.is_empty() && added.is_empty() {
+            return;
+        }
+        for span in diff_words(&removed.join("\n"), &added.join("\n")) {
+            let text = match span {
+                WordSpan::Same(text) => text,
+                WordSpan::Removed(text) if color => text.red().strikethrough().to_string(),
+                WordSpan::Added(text) if color => text.green().to_string(),
+                WordSpan::Removed(text) => format!("[-{}-]", text),
+                WordSpan::Added(text) => format!("{{+{}+}}", text),
+            };
+            result.push_str(&text);
+        }
+        result.push('\n');
+        removed.clear();
+        added.clear();
+    };
 
-                hunk_lines.clear();
-                i += added_context;
-                j += added_context;
-            }
-            i += 1;
-            j += 1;
-        } else if i < lines1.len() && (j >= lines2.len() || lines1[i] != lines2[j]) {
-            // Line removed
-            if hunk_lines.is_empty() {
-                hunk_start1 = i;
-                hunk_start2 = j;
-            }
-            hunk_lines.push(format!("-{}", lines1[i]));
-            i += 1;
-        } else if j < lines2.len() {
-            // Line added
-            if hunk_lines.is_empty() {
-                hunk_start1 = i;
-                hunk_start2 = j;
+    for hunk in patch::hunks(content1, content2, context) {
+        let header = hunk.header();
+        if color {
+            result.push_str(&header.cyan().to_string());
+        } else {
+            result.push_str(&header);
+        }
+        result.push('\n');
+
+        let (mut removed, mut added) = (Vec::new(), Vec::new());
+        for line in &hunk.lines {
+            match line {
+                HunkLine::Delete(text) => removed.push(text.as_str()),
+                HunkLine::Insert(text) => added.push(text.as_str()),
+                HunkLine::Context(text) => {
+                    render(&mut removed, &mut added, &mut result);
+                    result.push_str(text);
+                    result.push('\n');
+                }
             }
-            hunk_lines.push(format!("+{}", lines2[j]));
-            j += 1;
         }
+        render(&mut removed, &mut added, &mut result);
     }
+    result
+}
 
-    // Output final hunk if any
-    if !hunk_lines.is_empty() {
-        let hunk_len1 = hunk_lines.iter().filter(|l| !l.starts_with('+')).count();
-        let hunk_len2 = hunk_lines.iter().filter(|l| !l.starts_with('-')).count();
-        result.push_str(&format!(
-            "@@ -{},{} +{},{} @@\n",
-            hunk_start1 + 1,
-            hunk_len1,
-            hunk_start2 + 1,
-            hunk_len2
-        ));
-
-        for line in &hunk_lines {
-            if line.starts_with('-') {
-                result.push_str(&format!("{}\n", line.red()));
-            } else if line.starts_with('+') {
-                result.push_str(&format!("{}\n", line.green()));
-            } else {
-                result.push_str(&format!("{}\n", line));
-            }
+/// Summary of changed lines, as `git diff --stat` prints it
+fn generate_stat(
+    name1: &str,
+    content1: &str,
+    name2: &str,
+    content2: &str,
+    metadata_changes: Option<&[(String, FieldChange)]>,
+) -> String {
+    let lines = patch::hunks(content1, content2, 0)
+        .into_iter()
+        .flat_map(|hunk| hunk.lines);
+    let (mut insertions, mut deletions) = (0usize, 0usize);
+    for line in lines {
+        match line {
+            HunkLine::Insert(_) => insertions += 1,
+            HunkLine::Delete(_) => deletions += 1,
+            HunkLine::Context(_) => {}
         }
     }
 
+    // Keep the bar to a readable width for big rewrites
+    let total = insertions + deletions;
+    let scale = |n: usize| if total > 40 { (n * 40).div_ceil(total) } else { n };
+    let mut result = format!(
+        " {} → {} | {} {}{}\n",
+        name1,
+        name2,
+        total,
+        "+".repeat(scale(insertions)).green(),
+        "-".repeat(scale(deletions)).red()
+    );
+    result.push_str(&format!(
+        " {} prompt changed, {} insertion(s)(+), {} deletion(s)(-)",
+        usize::from(total > 0),
+        insertions,
+        deletions
+    ));
+    if let Some(changes) = metadata_changes {
+        result.push_str(&format!(", {} metadata field(s) changed", changes.len()));
+    }
     result
 }
 
@@ -312,6 +1236,49 @@ mod tests {
         (temp_dir, storage)
     }
 
+    #[test]
+    fn test_merge_base_ignores_unrelated_tag_of_the_same_name() {
+        let (_temp, storage) = create_test_storage();
+        let store = storage.revisions();
+        let (mut alpha, _) = storage.parse_prompt_content("alpha one").unwrap();
+        storage.write_prompt("alpha", &alpha, "alpha one").unwrap();
+        store.set_tag("alpha", "v1.0", 1, None).unwrap();
+        alpha.parent_version = Some("v1.0".to_string());
+        storage.write_prompt("alpha", &alpha, "alpha two").unwrap();
+        let (beta, _) = storage.parse_prompt_content("beta one").unwrap();
+        storage.write_prompt("beta", &beta, "beta one").unwrap();
+        store.set_tag("beta", "v1.0", 1, None).unwrap();
+
+        // alpha's v1.0 is its own tag, not beta's
+        assert!(find_merge_base(&storage, "alpha", &alpha, "beta", &beta).unwrap().is_none());
+
+        // A qualified parent names the other prompt explicitly
+        alpha.parent_version = Some("beta@v1.0".to_string());
+        let base = find_merge_base(&storage, "alpha", &alpha, "beta", &beta).unwrap().unwrap();
+        assert_eq!(base.label, "beta@v1.0");
+        assert_eq!(base.body, "beta one");
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts_without_parking_them() {
+        let (_temp, storage) = create_test_storage();
+        let (alpha, _) = storage.parse_prompt_content("x").unwrap();
+        storage.write_prompt("alpha", &alpha, "first\nshared").unwrap();
+        let mut beta = alpha.clone();
+        beta.parent_version = Some("alpha@r1".to_string());
+        storage.write_prompt("beta", &beta, "beta\nshared").unwrap();
+        storage.write_prompt("alpha", &alpha, "alpha\nshared").unwrap();
+
+        let options = MergeOptions {
+            no_conflicts: true,
+            ..Default::default()
+        };
+        let outcome = merge_prompts(&storage, "alpha", "beta", options, Instant::now()).unwrap();
+        assert_eq!(outcome, MergeOutcome::Conflicts { count: 1, parked: false });
+        assert_eq!(storage.read_prompt("beta").unwrap().1, "beta\nshared");
+        assert!(load_merge_state(&storage).unwrap().is_none());
+    }
+
     #[test]
     fn test_generate_brief_diff_identical() {
         let result = generate_brief_diff("prompt1", "same content", "prompt2", "same content");
@@ -333,6 +1300,29 @@ mod tests {
         assert!(result.contains("line1"));
     }
 
+    #[test]
+    fn test_metadata_changes_include_custom_keys() {
+        let old = frontmatter_fields("---\nid: a\ndescription: Old\ntags: [x, y]\nowner: alice\n---\nbody");
+        let new = frontmatter_fields("---\nid: b\ndescription: New\ntags: [y, z]\nteam: ops\n---\nbody");
+        let changes = metadata_changes(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                ("description".to_string(), FieldChange::Changed("Old".into(), "New".into())),
+                ("owner".to_string(), FieldChange::Removed("alice".into())),
+                (
+                    "tags".to_string(),
+                    FieldChange::Items {
+                        added: vec!["z".into()],
+                        removed: vec!["x".into()],
+                    }
+                ),
+                ("team".to_string(), FieldChange::Added("ops".into())),
+            ]
+        );
+        assert!(frontmatter_fields("no frontmatter").is_empty());
+    }
+
     #[test]
     fn test_generate_unified_diff() {
         let result = generate_unified_diff("p1", "line1\nline2", "p2", "line1\nchanged", 1);
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::{init_telemetry, record_command_metric, IoOptions, RetentionConfig, Storage};
+
+/// `ph doctor --json`
+#[derive(Serialize)]
+struct DoctorReport {
+    histories: usize,
+    revisions: usize,
+    objects_bytes: u64,
+    retention: String,
+    /// Histories whose prompt no longer exists
+    orphaned_histories: Vec<String>,
+    /// `<name>.versions` directories from older releases without a prompt
+    orphaned_version_dirs: Vec<String>,
+    /// `<name>.backup` prompts made by older `merge --backup`
+    backup_prompts: Vec<String>,
+}
+
+impl DoctorReport {
+    fn problems(&self) -> usize {
+        self.orphaned_histories.len() + self.orphaned_version_dirs.len() + self.backup_prompts.len()
+    }
+}
+
+/// Handle the doctor command - report leftovers in the storage directory
+pub fn handle_doctor(storage: &Storage, io_options: &IoOptions, start: Instant) -> Result<()> {
+    let mut telemetry = Some(init_telemetry(storage.base_dir().clone()).ok()).flatten();
+
+    let store = storage.revisions();
+    let histories = store.histories()?;
+    let revisions = histories
+        .iter()
+        .filter(|history| !history.orphan)
+        .map(|history| store.log(&history.name).map(|log| log.len()).unwrap_or(0))
+        .sum();
+    let objects_bytes = walkdir::WalkDir::new(storage.base_dir().join("objects"))
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    let report = DoctorReport {
+        histories: histories.iter().filter(|history| !history.orphan).count(),
+        revisions,
+        objects_bytes,
+        retention: RetentionConfig::load(storage.base_dir()).describe(),
+        orphaned_histories: histories
+            .iter()
+            .filter(|history| history.orphan)
+            .map(|history| history.name.clone())
+            .collect(),
+        orphaned_version_dirs: store
+            .orphaned_legacy_dirs()?
+            .iter()
+            .map(|dir| {
+                dir.strip_prefix(storage.base_dir())
+                    .unwrap_or(dir)
+                    .display()
+                    .to_string()
+            })
+            .collect(),
+        backup_prompts: storage
+            .list_prompts()?
+            .into_iter()
+            .filter(|name| name.ends_with(".backup"))
+            .collect(),
+    };
+
+    if io_options.is_structured() {
+        return io_options.emit(&report);
+    }
+
+    println!("{}", "🩺 PromptHive doctor".bold());
+    println!(
+        "  {} Revision history: {} prompts, {} revisions, {} KB",
+        "✓".green(),
+        report.histories,
+        report.revisions,
+        report.objects_bytes.div_ceil(1024)
+    );
+    println!("  {} Retention: {}", "✓".green(), report.retention);
+
+    if !report.orphaned_histories.is_empty() {
+        println!(
+            "  {} {} orphaned version histories (prompt no longer exists): {}",
+            "⚠".yellow(),
+            report.orphaned_histories.len(),
+            report.orphaned_histories.join(", ")
+        );
+    }
+    if !report.orphaned_version_dirs.is_empty() {
+        println!(
+            "  {} {} orphaned .versions directories: {}",
+            "⚠".yellow(),
+            report.orphaned_version_dirs.len(),
+            report.orphaned_version_dirs.join(", ")
+        );
+    }
+    if !report.orphaned_histories.is_empty() || !report.orphaned_version_dirs.is_empty() {
+        println!("      Remove with: {}", "ph versions prune --orphans".cyan());
+    }
+    if !report.backup_prompts.is_empty() {
+        println!(
+            "  {} {} backup prompts: {}",
+            "⚠".yellow(),
+            report.backup_prompts.len(),
+            report.backup_prompts.join(", ")
+        );
+        println!(
+            "      Backups are now version tags; remove these with: {}",
+            format!("ph delete {}", report.backup_prompts[0]).cyan()
+        );
+    }
+
+    match report.problems() {
+        0 => println!("✓ No problems found ({}ms)", start.elapsed().as_millis()),
+        n => println!("⚠ {} problem(s) found ({}ms)", n, start.elapsed().as_millis()),
+    }
+
+    record_command_metric(&mut telemetry, "doctor", start.elapsed(), true, None, None);
+    Ok(())
+}
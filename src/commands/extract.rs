@@ -0,0 +1,146 @@
+use crate::transcript::{parse_transcript, render_turns, select_turns, Role, TranscriptFormat};
+use crate::{IoOptions, Storage};
+use anyhow::Result;
+use colored::*;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::Instant;
+
+/// Handle the extract command - pull conversation turns out of a transcript
+#[allow(clippy::too_many_arguments)]
+pub fn handle_extract(
+    source: Option<&str>,
+    format: &str,
+    roles: &[String],
+    turns: Option<&str>,
+    list: bool,
+    io_options: &IoOptions,
+    start: Instant,
+) -> Result<()> {
+    // Read the transcript from a file, or stdin when no path (or "-") is given
+    let (content, origin) = match source.filter(|s| *s != "-") {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(content) => {
+                let origin = fs::canonicalize(path)
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|_| path.to_string());
+                (content, origin)
+            }
+            Err(e) => {
+                eprintln!("Error: Cannot read transcript '{}': {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let mut buffer = String::new();
+            io::stdin().read_to_string(&mut buffer)?;
+            (buffer, "stdin".to_string())
+        }
+    };
+
+    let format = if format == "auto" {
+        TranscriptFormat::detect(&content)
+    } else {
+        match format.parse::<TranscriptFormat>() {
+            Ok(format) => format,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let roles: Vec<Role> = match roles.iter().map(|r| r.parse()).collect::<Result<_>>() {
+        Ok(roles) => roles,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let all_turns = parse_transcript(&content, format);
+    if all_turns.is_empty() {
+        eprintln!(
+            "Error: No conversation turns found in {} (read as {})",
+            origin,
+            format.name()
+        );
+        eprintln!("Try --format terminal, jsonl or markdown");
+        std::process::exit(1);
+    }
+
+    if list {
+        return list_turns(&all_turns, &roles, format, io_options, start);
+    }
+
+    let selected = match select_turns(&all_turns, &roles, turns) {
+        Ok(selected) if !selected.is_empty() => selected,
+        Ok(_) => {
+            eprintln!("Error: No turns match the selection");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if io_options.is_structured() {
+        return io_options.emit(&selected);
+    }
+
+    // Record the transcript and the turns taken in saved prompts
+    let indices: Vec<String> = selected.iter().map(|t| t.index.to_string()).collect();
+    let source = format!("{}#turns={}", origin, indices.join(","));
+    let description = match Path::new(&origin).file_name() {
+        Some(name) if origin != "stdin" => format!("Extracted from {}", name.to_string_lossy()),
+        _ => "Extracted from transcript".to_string(),
+    };
+
+    let storage = Storage::new()?;
+    io_options
+        .clone()
+        .with_source(Some(source))
+        .apply_unified_io(&storage, &render_turns(&selected), &description, start)?;
+
+    Ok(())
+}
+
+/// Show the turns that `--turn` positions refer to
+fn list_turns(
+    turns: &[crate::transcript::Turn],
+    roles: &[Role],
+    format: TranscriptFormat,
+    io_options: &IoOptions,
+    start: Instant,
+) -> Result<()> {
+    let filtered = select_turns(turns, roles, None)?;
+    if io_options.is_structured() {
+        return io_options.emit(&filtered);
+    }
+
+    for (position, turn) in filtered.iter().enumerate() {
+        let preview: String = turn
+            .content
+            .lines()
+            .next()
+            .unwrap_or("")
+            .chars()
+            .take(70)
+            .collect();
+        let role = match turn.role {
+            Role::User => turn.role.name().green(),
+            Role::Assistant => turn.role.name().cyan(),
+            _ => turn.role.name().dimmed(),
+        };
+        println!("{:>4}  {:<9}  {}", position + 1, role, preview);
+    }
+    println!(
+        "\n{} turns, {} transcript ({}ms)",
+        filtered.len(),
+        format.name(),
+        start.elapsed().as_millis()
+    );
+    Ok(())
+}
@@ -0,0 +1,225 @@
+// History commands - list, show, replay and diff recorded commands
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::*;
+use std::time::Instant;
+
+use crate::{CommandCategory, HistoryEntry, HistoryTracker, IoOptions, Storage};
+
+#[derive(Subcommand)]
+pub enum HistoryCommands {
+    /// Show a history entry in full
+    Show {
+        /// Entry id (or unique prefix)
+        id: String,
+    },
+    /// Run a recorded `ph use` again with the same prompt and input
+    Replay {
+        /// Entry id (or unique prefix)
+        id: String,
+    },
+    /// Compare the outputs of two history entries
+    Diff {
+        /// First entry id
+        id1: String,
+        /// Second entry id
+        id2: String,
+    },
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_history(
+    storage: &Storage,
+    action: &Option<HistoryCommands>,
+    limit: usize,
+    search: Option<&str>,
+    success_only: bool,
+    last: bool,
+    io_options: &IoOptions,
+    start: Instant,
+) -> Result<()> {
+    let tracker = HistoryTracker::new(storage.base_dir().to_path_buf());
+
+    match action {
+        Some(HistoryCommands::Show { id }) if io_options.is_structured() => {
+            io_options.emit(&find_entry(&tracker, id))
+        }
+        Some(HistoryCommands::Show { id }) => handle_history_show(&tracker, id, start),
+        Some(HistoryCommands::Replay { id }) => {
+            let entry = find_entry(&tracker, id);
+            replay(storage, &entry, start)
+        }
+        Some(HistoryCommands::Diff { id1, id2 }) => handle_history_diff(&tracker, id1, id2, start),
+        None if last => match tracker.get_last()? {
+            Some(entry) => replay(storage, &entry, start),
+            None => {
+                eprintln!("Error: History is empty");
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let entries = match search {
+                Some(query) => tracker.search(query)?.into_iter().take(limit).collect(),
+                None if success_only => tracker.get_successful_only(limit)?,
+                None => tracker.get_recent(limit)?,
+            };
+            let entries: Vec<HistoryEntry> = entries
+                .into_iter()
+                .filter(|entry| !success_only || entry.success)
+                .collect();
+
+            if io_options.is_structured() {
+                return io_options.emit(&entries);
+            }
+
+            println!(
+                "📜 {} ({}ms)\n",
+                "Command history:".green(),
+                start.elapsed().as_millis()
+            );
+
+            if entries.is_empty() {
+                println!("  No history yet");
+            }
+            for entry in &entries {
+                entry.display();
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn handle_history_show(tracker: &HistoryTracker, id: &str, start: Instant) -> Result<()> {
+    let entry = find_entry(tracker, id);
+
+    entry.display();
+    if let Some(prompt) = &entry.prompt {
+        println!("  Prompt: {}", prompt);
+    }
+
+    if !entry.vars.is_empty() {
+        println!("\n{}", "Variables:".bold());
+        for (name, value) in &entry.vars {
+            println!("  {} = {}", name.cyan(), value);
+        }
+    }
+
+    if let Some(input) = entry.input.as_deref().filter(|i| !i.is_empty()) {
+        println!("\n{}", "Input:".bold());
+        println!("{}", input);
+    }
+
+    if let Some(output) = &entry.output {
+        println!("\n{}", "Output:".bold());
+        println!("{}", output);
+    }
+
+    if entry.truncated {
+        println!(
+            "\n{}",
+            "(content was truncated at the history size cap)".dimmed()
+        );
+    } else if entry.output.is_none() && !entry.output_preview.is_empty() {
+        println!(
+            "\n{}",
+            "Only previews were recorded. Keep full content with: ph config history full".dimmed()
+        );
+    }
+
+    println!("\n⏱️  Loaded entry ({}ms)", start.elapsed().as_millis());
+    Ok(())
+}
+
+fn handle_history_diff(
+    tracker: &HistoryTracker,
+    id1: &str,
+    id2: &str,
+    start: Instant,
+) -> Result<()> {
+    let first = find_entry(tracker, id1);
+    let second = find_entry(tracker, id2);
+
+    if first.output.is_none() || second.output.is_none() {
+        eprintln!(
+            "{}",
+            "Note: comparing previews, full output was not recorded for both entries".yellow()
+        );
+    }
+
+    let output1 = first.output.as_deref().unwrap_or(&first.output_preview);
+    let output2 = second.output.as_deref().unwrap_or(&second.output_preview);
+
+    if output1 == output2 {
+        println!("✓ Outputs of {} and {} are identical", first.id, second.id);
+    } else {
+        let label1 = format!("{} ({})", first.id, first.command);
+        let label2 = format!("{} ({})", second.id, second.command);
+        print!(
+            "{}",
+            super::diff_merge::generate_unified_diff(&label1, output1, &label2, output2, 3)
+        );
+    }
+
+    println!("⏱️  Diff completed ({}ms)", start.elapsed().as_millis());
+    Ok(())
+}
+
+/// Re-run a recorded `ph use` with its stored prompt and input
+fn replay(storage: &Storage, entry: &HistoryEntry, start: Instant) -> Result<()> {
+    let prompt = match (&entry.prompt, entry.command.strip_prefix("use ")) {
+        (Some(prompt), _) => prompt.clone(),
+        (None, Some(name)) => name.to_string(),
+        _ => {
+            eprintln!(
+                "Error: Only 'use' commands can be replayed ('{}')",
+                entry.command
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let input = match &entry.input {
+        Some(input) if !entry.truncated => input.clone(),
+        // Short inputs are complete in the preview; long ones end in "..."
+        None if !entry.input_preview.ends_with("...") => entry.input_preview.clone(),
+        _ => {
+            eprintln!(
+                "Error: The input of {} was not recorded in full, so it cannot be replayed",
+                entry.id
+            );
+            eprintln!("Keep full input for future commands with: ph config history full");
+            std::process::exit(1);
+        }
+    };
+
+    eprintln!(
+        "{} {} ({})",
+        "↻ Replaying".dimmed(),
+        entry.command,
+        entry.id
+    );
+
+    let io_options = IoOptions::new(None, None, false, None, false)
+        .with_category(CommandCategory::TextTransform);
+    super::core::handle_use(
+        storage,
+        &prompt,
+        Some(&input),
+        false,
+        &io_options,
+        None,
+        start,
+    )
+}
+
+fn find_entry(tracker: &HistoryTracker, id: &str) -> HistoryEntry {
+    match tracker.find(id) {
+        Ok(entry) => entry,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
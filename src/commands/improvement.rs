@@ -115,6 +115,7 @@ pub async fn handle_improve(
 
     // Read the prompt
     let (metadata, body) = storage.read_prompt(&resolved_name)?;
+    let body = super::redact::redact_outgoing(storage, &resolved_name, &body)?;
 
     if crowd {
         handle_crowd_improvement(storage, &client, &resolved_name, &metadata.description, &body, instructions, priority, start).await
@@ -125,6 +126,7 @@ pub async fn handle_improve(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_crowd_improvement(
     _storage: &Storage,
     client: &RegistryClient,
@@ -193,6 +195,7 @@ async fn handle_crowd_improvement(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_ai_improvement(
     _storage: &Storage,
     client: &RegistryClient,
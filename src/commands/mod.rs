@@ -1,3 +1,4 @@
+pub mod aliases;
 pub mod banks;
 pub mod clean;
 pub mod common;
@@ -5,9 +6,15 @@ pub mod completion;
 pub mod configuration;
 pub mod core;
 pub mod diff_merge;
+pub mod doctor;
+pub mod extract;
+pub mod history;
 pub mod improvement;
+pub mod redact;
 pub mod registry;
+pub mod security;
 pub mod sharing;
+pub mod similarity;
 pub mod statistics;
 pub mod subscription;
 pub mod sync;
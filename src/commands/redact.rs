@@ -0,0 +1,171 @@
+// Redaction commands - check prompts for secrets and manage false positives
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::*;
+use std::time::Instant;
+
+use super::common::{resolve_prompt_for_write, resolve_prompt_name};
+use crate::redaction::{self, RedactionAllowList};
+use crate::Storage;
+
+#[derive(Subcommand)]
+pub enum RedactCommands {
+    /// Show which secrets would be redacted from a prompt
+    Check {
+        /// Prompt name
+        prompt: String,
+    },
+    /// Allow a value or kind (e.g. email) that is not a secret
+    Allow {
+        /// Prompt name
+        prompt: String,
+        /// Literal value or secret kind to stop redacting
+        value: String,
+    },
+    /// Redact a previously allowed value or kind again
+    #[command(alias = "rm")]
+    Remove {
+        /// Prompt name
+        prompt: String,
+        /// Literal value or secret kind
+        value: String,
+    },
+    /// List allowed values
+    #[command(alias = "ls")]
+    List {
+        /// Only show entries for this prompt
+        prompt: Option<String>,
+    },
+}
+
+pub fn handle_redact(
+    storage: &Storage,
+    action: &Option<RedactCommands>,
+    start: Instant,
+) -> Result<()> {
+    match action {
+        Some(RedactCommands::Check { prompt }) => handle_redact_check(storage, prompt, start),
+        Some(RedactCommands::Allow { prompt, value }) => {
+            let resolved = resolve_prompt_for_write(storage, prompt, "redact allow")?;
+            let mut allow_list = RedactionAllowList::load(storage)?;
+            if allow_list.allow(&resolved, value) {
+                allow_list.save(storage)?;
+                println!(
+                    "✓ '{}' will not be redacted from {} ({}ms)",
+                    value,
+                    resolved.bold(),
+                    start.elapsed().as_millis()
+                );
+            } else {
+                println!("'{}' is already allowed for {}", value, resolved);
+            }
+            Ok(())
+        }
+        Some(RedactCommands::Remove { prompt, value }) => {
+            let mut allow_list = RedactionAllowList::load(storage)?;
+            if !allow_list.remove(prompt, value) {
+                eprintln!("Error: '{}' is not allowed for '{}'", value, prompt);
+                std::process::exit(1);
+            }
+            allow_list.save(storage)?;
+            println!(
+                "✓ '{}' will be redacted from {} again ({}ms)",
+                value,
+                prompt.bold(),
+                start.elapsed().as_millis()
+            );
+            Ok(())
+        }
+        Some(RedactCommands::List { prompt }) => {
+            let allow_list = RedactionAllowList::load(storage)?;
+            println!(
+                "🔒 {} ({}ms)\n",
+                "Redaction allow-list:".green(),
+                start.elapsed().as_millis()
+            );
+
+            let entries: Vec<_> = allow_list
+                .prompts
+                .iter()
+                .filter(|(name, _)| prompt.as_deref().is_none_or(|p| p == name.as_str()))
+                .collect();
+            if entries.is_empty() {
+                println!("  No allowed values");
+            }
+            for (name, values) in entries {
+                println!("  {}", name.bold());
+                for value in values {
+                    println!("    {}", value);
+                }
+            }
+            Ok(())
+        }
+        None => {
+            println!("Redaction commands:");
+            println!("  ph redact check <prompt>          Show secrets that would be redacted");
+            println!("  ph redact allow <prompt> <value>  Stop redacting a false positive");
+            println!("  ph redact rm <prompt> <value>     Redact an allowed value again");
+            println!("  ph redact ls [prompt]             List allowed values");
+            Ok(())
+        }
+    }
+}
+
+fn handle_redact_check(storage: &Storage, prompt: &str, start: Instant) -> Result<()> {
+    let resolved = resolve_prompt_name(storage, prompt)?;
+    let (_, body) = storage.read_prompt(&resolved)?;
+    let redaction = redaction::redact_prompt(storage, &resolved, &body)?;
+
+    println!(
+        "🔒 {} {} ({}ms)\n",
+        "Redaction check for".green(),
+        resolved.bold(),
+        start.elapsed().as_millis()
+    );
+
+    if redaction.is_clean() {
+        println!("  No secrets found");
+        return Ok(());
+    }
+
+    for secret in &redaction.secrets {
+        println!(
+            "  {}  {} ({}x)",
+            secret.placeholder.yellow(),
+            secret.value,
+            secret.occurrences
+        );
+    }
+    println!(
+        "\n  Allow a false positive with: {} redact allow {} <value|kind>",
+        "ph".bold(),
+        resolved
+    );
+
+    Ok(())
+}
+
+/// Redact prompt content before it leaves the machine
+///
+/// Prints a notice on stderr when anything was replaced, so the user can
+/// allow false positives.
+pub fn redact_outgoing(storage: &Storage, prompt: &str, content: &str) -> Result<String> {
+    let redaction = redaction::redact_prompt(storage, prompt, content)?;
+
+    if !redaction.is_clean() {
+        eprintln!(
+            "🔒 Redacted {} secret(s) from {}: {}",
+            redaction.secrets.len(),
+            prompt.bold(),
+            redaction.summary()
+        );
+        eprintln!(
+            "   Review with: {} redact check {}",
+            "ph".bold(),
+            prompt
+        );
+    }
+
+    Ok(redaction.content)
+}
@@ -1,6 +1,6 @@
 // Registry commands for search, install, publish, etc.
 
-use crate::{MatchResult, Matcher, Prompt, RegistryClient, Storage};
+use crate::{MatchResult, Matcher, Prompt, RegistryClient, ShortCodeRegistry, Storage};
 #[cfg(feature = "registry")]
 use crate::{PackagePrompt, PublishRequest};
 use anyhow::{Context, Result};
@@ -17,7 +17,12 @@ pub async fn handle_search(query: &str, limit: Option<u32>, start: Instant) -> R
     handle_browse(query, limit.unwrap_or(10), start).await
 }
 
-pub async fn handle_install(storage: &Storage, package: &str, start: Instant) -> Result<()> {
+pub async fn handle_install(
+    storage: &Storage,
+    package: &str,
+    allow_insecure: bool,
+    start: Instant,
+) -> Result<()> {
     // Parse package name and version
     let (package_name, version) = if package.contains('@') && !package.starts_with('@') {
         let parts: Vec<&str> = package.rsplitn(2, '@').collect();
@@ -49,13 +54,16 @@ pub async fn handle_install(storage: &Storage, package: &str, start: Instant) ->
 
     // Install package
     let result = client
-        .install_package(storage, package_name, version)
+        .install_package(storage, package_name, version, allow_insecure)
         .await;
 
     match result {
         Ok(install_result) => {
             // Display results
             install_result.display();
+            if !install_result.blocked.is_empty() {
+                std::process::exit(1);
+            }
             println!(
                 "\n⏱️  Install completed ({}ms)",
                 start.elapsed().as_millis()
@@ -76,6 +84,7 @@ pub async fn handle_install(storage: &Storage, package: &str, start: Instant) ->
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_publish(
     storage: &Storage,
     prompt_name: &str,
@@ -83,6 +92,7 @@ pub async fn handle_publish(
     version: &str,
     tags: Option<&str>,
     bank: &str,
+    allow_insecure: bool,
     start: Instant,
 ) -> Result<()> {
     // Check authentication first before doing any other work
@@ -105,6 +115,8 @@ pub async fn handle_publish(
 
     // Read the prompt
     let (metadata, body) = storage.read_prompt(&resolved_name)?;
+    super::security::enforce_gate(storage, "publish", &resolved_name, &body, allow_insecure)?;
+    let body = super::redact::redact_outgoing(storage, &resolved_name, &body)?;
 
     // Use provided description or fallback to metadata description
     let final_description = description.unwrap_or(&metadata.description);
@@ -520,19 +532,17 @@ fn resolve_prompt_name(storage: &Storage, query: &str) -> Result<String> {
 
     // Regular prompt resolution (no bank specified)
     let prompt_names = storage.list_prompts()?;
+    let short_codes = ShortCodeRegistry::load_for(storage, &prompt_names)?;
     let mut prompts = Vec::new();
 
     for prompt_name in &prompt_names {
         if let Ok((metadata, _)) = storage.read_prompt(prompt_name) {
             prompts.push(Prompt {
                 name: prompt_name.clone(),
-                short_code: Matcher::generate_short_code(
-                    prompt_name,
-                    &prompts
-                        .iter()
-                        .map(|p: &Prompt| p.short_code.clone())
-                        .collect::<Vec<_>>(),
-                ),
+                short_code: short_codes
+                    .code_for(prompt_name)
+                    .unwrap_or_default()
+                    .to_string(),
                 description: metadata.description,
                 version: metadata.version,
                 created_at: metadata.created_at,
@@ -0,0 +1,280 @@
+// Security commands - prompt audits and the policy gate for content leaving the library
+
+use anyhow::Result;
+use colored::*;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use super::common::resolve_prompt_name;
+use crate::security::{
+    status_for, SecurityAuditReport, SecurityAuditSummary, SecurityScanResult, SecuritySeverity,
+    SecurityStatus,
+};
+use crate::security_policy::{fingerprint, SecurityBaseline};
+use crate::{GateDecision, GateResult, SecurityScanner, Storage};
+
+/// Audit one prompt or the whole library
+///
+/// Findings recorded in the baseline are hidden unless `no_baseline` is set;
+/// `update_baseline` accepts all current findings.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_security(
+    storage: &Storage,
+    prompt: Option<&str>,
+    all: bool,
+    format: &str,
+    summary: bool,
+    fail_on: Option<&str>,
+    update_baseline: bool,
+    no_baseline: bool,
+    start: Instant,
+) -> Result<()> {
+    if !matches!(format, "table" | "json" | "sarif" | "junit") {
+        eprintln!("Error: Unknown format '{}'. Use: table, json, sarif, junit", format);
+        std::process::exit(1);
+    }
+
+    let threshold = match fail_on.map(str::parse::<SecuritySeverity>) {
+        Some(Ok(severity)) => Some(severity),
+        Some(Err(e)) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    let prompts = match (prompt, all) {
+        (Some(prompt), _) => vec![resolve_prompt_name(storage, prompt)?],
+        (None, true) => storage.list_prompts()?,
+        (None, false) => {
+            eprintln!("Error: Specify a prompt to scan, or --all for the whole library");
+            std::process::exit(1);
+        }
+    };
+
+    let scanner = SecurityScanner::for_library(storage.base_dir())?;
+    let baseline_path = scanner.policy().baseline_path(storage.base_dir());
+    let mut baseline = SecurityBaseline::load(&baseline_path)?;
+
+    let mut results = Vec::new();
+    let mut hidden = 0;
+    for name in &prompts {
+        let path = storage.prompt_path(name);
+        let content = fs::read_to_string(&path)?;
+        let relative = relative_path(storage.base_dir(), &path);
+        let mut result = scanner.scan_prompt(&content, name, &relative);
+
+        let fingerprints: Vec<String> = result
+            .issues
+            .iter()
+            .map(|issue| fingerprint(issue, name, &content))
+            .collect();
+
+        if update_baseline {
+            baseline.findings.extend(fingerprints);
+        } else if !no_baseline {
+            let before = result.issues.len();
+            let mut known = fingerprints.iter().map(|f| baseline.contains(f));
+            result.issues.retain(|_| !known.next().unwrap_or(false));
+            hidden += before - result.issues.len();
+            result.status = status_for(&result.issues);
+        }
+
+        results.push((name.clone(), result));
+    }
+
+    if update_baseline {
+        baseline.save(&baseline_path)?;
+        println!(
+            "✓ Baseline updated with {} finding(s) at {} ({}ms)",
+            baseline.findings.len(),
+            baseline_path.display(),
+            start.elapsed().as_millis()
+        );
+        return Ok(());
+    }
+
+    let report = build_report(storage, &results, start);
+
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        "sarif" => println!("{}", serde_json::to_string_pretty(&report.to_sarif())?),
+        "junit" => print!(
+            "{}",
+            report.to_junit(threshold.as_ref().unwrap_or(&SecuritySeverity::Low))
+        ),
+        _ => print_table(&report, &results, all, summary, hidden, start),
+    }
+
+    // CI exit code: fail when any finding reaches the --fail-on threshold
+    if let Some(threshold) = threshold {
+        if report.fails_at(&threshold) {
+            if format == "table" {
+                eprintln!(
+                    "{}: Findings at or above {:?} severity",
+                    "Error".red(),
+                    threshold
+                );
+            }
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_table(
+    report: &SecurityAuditReport,
+    results: &[(String, SecurityScanResult)],
+    all: bool,
+    summary: bool,
+    hidden: usize,
+    start: Instant,
+) {
+    println!(
+        "🔒 {} ({}ms)\n",
+        format!("Security scan of {} prompt(s)", results.len()).green(),
+        start.elapsed().as_millis()
+    );
+
+    if !summary {
+        for (name, result) in results {
+            if result.issues.is_empty() && all {
+                continue;
+            }
+            println!("  {}  {}", status_label(&result.status), name.bold());
+            for issue in &result.issues {
+                let line = issue
+                    .line_number
+                    .map(|line| format!("line {}", line))
+                    .unwrap_or_default();
+                println!(
+                    "    {:<9} [{:?}] {} ({}, {} confidence)",
+                    line,
+                    issue.severity,
+                    issue.description,
+                    issue.rule_id.dimmed(),
+                    issue.confidence
+                );
+                if let Some(matched) = &issue.matched_content {
+                    println!("              {}", matched.dimmed());
+                }
+            }
+        }
+        println!();
+    }
+
+    let s = &report.summary;
+    println!(
+        "Summary: {} scanned, {} clean, {} warning, {} critical, {} blocked",
+        s.total_files, s.clean_files, s.warning_files, s.critical_files, s.blocked_files
+    );
+    if hidden > 0 {
+        println!(
+            "  {} known finding(s) hidden by the baseline (--no-baseline to show)",
+            hidden
+        );
+    }
+    if s.total_files > s.clean_files {
+        println!(
+            "  Silence a false positive with {} in the prompt or in security.toml",
+            "<!-- ph-security-ignore: rule-id -->".dimmed()
+        );
+    }
+}
+
+fn build_report(
+    storage: &Storage,
+    results: &[(String, SecurityScanResult)],
+    start: Instant,
+) -> SecurityAuditReport {
+    let count = |status: SecurityStatus| results.iter().filter(|(_, r)| r.status == status).count();
+
+    SecurityAuditReport {
+        directory: storage.base_dir().to_string_lossy().to_string(),
+        scan_results: results.iter().map(|(_, r)| r.clone()).collect(),
+        summary: SecurityAuditSummary {
+            total_files: results.len(),
+            clean_files: count(SecurityStatus::Clean),
+            warning_files: count(SecurityStatus::Warning),
+            critical_files: count(SecurityStatus::Critical),
+            blocked_files: count(SecurityStatus::Blocked),
+            audit_duration_ms: start.elapsed().as_millis() as u64,
+        },
+    }
+}
+
+fn status_label(status: &SecurityStatus) -> ColoredString {
+    match status {
+        SecurityStatus::Clean => "✓ Clean   ".green(),
+        SecurityStatus::Warning => "⚠ Warning ".yellow(),
+        SecurityStatus::Critical => "✗ Critical".red(),
+        SecurityStatus::Blocked => "✗ Blocked ".red().bold(),
+    }
+}
+
+fn relative_path(base_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(base_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Stop `action` when content has Critical or Blocked findings
+///
+/// Runs before publish, share and sync push, on the content as written:
+/// redaction comes after, so a secret it would hide still blocks. With
+/// `--allow-insecure` the findings are printed as a warning and the action
+/// continues.
+pub fn enforce_gate(
+    storage: &Storage,
+    action: &str,
+    subject: &str,
+    content: &str,
+    allow_insecure: bool,
+) -> Result<()> {
+    let gate = SecurityScanner::for_library(storage.base_dir())?.gate(
+        action,
+        subject,
+        content,
+        allow_insecure,
+    );
+
+    match gate.decision {
+        GateDecision::Allowed => Ok(()),
+        GateDecision::Overridden => {
+            eprintln!(
+                "⚠️  {} has security findings, continuing because of --allow-insecure",
+                subject.bold()
+            );
+            print_findings(&gate);
+            Ok(())
+        }
+        GateDecision::Blocked => {
+            eprintln!(
+                "{}: Refusing to {} '{}': security scan found critical issues",
+                "Error".red(),
+                action,
+                subject
+            );
+            print_findings(&gate);
+            eprintln!("\nFix the prompt, or pass --allow-insecure to {} anyway", action);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Print the blocking findings of a gate result on stderr
+pub fn print_findings(gate: &GateResult) {
+    for issue in gate.blocking_issues() {
+        let location = issue
+            .line_number
+            .map(|line| format!("line {}: ", line))
+            .unwrap_or_default();
+        eprintln!("  ✗ {}{}", location, issue.description.red());
+        if let Some(matched) = &issue.matched_content {
+            eprintln!("      {}", matched.dimmed());
+        }
+    }
+}
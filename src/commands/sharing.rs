@@ -46,6 +46,7 @@ pub enum SuggestionsCommands {
     },
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_share(
     storage: &Storage,
     prompt: &str,
@@ -53,6 +54,7 @@ pub async fn handle_share(
     invite: Option<&str>,
     allow_suggestions: bool,
     expires: Option<u32>,
+    allow_insecure: bool,
     start: Instant,
 ) -> Result<()> {
     // Check if user has API key (Pro feature)
@@ -71,7 +73,16 @@ pub async fn handle_share(
     }
 
     if public {
-        handle_share_public(storage, &client, prompt, allow_suggestions, expires, start).await
+        handle_share_public(
+            storage,
+            &client,
+            prompt,
+            allow_suggestions,
+            expires,
+            allow_insecure,
+            start,
+        )
+        .await
     } else if let Some(emails) = invite {
         handle_share_invite(
             storage,
@@ -80,6 +91,7 @@ pub async fn handle_share(
             emails,
             allow_suggestions,
             expires,
+            allow_insecure,
             start,
         )
         .await
@@ -94,6 +106,7 @@ async fn handle_share_public(
     prompt: &str,
     allow_suggestions: bool,
     expires: Option<u32>,
+    allow_insecure: bool,
     start: Instant,
 ) -> Result<()> {
     // Resolve prompt name using fuzzy matching
@@ -101,6 +114,8 @@ async fn handle_share_public(
 
     // Read the prompt
     let (metadata, body) = storage.read_prompt(&resolved_name)?;
+    super::security::enforce_gate(storage, "share", &resolved_name, &body, allow_insecure)?;
+    let body = super::redact::redact_outgoing(storage, &resolved_name, &body)?;
 
     println!("🌐 {}", "Creating public sharing link...".blue());
 
@@ -158,6 +173,7 @@ async fn handle_share_public(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_share_invite(
     storage: &Storage,
     client: &RegistryClient,
@@ -165,6 +181,7 @@ async fn handle_share_invite(
     emails: &str,
     allow_suggestions: bool,
     expires: Option<u32>,
+    allow_insecure: bool,
     start: Instant,
 ) -> Result<()> {
     // Parse email list
@@ -195,6 +212,8 @@ async fn handle_share_invite(
 
     // Read the prompt
     let (metadata, body) = storage.read_prompt(&resolved_name)?;
+    super::security::enforce_gate(storage, "share", &resolved_name, &body, allow_insecure)?;
+    let body = super::redact::redact_outgoing(storage, &resolved_name, &body)?;
 
     println!("📧 {}", "Creating private sharing invitations...".blue());
 
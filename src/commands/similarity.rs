@@ -0,0 +1,197 @@
+// Similar-prompt commands - near-duplicate detection across the library
+
+use anyhow::Result;
+use colored::*;
+use std::io::Write;
+use std::time::Instant;
+
+use super::common::{is_interactive, resolve_prompt_name};
+use super::diff_merge::{merge_prompts, MergeOptions, MergeOutcome};
+use crate::similarity::{SimilarityIndex, NEAR_DUPLICATE_THRESHOLD};
+use crate::Storage;
+
+pub fn handle_similar(
+    storage: &Storage,
+    prompt: &str,
+    threshold: f64,
+    limit: usize,
+    start: Instant,
+) -> Result<()> {
+    let resolved = resolve_prompt_name(storage, prompt)?;
+    let index = SimilarityIndex::from_storage(storage)?;
+
+    if !index.contains(&resolved) {
+        println!("'{}' has no content to compare", resolved);
+        return Ok(());
+    }
+
+    let similar = index.similar_to(&resolved, threshold);
+
+    println!(
+        "🧬 {} {} ({}ms)\n",
+        "Prompts similar to".green(),
+        resolved.bold(),
+        start.elapsed().as_millis()
+    );
+
+    if similar.is_empty() {
+        println!("  No prompts above {:.0}% similarity", threshold * 100.0);
+        return Ok(());
+    }
+
+    for entry in similar.iter().take(limit) {
+        println!("  {:>4.0}%  {}", entry.score * 100.0, entry.name.bold());
+    }
+
+    if let Some(best) = similar.first() {
+        println!(
+            "\n  Compare with: {} diff {} {}",
+            "ph".bold(),
+            resolved,
+            best.name
+        );
+    }
+
+    Ok(())
+}
+
+pub fn handle_dedupe(storage: &Storage, threshold: f64, start: Instant) -> Result<()> {
+    let index = SimilarityIndex::from_storage(storage)?;
+    let clusters = index.clusters(threshold);
+
+    println!(
+        "🧬 {} ({}ms)\n",
+        format!("Near-duplicate clusters above {:.0}%", threshold * 100.0).green(),
+        start.elapsed().as_millis()
+    );
+
+    if clusters.is_empty() {
+        println!("  No near-duplicates found");
+        return Ok(());
+    }
+
+    for (number, cluster) in clusters.iter().enumerate() {
+        println!(
+            "  {} ({} prompts)",
+            format!("Cluster {}", number + 1).bold(),
+            cluster.prompts.len()
+        );
+        for pair in &cluster.pairs {
+            println!(
+                "    {:>4.0}%  {} ↔ {}",
+                pair.score * 100.0,
+                pair.first,
+                pair.second
+            );
+        }
+        println!();
+    }
+
+    if !is_interactive() {
+        println!("Review a pair with: {} diff <a> <b>", "ph".bold());
+        println!(
+            "Fold one into another with: {} merge <source> <target> --backup",
+            "ph".bold()
+        );
+        return Ok(());
+    }
+
+    // Walk the pairs and hand off to the existing diff/merge commands
+    for cluster in &clusters {
+        for pair in &cluster.pairs {
+            if !storage.prompt_exists(&pair.first) || !storage.prompt_exists(&pair.second) {
+                continue;
+            }
+
+            loop {
+                print!(
+                    "{} ↔ {}: [d]iff, [m]erge {} into {}, [s]kip, [q]uit? ",
+                    pair.first.bold(),
+                    pair.second.bold(),
+                    pair.second,
+                    pair.first
+                );
+                std::io::stdout().flush()?;
+
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+
+                match input.trim().to_lowercase().as_str() {
+                    "d" | "diff" => {
+                        super::diff_merge::handle_diff(
+                            storage,
+                            &pair.first,
+                            &pair.second,
+                            "unified",
+                            3,
+                            false,
+                            false,
+                            false,
+                            false,
+                            None,
+                            Instant::now(),
+                        )?;
+                    }
+                    "m" | "merge" => {
+                        // Conflicts are reported, not parked, so the walk can go on
+                        let options = MergeOptions {
+                            backup: true,
+                            no_conflicts: true,
+                            ..Default::default()
+                        };
+                        match merge_prompts(storage, &pair.second, &pair.first, options, Instant::now())? {
+                            MergeOutcome::Done => println!(
+                                "  Remove the duplicate with: {} delete {}",
+                                "ph".bold(),
+                                pair.second
+                            ),
+                            MergeOutcome::Conflicts { count, .. } => println!(
+                                "  ⚠ {} conflict(s); nothing was changed. Merge by hand with: {} merge {} {}",
+                                count,
+                                "ph".bold(),
+                                pair.second,
+                                pair.first
+                            ),
+                            MergeOutcome::InProgress { source, target } => println!(
+                                "  ⚠ A merge of '{}' into '{}' is in progress; finish it with {} merge --continue",
+                                source,
+                                target,
+                                "ph".bold()
+                            ),
+                            MergeOutcome::Rejected => {}
+                        }
+                        break;
+                    }
+                    "q" | "quit" => return Ok(()),
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Warn on stderr when content nearly matches prompts already in the library
+pub fn warn_near_duplicates(storage: &Storage, name: &str, content: &str) {
+    let index = match SimilarityIndex::from_storage(storage) {
+        Ok(index) => index,
+        Err(_) => return,
+    };
+
+    let similar = index.similar_to_text(content, Some(name), NEAR_DUPLICATE_THRESHOLD);
+    if let Some(best) = similar.first() {
+        eprintln!(
+            "⚠️  {} is {:.0}% similar to {}",
+            name.bold(),
+            best.score * 100.0,
+            best.name.bold()
+        );
+        eprintln!(
+            "   Compare with: {} diff {} {}",
+            "ph".bold(),
+            name,
+            best.name
+        );
+    }
+}
@@ -2,11 +2,70 @@
 
 use anyhow::Result;
 use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::time::Instant;
 
-use crate::{PerformanceVerifier, Storage};
+use crate::{IoOptions, PerformanceVerifier, Storage};
+
+/// Library statistics, the schema of `ph stats --json`
+#[derive(Debug, Default, Serialize)]
+pub struct LibraryStats {
+    /// All prompts, including bank and team prompts
+    pub prompts: usize,
+    /// Prompts outside banks and teams
+    pub root_prompts: usize,
+    /// Prompt count per bank
+    pub banks: BTreeMap<String, usize>,
+    /// Prompt count per team
+    pub teams: BTreeMap<String, usize>,
+    /// Total size of all prompt files in bytes
+    pub total_bytes: u64,
+}
+
+impl LibraryStats {
+    pub fn collect(storage: &Storage) -> Result<Self> {
+        let mut stats = Self::default();
+
+        for name in storage.list_prompts()? {
+            stats.prompts += 1;
+            stats.total_bytes += std::fs::metadata(storage.prompt_path(&name))
+                .map(|m| m.len())
+                .unwrap_or(0);
+
+            if let Some(team) = name.strip_prefix('@').and_then(|n| n.split('/').next()) {
+                *stats.teams.entry(team.to_string()).or_insert(0) += 1;
+            } else if let Some((bank, _)) = name.split_once('/') {
+                *stats.banks.entry(bank.to_string()).or_insert(0) += 1;
+            } else {
+                stats.root_prompts += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+pub fn handle_stats(storage: &Storage, io_options: &IoOptions, start: Instant) -> Result<()> {
+    let stats = LibraryStats::collect(storage)?;
+    if io_options.is_structured() {
+        return io_options.emit(&stats);
+    }
+
+    println!("📚 {}", "Library".bold());
+    println!(
+        "  {} prompts ({} root, {} in banks, {} in teams), {} KB",
+        stats.prompts,
+        stats.root_prompts,
+        stats.banks.values().sum::<usize>(),
+        stats.teams.values().sum::<usize>(),
+        stats.total_bytes.div_ceil(1024)
+    );
+    for (bank, count) in &stats.banks {
+        println!("  {:<20} {}", bank, count);
+    }
+    println!();
 
-pub fn handle_stats(start: Instant) -> Result<()> {
     println!("📊 Telemetry Statistics");
     println!("For detailed telemetry data, use: ph config telemetry show");
     println!("To enable telemetry collection: ph config telemetry enable");
@@ -134,6 +193,7 @@ mod tests {
                 version: None,
                 git_hash: None,
                 parent_version: None,
+                source: None,
             };
             storage
                 .write_prompt(
@@ -445,7 +445,7 @@ fn get_user_email() -> Result<String> {
                         None
                     }
                 })
-                .ok_or_else(|| std::env::VarError::NotPresent)
+                .ok_or(std::env::VarError::NotPresent)
         })
         .context("User email not configured. Set PROMPTHIVE_USER_EMAIL environment variable or configure git user.email")
 }
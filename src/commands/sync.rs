@@ -1,11 +1,17 @@
 use super::common;
-use crate::{PromptMetadata, RegistryClient, Storage};
+use crate::cloud_sync::{
+    classify, prompt_hash, CloudPrompt, CloudPromptList, CloudState, CloudSummary, PushPrompt, PushRequest,
+    PushResponse, PushStatus, SyncBases,
+};
+use crate::{IoOptions, PromptMetadata, RegistryClient, Storage};
 use super::SimpleSyncManager;
 use anyhow::{Context, Result};
 use chrono;
 use clap::Subcommand;
 use colored::Colorize;
+use serde::Serialize;
 use serde_json;
+use std::collections::BTreeMap;
 use std::time::Instant;
 use urlencoding;
 
@@ -25,6 +31,9 @@ pub enum SyncCommands {
         /// Force push even if conflicts exist
         #[arg(short = 'f', long = "force")]
         force: bool,
+        /// Push even if the security scan finds critical issues
+        #[arg(long = "allow-insecure")]
+        allow_insecure: bool,
     },
     /// Pull cloud prompts to local storage
     Pull {
@@ -108,9 +117,9 @@ pub enum SyncCommands {
         #[arg(short = 'v', long = "verbose")]
         verbose: bool,
     },
-    /// Watch files for changes and auto-sync
+    /// Watch synced files and their prompts, copying changes either way
     Watch {
-        /// Directory to watch (default: current directory)
+        /// Only watch synced files under this directory (default: all)
         directory: Option<String>,
         /// Debounce delay in milliseconds (default: 100)
         #[arg(short = 'd', long = "delay")]
@@ -121,8 +130,20 @@ pub enum SyncCommands {
 pub async fn handle_sync(
     storage: &Storage,
     action: &Option<SyncCommands>,
+    io_options: &IoOptions,
     start: Instant,
 ) -> Result<()> {
+    // File sync is local, so it doesn't need an account
+    match action {
+        Some(SyncCommands::SyncFile { path, name, force }) => {
+            return handle_sync_file(storage, path, name.as_deref(), *force, start).await;
+        }
+        Some(SyncCommands::Watch { directory, delay }) => {
+            return handle_sync_watch(storage, directory.as_deref(), *delay, start);
+        }
+        _ => {}
+    }
+
     // Check if user has API key
     let api_key = common::require_api_key("Sync")?;
 
@@ -130,14 +151,26 @@ pub async fn handle_sync(
     let client = RegistryClient::new(registry_url).with_api_key(api_key);
 
     match action {
-        Some(SyncCommands::Push { prompt, force }) => {
-            handle_sync_push(storage, &client, prompt.as_deref(), *force, start).await
+        Some(SyncCommands::Push {
+            prompt,
+            force,
+            allow_insecure,
+        }) => {
+            handle_sync_push(
+                storage,
+                &client,
+                prompt.as_deref(),
+                *force,
+                *allow_insecure,
+                start,
+            )
+            .await
         }
         Some(SyncCommands::Pull { prompt, force }) => {
             handle_sync_pull(storage, &client, prompt.as_deref(), *force, start).await
         }
         Some(SyncCommands::Status { verbose }) => {
-            handle_sync_status(storage, &client, *verbose, start).await
+            handle_sync_status(storage, &client, *verbose, io_options, start).await
         }
         Some(SyncCommands::Resolve { prompt, resolution }) => {
             handle_sync_resolve(storage, &client, prompt, resolution, start).await
@@ -145,9 +178,7 @@ pub async fn handle_sync(
         Some(SyncCommands::Verify { prompt, verbose }) => {
             handle_sync_verify(storage, &client, prompt.as_deref(), *verbose, start).await
         }
-        Some(SyncCommands::SyncFile { path, name, force }) => {
-            handle_sync_file(storage, path, name.as_deref(), *force, start).await
-        }
+        Some(SyncCommands::SyncFile { .. }) => unreachable!("handled above"),
         Some(SyncCommands::SyncDir { directory: _, pattern: _, force: _ }) => {
             Err(anyhow::anyhow!("SyncDir functionality temporarily disabled - coming soon"))
         }
@@ -163,9 +194,7 @@ pub async fn handle_sync(
         Some(SyncCommands::Conflicts { verbose: _ }) => {
             Err(anyhow::anyhow!("Conflicts functionality temporarily disabled - coming soon"))
         }
-        Some(SyncCommands::Watch { directory: _, delay: _ }) => {
-            Err(anyhow::anyhow!("Watch functionality temporarily disabled - coming soon"))
-        }
+        Some(SyncCommands::Watch { .. }) => unreachable!("handled above"),
         None => {
             // Default sync (bidirectional)
             handle_sync_bidirectional(storage, &client, start).await
@@ -176,11 +205,201 @@ pub async fn handle_sync(
 // Note: The actual sync function implementations will be extracted from main.rs in the next step
 // This is a placeholder to establish the module structure
 
+/// Both copies of a prompt and where they stand against the last sync
+struct CloudComparison {
+    local: Option<(PromptMetadata, String)>,
+    remote: Option<CloudSummary>,
+    has_base: bool,
+    state: CloudState,
+}
+
+impl CloudComparison {
+    /// Why the prompt isn't in sync, for status output
+    fn detail(&self) -> &'static str {
+        match self.state {
+            CloudState::InSync => "in sync",
+            CloudState::LocalChange if self.remote.is_none() => "not in cloud",
+            CloudState::LocalChange => "changed locally",
+            CloudState::RemoteChange if self.local.is_none() => "cloud only",
+            CloudState::RemoteChange => "changed in the cloud",
+            CloudState::Conflict if self.has_base => "changed locally and in the cloud",
+            CloudState::Conflict => "differs and was never synced",
+        }
+    }
+}
+
+/// Fetch one prompt from the cloud; `None` if it isn't there
+async fn fetch_cloud_prompt(client: &RegistryClient, name: &str) -> Result<Option<CloudPrompt>> {
+    let url = format!("/api/prompts/{}", urlencoding::encode(name));
+    let response = client.get(&url).await?;
+    let status = response.status();
+    if status.as_u16() == 404 {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(anyhow::anyhow!("Cloud API error: {}", status));
+    }
+    let mut prompt: CloudPrompt = response
+        .json()
+        .await
+        .context("Failed to parse cloud response")?;
+    if prompt.name.is_empty() {
+        prompt.name = name.to_string();
+    }
+    Ok(Some(prompt))
+}
+
+/// Hash of a local prompt as a push would send it (after redaction)
+fn outgoing_hash(storage: &Storage, name: &str, metadata: &PromptMetadata, content: &str) -> Result<String> {
+    let content = crate::redaction::redact_prompt(storage, name, content)?.content;
+    Ok(prompt_hash(
+        &content,
+        &metadata.description,
+        metadata.tags.as_deref().unwrap_or_default(),
+    ))
+}
+
+/// The cloud copy of a prompt that is about to be pulled
+async fn pull_cloud_prompt(client: &RegistryClient, name: &str) -> Result<CloudPrompt> {
+    fetch_cloud_prompt(client, name)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("'{}' is no longer in the cloud", name))
+}
+
+/// Compare a prompt's local copy and its cloud listing with the last synced state
+fn compare_with_cloud(
+    storage: &Storage,
+    bases: &SyncBases,
+    name: &str,
+    remote: Option<&CloudSummary>,
+) -> Result<CloudComparison> {
+    let local = if storage.prompt_exists(name) {
+        Some(storage.read_prompt(name)?)
+    } else {
+        None
+    };
+    let local_hash = match &local {
+        Some((metadata, content)) => Some(outgoing_hash(storage, name, metadata, content)?),
+        None => None,
+    };
+    let base = bases.get(name);
+    let state = classify(
+        base.map(|base| base.hash.as_str()),
+        local_hash.as_deref(),
+        remote.map(|remote| remote.hash.as_str()),
+    );
+    Ok(CloudComparison {
+        local,
+        remote: remote.cloned(),
+        has_base: base.is_some(),
+        state,
+    })
+}
+
+/// Gate and redact a local prompt, ready to push on top of the cloud copy `base`
+fn outgoing_prompt(
+    storage: &Storage,
+    name: &str,
+    metadata: &PromptMetadata,
+    content: &str,
+    base: Option<&CloudSummary>,
+    allow_insecure: bool,
+) -> Result<PushPrompt> {
+    super::security::enforce_gate(storage, "push", name, content, allow_insecure)?;
+    let content = super::redact::redact_outgoing(storage, name, content)?;
+    Ok(PushPrompt {
+        name: name.to_string(),
+        content,
+        description: metadata.description.clone(),
+        tags: metadata.tags.clone().unwrap_or_default(),
+        base_revision: base.and_then(|base| base.revision),
+        base_hash: base.map(|base| base.hash.clone()).filter(|hash| !hash.is_empty()),
+    })
+}
+
+async fn send_push(client: &RegistryClient, request: &PushRequest) -> Result<PushResponse> {
+    let response = client
+        .post("/api/sync/push", &serde_json::to_value(request)?)
+        .await
+        .context("Failed to push prompts to cloud")?;
+
+    let status = response.status();
+    let response_text = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(anyhow::anyhow!(
+            "Sync push failed with status {}: {}",
+            status,
+            response_text
+        ));
+    }
+
+    serde_json::from_str(&response_text).context(format!(
+        "Failed to parse sync push response: {}",
+        response_text
+    ))
+}
+
+/// Save the cloud copy locally, keeping the local prompt's own metadata
+fn write_cloud_prompt(storage: &Storage, cloud: &CloudPrompt, local: Option<PromptMetadata>) -> Result<()> {
+    let tags = if cloud.tags.is_empty() { None } else { Some(cloud.tags.clone()) };
+    let metadata = match local {
+        Some(local) => PromptMetadata {
+            description: cloud.description.clone(),
+            tags,
+            updated_at: Some(chrono::Utc::now().to_rfc3339()),
+            ..local
+        },
+        None => PromptMetadata {
+            id: cloud.name.clone(),
+            description: cloud.description.clone(),
+            tags,
+            created_at: Some(chrono::Utc::now().to_rfc3339()),
+            updated_at: None,
+            version: None,
+            git_hash: None,
+            parent_version: None,
+            source: None,
+        },
+    };
+    storage.write_prompt(&cloud.name, &metadata, &cloud.content)
+}
+
+/// Every prompt in the cloud with its hash and revision, by name
+async fn fetch_cloud_listing(client: &RegistryClient) -> Result<BTreeMap<String, CloudSummary>> {
+    let response = client
+        .get("/api/prompts")
+        .await
+        .map_err(|e| anyhow::anyhow!("Request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Failed to fetch cloud prompts: {}",
+            response.status()
+        ));
+    }
+    let list: CloudPromptList = response
+        .json()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to parse cloud prompts: {}", e))?;
+
+    let mut listing = BTreeMap::new();
+    for mut summary in list.prompts {
+        // Servers without hashes in the list need the full prompt to compare
+        if summary.hash.is_empty() {
+            if let Some(prompt) = fetch_cloud_prompt(client, &summary.name).await? {
+                summary = CloudSummary::from(&prompt);
+            }
+        }
+        listing.insert(summary.name.clone(), summary);
+    }
+    Ok(listing)
+}
+
 async fn handle_sync_push(
     storage: &Storage,
     client: &RegistryClient,
     prompt: Option<&str>,
     force: bool,
+    allow_insecure: bool,
     start: Instant,
 ) -> Result<()> {
     println!("☁️  Pushing prompts to cloud...");
@@ -199,153 +418,165 @@ async fn handle_sync_push(
         return Ok(());
     }
 
-    // Build prompts payload
-    let mut prompts_data = Vec::new();
-    for prompt_name in &prompts_to_sync {
-        let (metadata, content) = storage
-            .read_prompt(prompt_name)
-            .with_context(|| format!("Failed to read prompt '{}'", prompt_name))?;
+    let mut bases = SyncBases::load(storage)?;
+    let listing = fetch_cloud_listing(client).await?;
+    let mut request = PushRequest {
+        prompts: Vec::new(),
+        force,
+    };
+    let mut up_to_date = 0;
+    let mut behind = 0;
+    let mut conflicts = 0;
 
-        prompts_data.push(serde_json::json!({
-            "name": prompt_name,
-            "content": content,
-            "description": metadata.description,
-            "tags": metadata.tags.unwrap_or_default()
-        }));
-    }
+    // Only push what changed here; cloud-side changes would be overwritten
+    for prompt_name in &prompts_to_sync {
+        let comparison = compare_with_cloud(storage, &bases, prompt_name, listing.get(prompt_name))
+            .with_context(|| format!("Failed to compare '{}' with the cloud", prompt_name))?;
+        let Some((metadata, content)) = &comparison.local else {
+            return Err(anyhow::anyhow!("Failed to read prompt '{}'", prompt_name));
+        };
+        let remote_revision = comparison.remote.as_ref().and_then(|remote| remote.revision);
 
-    let payload = serde_json::json!({
-        "prompts": prompts_data,
-        "force": force
-    });
+        match comparison.state {
+            CloudState::InSync => {
+                up_to_date += 1;
+                if let Some(remote) = &comparison.remote {
+                    bases.record(prompt_name, remote.hash.clone(), remote_revision);
+                }
+                continue;
+            }
+            CloudState::RemoteChange if !force => {
+                behind += 1;
+                println!("⏭️  Skipped: {} (changed in the cloud; run `ph sync pull` first)", prompt_name);
+                continue;
+            }
+            CloudState::Conflict if !force => {
+                conflicts += 1;
+                println!(
+                    "⚠️  Conflict: {} ({}; use `ph sync resolve {}`)",
+                    prompt_name,
+                    comparison.detail(),
+                    prompt_name
+                );
+                continue;
+            }
+            _ => {}
+        }
 
-    // Send sync push request
-    let response = client
-        .post("/api/sync/push", &payload)
-        .await
-        .context("Failed to push prompts to cloud")?;
+        request.prompts.push(outgoing_prompt(
+            storage,
+            prompt_name,
+            metadata,
+            content,
+            comparison.remote.as_ref(),
+            allow_insecure,
+        )?);
+    }
 
-    let status = response.status();
-    let response_text = response.text().await.unwrap_or_default();
+    let mut created = 0;
+    let mut updated = 0;
+    let mut errors = 0;
+    let mut sync_success = true;
+    let mut sync_message = "Sync completed".to_string();
+
+    if !request.prompts.is_empty() {
+        let response = send_push(client, &request).await?;
+        sync_success = response.success;
+        if let Some(message) = response.message {
+            sync_message = message;
+        }
 
-    if !status.is_success() {
-        return Err(anyhow::anyhow!(
-            "Sync push failed with status {}: {}",
-            status,
-            response_text
-        ));
-    }
+        if response.results.is_empty() {
+            println!("⚠️  Warning: No detailed results received from server");
+            if !sync_success {
+                return Err(anyhow::anyhow!("Sync push failed: {}", sync_message));
+            }
+        }
 
-    let result: serde_json::Value = serde_json::from_str(&response_text).context(format!(
-        "Failed to parse sync push response: {}",
-        response_text
-    ))?;
-
-    let sync_success = result
-        .get("success")
-        .and_then(|s| s.as_bool())
-        .unwrap_or(false);
-    let sync_message = result
-        .get("message")
-        .and_then(|m| m.as_str())
-        .unwrap_or("Sync completed");
-
-    // Process results
-    if let Some(results) = result.get("results").and_then(|r| r.as_array()) {
-        let mut created = 0;
-        let mut updated = 0;
-        let mut conflicts = 0;
-        let mut errors = 0;
-
-        for result_item in results {
-            let name = result_item
-                .get("name")
-                .and_then(|n| n.as_str())
-                .unwrap_or("unknown");
-            let status = result_item
-                .get("status")
-                .and_then(|s| s.as_str())
-                .unwrap_or("unknown");
-
-            match status {
-                "created" => {
-                    created += 1;
-                    println!("✅ Created: {}", name);
-                }
-                "updated" => {
-                    updated += 1;
-                    println!("🔄 Updated: {}", name);
+        for result in &response.results {
+            let pushed = request.prompts.iter().find(|p| p.name == result.name);
+            match result.status {
+                PushStatus::Created | PushStatus::Updated | PushStatus::Unchanged => {
+                    if let Some(pushed) = pushed {
+                        bases.record(&result.name, pushed.hash(), result.revision);
+                    }
+                    match result.status {
+                        PushStatus::Created => {
+                            created += 1;
+                            println!("✅ Created: {}", result.name);
+                        }
+                        PushStatus::Updated => {
+                            updated += 1;
+                            println!("🔄 Updated: {}", result.name);
+                        }
+                        _ => up_to_date += 1,
+                    }
                 }
-                "conflict" => {
+                PushStatus::Conflict => {
+                    // The cloud moved on between our check and the push
                     conflicts += 1;
-                    println!("⚠️  Conflict: {} (use `ph sync status` to resolve)", name);
+                    println!(
+                        "⚠️  Conflict: {} (changed in the cloud meanwhile; run `ph sync pull`)",
+                        result.name
+                    );
                 }
-                "error" => {
+                PushStatus::Error => {
                     errors += 1;
-                    let error = result_item
-                        .get("error")
-                        .and_then(|e| e.as_str())
-                        .unwrap_or("unknown error");
-                    println!("❌ Error: {} - {}", name, error);
+                    println!(
+                        "❌ Error: {} - {}",
+                        result.name,
+                        result.error.as_deref().unwrap_or("unknown error")
+                    );
                 }
-                _ => {
-                    println!("❓ Unknown status for {}: {}", name, status);
+                PushStatus::Unknown => {
+                    println!("❓ Unknown status for {}", result.name);
                 }
             }
         }
+    }
+    bases.save(storage)?;
 
-        // Summary
-        println!();
-        if sync_success {
-            println!(
-                "📊 {} {}",
-                "Sync Push Summary:".green(),
-                sync_message.green()
-            );
-        } else {
-            println!(
-                "📊 {} {}",
-                "Sync Push Summary:".yellow(),
-                sync_message.yellow()
-            );
-        }
-
-        if created > 0 {
-            println!("   ✅ Created: {}", created);
-        }
-        if updated > 0 {
-            println!("   🔄 Updated: {}", updated);
-        }
-        if conflicts > 0 {
-            println!(
-                "   ⚠️  Conflicts: {} (resolve with `ph sync status`)",
-                conflicts
-            );
-        }
-        if errors > 0 {
-            println!("   ❌ Errors: {} (check server logs or try again)", errors);
-            if !sync_success {
-                return Err(anyhow::anyhow!(
-                    "Sync push failed due to {} database errors",
-                    errors
-                ));
-            }
-        }
-
-        // Use stats from API if available
-        if let Some(stats) = result.get("stats") {
-            let api_errors = stats.get("errors").and_then(|e| e.as_u64()).unwrap_or(0);
-            if api_errors > 0 && !sync_success {
-                return Err(anyhow::anyhow!(
-                    "Server reported {} errors during sync push",
-                    api_errors
-                ));
-            }
-        }
+    // Summary
+    println!();
+    if sync_success {
+        println!(
+            "📊 {} {}",
+            "Sync Push Summary:".green(),
+            sync_message.green()
+        );
     } else {
-        println!("⚠️  Warning: No detailed results received from server");
+        println!(
+            "📊 {} {}",
+            "Sync Push Summary:".yellow(),
+            sync_message.yellow()
+        );
+    }
+
+    if created > 0 {
+        println!("   ✅ Created: {}", created);
+    }
+    if updated > 0 {
+        println!("   🔄 Updated: {}", updated);
+    }
+    if up_to_date > 0 {
+        println!("   💚 Already up to date: {}", up_to_date);
+    }
+    if behind > 0 {
+        println!("   ⏭️  Behind the cloud: {} (pull first with `ph sync pull`)", behind);
+    }
+    if conflicts > 0 {
+        println!(
+            "   ⚠️  Conflicts: {} (resolve with `ph sync resolve`, or --force to overwrite the cloud)",
+            conflicts
+        );
+    }
+    if errors > 0 {
+        println!("   ❌ Errors: {} (check server logs or try again)", errors);
         if !sync_success {
-            return Err(anyhow::anyhow!("Sync push failed: {}", sync_message));
+            return Err(anyhow::anyhow!(
+                "Sync push failed due to {} database errors",
+                errors
+            ));
         }
     }
 
@@ -366,41 +597,14 @@ async fn handle_sync_pull(
     println!("☁️  Pulling prompts from cloud...");
 
     // Get prompts to pull
+    let listing = fetch_cloud_listing(client).await?;
     let prompts_to_pull = if let Some(prompt_name) = prompt {
         // Pull specific prompt
         let resolved_name = resolve_prompt_name(storage, prompt_name)?;
         vec![resolved_name]
     } else {
         // Get all cloud prompts
-        match client.get("/api/prompts").await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    match response.json::<serde_json::Value>().await {
-                        Ok(cloud_data) => {
-                            if let Some(cloud_prompts) = cloud_data["prompts"].as_array() {
-                                cloud_prompts
-                                    .iter()
-                                    .filter_map(|p| p["name"].as_str().map(|s| s.to_string()))
-                                    .collect()
-                            } else {
-                                return Err(anyhow::anyhow!("Invalid cloud prompts response format"));
-                            }
-                        }
-                        Err(e) => {
-                            return Err(anyhow::anyhow!("Failed to parse cloud prompts: {}", e));
-                        }
-                    }
-                } else {
-                    return Err(anyhow::anyhow!(
-                        "Failed to fetch cloud prompts: {}",
-                        response.status()
-                    ));
-                }
-            }
-            Err(e) => {
-                return Err(anyhow::anyhow!("Request failed: {}", e));
-            }
-        }
+        listing.keys().cloned().collect()
     };
 
     if prompts_to_pull.is_empty() {
@@ -408,105 +612,80 @@ async fn handle_sync_pull(
         return Ok(());
     }
 
+    let mut bases = SyncBases::load(storage)?;
     let mut pulled = 0;
     let mut updated = 0;
+    let mut ahead = 0;
     let mut conflicts = 0;
     let mut errors = 0;
 
     for prompt_name in &prompts_to_pull {
         println!("📥 Pulling '{}'...", prompt_name);
 
-        // Fetch cloud prompt
-        let url = format!("/api/prompts/{}", urlencoding::encode(prompt_name));
-        match client.get(&url).await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    match response.json::<serde_json::Value>().await {
-                        Ok(cloud_data) => {
-                            let cloud_content = cloud_data["content"].as_str().unwrap_or("");
-                            let cloud_description = cloud_data["description"].as_str().unwrap_or("");
-                            let cloud_tags: Vec<String> = cloud_data["tags"]
-                                .as_array()
-                                .map(|arr| {
-                                    arr.iter()
-                                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                                        .collect()
-                                })
-                                .unwrap_or_default();
-
-                            // Check if local version exists
-                            let local_exists = storage.prompt_exists(prompt_name);
-
-                            if local_exists && !force {
-                                // Check for conflicts
-                                match storage.read_prompt(prompt_name) {
-                                    Ok((local_metadata, local_content)) => {
-                                        let content_matches = local_content.trim() == cloud_content.trim();
-                                        let description_matches = local_metadata.description == cloud_description;
-
-                                        if !content_matches || !description_matches {
-                                            conflicts += 1;
-                                            println!("⚠️  Conflict detected for '{}' (use --force to overwrite)", prompt_name);
-                                            continue;
-                                        } else {
-                                            println!("✅ '{}' is already up to date", prompt_name);
-                                            continue;
-                                        }
-                                    }
-                                    Err(e) => {
-                                        errors += 1;
-                                        println!("❌ Failed to read local '{}': {}", prompt_name, e);
-                                        continue;
-                                    }
-                                }
-                            }
-
-                            // Create/update local prompt
-                            let metadata = PromptMetadata {
-                                id: prompt_name.clone(),
-                                description: cloud_description.to_string(),
-                                tags: if cloud_tags.is_empty() { None } else { Some(cloud_tags) },
-                                created_at: Some(chrono::Utc::now().to_rfc3339()),
-                                updated_at: None,
-                                version: None,
-                                git_hash: None,
-                                parent_version: None,
-                            };
-
-                            match storage.write_prompt(prompt_name, &metadata, cloud_content) {
-                                Ok(_) => {
-                                    if local_exists {
-                                        updated += 1;
-                                        println!("🔄 Updated '{}'", prompt_name);
-                                    } else {
-                                        pulled += 1;
-                                        println!("📥 Pulled '{}'", prompt_name);
-                                    }
-                                }
-                                Err(e) => {
-                                    errors += 1;
-                                    println!("❌ Failed to save '{}': {}", prompt_name, e);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            errors += 1;
-                            println!("❌ Failed to parse cloud response for '{}': {}", prompt_name, e);
-                        }
-                    }
-                } else if response.status().as_u16() == 404 {
-                    println!("⚠️  '{}' not found in cloud", prompt_name);
+        let comparison = match compare_with_cloud(storage, &bases, prompt_name, listing.get(prompt_name)) {
+            Ok(comparison) => comparison,
+            Err(e) => {
+                errors += 1;
+                println!("❌ Failed to read '{}': {}", prompt_name, e);
+                continue;
+            }
+        };
+        let Some(remote) = &comparison.remote else {
+            println!("⚠️  '{}' not found in cloud", prompt_name);
+            continue;
+        };
+
+        match comparison.state {
+            CloudState::InSync => {
+                bases.record(prompt_name, remote.hash.clone(), remote.revision);
+                println!("✅ '{}' is already up to date", prompt_name);
+                continue;
+            }
+            CloudState::LocalChange if !force => {
+                ahead += 1;
+                println!("⏭️  '{}' has local changes to push (run `ph sync push`)", prompt_name);
+                continue;
+            }
+            CloudState::Conflict if !force => {
+                conflicts += 1;
+                println!(
+                    "⚠️  Conflict detected for '{}' ({}; use `ph sync resolve` or --force to overwrite)",
+                    prompt_name,
+                    comparison.detail()
+                );
+                continue;
+            }
+            _ => {}
+        }
+
+        let cloud = match pull_cloud_prompt(client, prompt_name).await {
+            Ok(cloud) => cloud,
+            Err(e) => {
+                errors += 1;
+                println!("❌ Failed to fetch '{}': {}", prompt_name, e);
+                continue;
+            }
+        };
+        let local_exists = comparison.local.is_some();
+        let local_metadata = comparison.local.clone().map(|(metadata, _)| metadata);
+        match write_cloud_prompt(storage, &cloud, local_metadata) {
+            Ok(_) => {
+                bases.record(prompt_name, cloud.hash(), cloud.revision);
+                if local_exists {
+                    updated += 1;
+                    println!("🔄 Updated '{}'", prompt_name);
                 } else {
-                    errors += 1;
-                    println!("❌ Cloud API error for '{}': {}", prompt_name, response.status());
+                    pulled += 1;
+                    println!("📥 Pulled '{}'", prompt_name);
                 }
             }
             Err(e) => {
                 errors += 1;
-                println!("❌ Request failed for '{}': {}", prompt_name, e);
+                println!("❌ Failed to save '{}': {}", prompt_name, e);
             }
         }
     }
+    bases.save(storage)?;
 
     // Summary
     println!();
@@ -518,6 +697,9 @@ async fn handle_sync_pull(
     if updated > 0 {
         println!("   🔄 Updated prompts: {}", updated);
     }
+    if ahead > 0 {
+        println!("   ⏭️  Local changes (skipped): {} (push with `ph sync push`)", ahead);
+    }
     if conflicts > 0 {
         println!("   ⚠️  Conflicts (skipped): {} (use --force to overwrite)", conflicts);
     }
@@ -525,7 +707,7 @@ async fn handle_sync_pull(
         println!("   ❌ Errors: {}", errors);
     }
 
-    if pulled == 0 && updated == 0 && conflicts == 0 && errors == 0 {
+    if pulled == 0 && updated == 0 && ahead == 0 && conflicts == 0 && errors == 0 {
         println!("   📭 No changes to pull");
     }
 
@@ -536,155 +718,158 @@ async fn handle_sync_pull(
     Ok(())
 }
 
+/// `ph sync status --json`
+#[derive(Serialize)]
+struct SyncStatusReport {
+    synced: usize,
+    pending_push: usize,
+    pending_pull: usize,
+    conflicts: usize,
+    errors: usize,
+    prompts: Vec<SyncStatusItem>,
+}
+
+/// State of one prompt: synced, pending_push, pending_pull, conflict or error
+#[derive(Serialize)]
+struct SyncStatusItem {
+    name: String,
+    state: &'static str,
+    detail: Option<String>,
+}
+
+impl SyncStatusItem {
+    fn new(name: &str, state: &'static str, detail: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            state,
+            detail,
+        }
+    }
+}
+
 async fn handle_sync_status(
     storage: &Storage,
     client: &RegistryClient,
     verbose: bool,
+    io_options: &IoOptions,
     start: Instant,
 ) -> Result<()> {
-    println!("🔄 Checking sync status...");
+    // Structured output keeps stdout for the report
+    let structured = io_options.is_structured();
+    let verbose = verbose && !structured;
+    if structured {
+        eprintln!("Checking sync status...");
+    } else {
+        println!("🔄 Checking sync status...");
+    }
 
     // Get all local prompts
     let local_prompts = storage.list_prompts()?;
 
-    if local_prompts.is_empty() {
-        println!("No local prompts to sync");
-        return Ok(());
-    }
-
     let mut synced = 0;
     let mut pending_push = 0;
     let mut pending_pull = 0;
     let mut conflicts = 0;
     let mut errors = 0;
+    let mut prompts = Vec::new();
+
+    if local_prompts.is_empty() {
+        if structured {
+            return io_options.emit(&SyncStatusReport {
+                synced,
+                pending_push,
+                pending_pull,
+                conflicts,
+                errors,
+                prompts,
+            });
+        }
+        println!("No local prompts to sync");
+        return Ok(());
+    }
 
-    // Check each prompt's sync status
+    // Check each prompt's sync status against the last sync
+    let bases = SyncBases::load(storage)?;
+    let listing = fetch_cloud_listing(client).await?;
     for prompt_name in &local_prompts {
         if verbose {
             println!("🔍 Checking '{}'...", prompt_name);
         }
 
-        // Read local prompt
-        let (local_metadata, local_content) = match storage.read_prompt(prompt_name) {
-            Ok((metadata, content)) => (metadata, content),
+        let comparison = match compare_with_cloud(storage, &bases, prompt_name, listing.get(prompt_name)) {
+            Ok(comparison) => comparison,
             Err(e) => {
+                errors += 1;
+                prompts.push(SyncStatusItem::new(prompt_name, "error", Some(e.to_string())));
                 if verbose {
-                    println!("❌ Local read error for '{}': {}", prompt_name, e);
+                    println!("❌ '{}' - {}", prompt_name, e);
                 }
-                errors += 1;
                 continue;
             }
         };
+        let detail = Some(comparison.detail().to_string());
 
-        // Check cloud version
-        let url = format!("/api/prompts/{}", urlencoding::encode(prompt_name));
-        match client.get(&url).await {
-            Ok(response) => {
-                let status = response.status();
-                if status.is_success() {
-                    // Parse cloud response
-                    match response.json::<serde_json::Value>().await {
-                        Ok(cloud_data) => {
-                            let cloud_content = cloud_data["content"].as_str().unwrap_or("");
-                            let cloud_description =
-                                cloud_data["description"].as_str().unwrap_or("");
-                            let cloud_updated = cloud_data["updated_at"].as_str().unwrap_or("");
-
-                            // Compare content and metadata
-                            let content_matches = local_content.trim() == cloud_content.trim();
-                            let description_matches =
-                                local_metadata.description == cloud_description;
-
-                            if content_matches && description_matches {
-                                synced += 1;
-                                if verbose {
-                                    println!(
-                                        "✅ '{}' - Synced (updated: {})",
-                                        prompt_name, cloud_updated
-                                    );
-                                }
-                            } else {
-                                conflicts += 1;
-                                if verbose {
-                                    println!("⚠️  '{}' - Conflict detected", prompt_name);
-                                    if !content_matches {
-                                        println!("   📝 Content differs");
-                                    }
-                                    if !description_matches {
-                                        println!("   📄 Description differs");
-                                    }
-                                    println!("   💡 Use `ph sync resolve {}` to fix", prompt_name);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            errors += 1;
-                            if verbose {
-                                println!(
-                                    "❌ '{}' - Failed to parse cloud response: {}",
-                                    prompt_name, e
-                                );
-                            }
-                        }
-                    }
-                } else if status.as_u16() == 404 {
-                    pending_push += 1;
-                    if verbose {
-                        println!("📤 '{}' - Needs push (not in cloud)", prompt_name);
-                    }
-                } else {
-                    errors += 1;
-                    if verbose {
-                        println!("❌ '{}' - Cloud API error: {}", prompt_name, status);
-                    }
+        match comparison.state {
+            CloudState::InSync => {
+                synced += 1;
+                prompts.push(SyncStatusItem::new(prompt_name, "synced", None));
+                if verbose {
+                    let cloud_updated = comparison
+                        .remote
+                        .as_ref()
+                        .and_then(|remote| remote.updated_at.as_deref())
+                        .unwrap_or("");
+                    println!(
+                        "✅ '{}' - Synced (updated: {})",
+                        prompt_name, cloud_updated
+                    );
                 }
             }
-            Err(e) => {
-                errors += 1;
+            CloudState::LocalChange => {
+                pending_push += 1;
+                prompts.push(SyncStatusItem::new(prompt_name, "pending_push", detail));
+                if verbose {
+                    println!("📤 '{}' - Needs push ({})", prompt_name, comparison.detail());
+                }
+            }
+            CloudState::RemoteChange => {
+                pending_pull += 1;
+                prompts.push(SyncStatusItem::new(prompt_name, "pending_pull", detail));
+                if verbose {
+                    println!("📥 '{}' - Needs pull ({})", prompt_name, comparison.detail());
+                }
+            }
+            CloudState::Conflict => {
+                conflicts += 1;
+                prompts.push(SyncStatusItem::new(prompt_name, "conflict", detail));
                 if verbose {
-                    println!("❌ '{}' - Request failed: {}", prompt_name, e);
+                    println!("⚠️  '{}' - Conflict detected ({})", prompt_name, comparison.detail());
+                    println!("   💡 Use `ph sync resolve {}` to fix", prompt_name);
                 }
             }
         }
     }
 
     // Check for cloud-only prompts (need pull)
-    match client.get("/api/prompts").await {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.json::<serde_json::Value>().await {
-                    Ok(cloud_data) => {
-                        if let Some(cloud_prompts) = cloud_data["prompts"].as_array() {
-                            for cloud_prompt in cloud_prompts {
-                                if let Some(cloud_name) = cloud_prompt["name"].as_str() {
-                                    if !local_prompts.contains(&cloud_name.to_string()) {
-                                        pending_pull += 1;
-                                        if verbose {
-                                            println!(
-                                                "📥 '{}' - Available for pull (cloud only)",
-                                                cloud_name
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Err(_) => {
-                        if verbose {
-                            println!("⚠️  Could not parse cloud prompts list");
-                        }
-                    }
-                }
-            }
-        }
-        Err(_) => {
-            if verbose {
-                println!("⚠️  Could not fetch cloud prompts list");
-            }
+    for cloud_name in listing.keys().filter(|name| !local_prompts.contains(name)) {
+        pending_pull += 1;
+        prompts.push(SyncStatusItem::new(cloud_name, "pending_pull", None));
+        if verbose {
+            println!("📥 '{}' - Available for pull (cloud only)", cloud_name);
         }
     }
 
+    if structured {
+        return io_options.emit(&SyncStatusReport {
+            synced,
+            pending_push,
+            pending_pull,
+            conflicts,
+            errors,
+            prompts,
+        });
+    }
+
     // Summary
     println!();
     println!("📊 {} Sync Status Summary", "🔄".green().bold());
@@ -755,7 +940,6 @@ async fn handle_sync_resolve(
     start: Instant,
 ) -> Result<()> {
     let resolved_name = resolve_prompt_name(storage, prompt)?;
-    println!("🔧 Resolving sync conflict for '{}'...", resolved_name);
 
     // Validate resolution strategy
     if !["local", "cloud", "manual"].contains(&resolution) {
@@ -773,72 +957,48 @@ async fn handle_sync_resolve(
         ));
     }
 
-    // Read local version
-    let (local_metadata, local_content) = storage
-        .read_prompt(&resolved_name)
-        .with_context(|| format!("Failed to read local prompt '{}'", resolved_name))?;
-
-    // Fetch cloud version
-    let url = format!("/api/prompts/{}", urlencoding::encode(&resolved_name));
-    let cloud_response = client
-        .get(&url)
-        .await
-        .with_context(|| format!("Failed to fetch cloud prompt '{}'", resolved_name))?;
-
-    if !cloud_response.status().is_success() {
-        if cloud_response.status().as_u16() == 404 {
-            return Err(anyhow::anyhow!(
-                "Prompt '{}' does not exist in cloud. No conflict to resolve.",
-                resolved_name
-            ));
-        } else {
-            return Err(anyhow::anyhow!(
-                "Failed to fetch cloud prompt '{}': {}",
-                resolved_name,
-                cloud_response.status()
-            ));
-        }
-    }
-
-    let cloud_data: serde_json::Value = cloud_response
-        .json()
+    let mut bases = SyncBases::load(storage)?;
+    let Some(cloud) = fetch_cloud_prompt(client, &resolved_name)
         .await
-        .with_context(|| format!("Failed to parse cloud response for '{}'", resolved_name))?;
-
-    let cloud_content = cloud_data["content"].as_str().unwrap_or("");
-    let cloud_description = cloud_data["description"].as_str().unwrap_or("");
-    let cloud_tags: Vec<String> = cloud_data["tags"]
-        .as_array()
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default();
+        .with_context(|| format!("Failed to fetch cloud prompt '{}'", resolved_name))?
+    else {
+        return Err(anyhow::anyhow!(
+            "Prompt '{}' does not exist in cloud. No conflict to resolve.",
+            resolved_name
+        ));
+    };
+    let comparison = compare_with_cloud(storage, &bases, &resolved_name, Some(&CloudSummary::from(&cloud)))?;
+    let detail = comparison.detail();
+    let Some((local_metadata, local_content)) = comparison.local else {
+        return Err(anyhow::anyhow!("Failed to read local prompt '{}'", resolved_name));
+    };
 
     // Check if there's actually a conflict
-    let content_matches = local_content.trim() == cloud_content.trim();
-    let description_matches = local_metadata.description == cloud_description;
-
-    if content_matches && description_matches {
+    if comparison.state == CloudState::InSync {
+        bases.record(&resolved_name, cloud.hash(), cloud.revision);
+        bases.save(storage)?;
         println!("✅ No conflict detected for '{}' - already in sync", resolved_name);
         return Ok(());
     }
 
+    let content_matches = local_content.trim() == cloud.content.trim();
+    let description_matches = local_metadata.description == cloud.description;
+
     // Show conflict details
     println!();
     println!("⚠️  {} Conflict Details:", "Sync conflict detected!".yellow().bold());
+    println!("   🔀 {}", detail);
     
     if !content_matches {
         println!("   📝 Content differs:");
         println!("      Local: {} characters", local_content.len());
-        println!("      Cloud: {} characters", cloud_content.len());
+        println!("      Cloud: {} characters", cloud.content.len());
     }
     
     if !description_matches {
         println!("   📄 Description differs:");
         println!("      Local: '{}'", local_metadata.description);
-        println!("      Cloud: '{}'", cloud_description);
+        println!("      Cloud: '{}'", cloud.description);
     }
 
     println!();
@@ -848,49 +1008,42 @@ async fn handle_sync_resolve(
             println!("🏠 Keeping local version and pushing to cloud...");
             
             // Push local version to cloud
-            let payload = serde_json::json!({
-                "prompts": [{
-                    "name": resolved_name,
-                    "content": local_content,
-                    "description": local_metadata.description,
-                    "tags": local_metadata.tags.unwrap_or_default()
-                }],
-                "force": true
-            });
-
-            let push_response = client
-                .post("/api/sync/push", &payload)
+            let request = PushRequest {
+                prompts: vec![outgoing_prompt(
+                    storage,
+                    &resolved_name,
+                    &local_metadata,
+                    &local_content,
+                    Some(&CloudSummary::from(&cloud)),
+                    false,
+                )?],
+                force: true,
+            };
+            let response = send_push(client, &request)
                 .await
                 .context("Failed to push local version to cloud")?;
 
-            if push_response.status().is_success() {
-                println!("✅ Local version pushed to cloud successfully");
-            } else {
-                let error_text = push_response.text().await.unwrap_or_default();
-                return Err(anyhow::anyhow!(
-                    "Failed to push local version: {}",
-                    error_text
-                ));
+            match response.results.first() {
+                Some(result) if matches!(result.status, PushStatus::Conflict | PushStatus::Error) => {
+                    return Err(anyhow::anyhow!(
+                        "Failed to push local version: {}",
+                        result.error.as_deref().unwrap_or("rejected by the server")
+                    ));
+                }
+                result => {
+                    let revision = result.and_then(|result| result.revision);
+                    bases.record(&resolved_name, request.prompts[0].hash(), revision);
+                }
             }
+            println!("✅ Local version pushed to cloud successfully");
         }
         "cloud" => {
             println!("☁️  Keeping cloud version and updating local...");
             
             // Update local with cloud version
-            let metadata = PromptMetadata {
-                id: resolved_name.clone(),
-                description: cloud_description.to_string(),
-                tags: if cloud_tags.is_empty() { None } else { Some(cloud_tags) },
-                created_at: local_metadata.created_at,
-                updated_at: Some(chrono::Utc::now().to_rfc3339()),
-                version: local_metadata.version,
-                git_hash: local_metadata.git_hash,
-                parent_version: local_metadata.parent_version,
-            };
-
-            storage
-                .write_prompt(&resolved_name, &metadata, cloud_content)
+            write_cloud_prompt(storage, &cloud, Some(local_metadata))
                 .with_context(|| format!("Failed to save cloud version locally for '{}'", resolved_name))?;
+            bases.record(&resolved_name, cloud.hash(), cloud.revision);
 
             println!("✅ Cloud version saved locally successfully");
         }
@@ -907,13 +1060,17 @@ async fn handle_sync_resolve(
             
             println!();
             println!("Cloud version:");
-            println!("  Description: {}", cloud_description);
+            println!("  Description: {}", cloud.description);
             println!("  Content (first 100 chars): {}", 
-                &cloud_content.chars().take(100).collect::<String>());
-            if cloud_content.len() > 100 {
-                println!("  ... ({} more characters)", cloud_content.len() - 100);
+                &cloud.content.chars().take(100).collect::<String>());
+            if cloud.content.len() > 100 {
+                println!("  ... ({} more characters)", cloud.content.len() - 100);
             }
 
+            // The cloud version has been seen, so the local edit now builds on it
+            bases.record(&resolved_name, cloud.hash(), cloud.revision);
+            bases.save(storage)?;
+
             println!();
             println!("💡 To manually resolve this conflict:");
             println!("   1. Edit the prompt: {}", format!("ph edit {}", resolved_name).bold());
@@ -925,6 +1082,7 @@ async fn handle_sync_resolve(
         }
         _ => unreachable!(),
     }
+    bases.save(storage)?;
 
     println!();
     println!(
@@ -951,84 +1109,35 @@ async fn handle_sync_bidirectional(
     // Phase 1: Check sync status first
     println!("📊 Phase 1: Analyzing sync status...");
     
-    let local_prompts = storage.list_prompts()?;
-    let mut cloud_prompts = Vec::new();
-    
-    // Get cloud prompts
-    match client.get("/api/prompts").await {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.json::<serde_json::Value>().await {
-                    Ok(cloud_data) => {
-                        if let Some(cloud_array) = cloud_data["prompts"].as_array() {
-                            cloud_prompts = cloud_array
-                                .iter()
-                                .filter_map(|p| p["name"].as_str().map(|s| s.to_string()))
-                                .collect();
-                        }
-                    }
-                    Err(e) => {
-                        return Err(anyhow::anyhow!("Failed to parse cloud prompts: {}", e));
-                    }
-                }
-            } else {
-                return Err(anyhow::anyhow!(
-                    "Failed to fetch cloud prompts: {}",
-                    response.status()
-                ));
-            }
-        }
-        Err(e) => {
-            return Err(anyhow::anyhow!("Request failed: {}", e));
+    let listing = fetch_cloud_listing(client).await?;
+    let mut names = storage.list_prompts()?;
+    for cloud_name in listing.keys() {
+        if !names.contains(cloud_name) {
+            names.push(cloud_name.clone());
         }
     }
 
+    let mut bases = SyncBases::load(storage)?;
     let mut pending_push = Vec::new();
     let mut pending_pull = Vec::new();
     let mut conflicts = Vec::new();
+    let mut failed = Vec::new();
     let mut synced = 0;
 
-    // Check each local prompt
-    for prompt_name in &local_prompts {
-        if cloud_prompts.contains(prompt_name) {
-            // Check for conflicts
-            let url = format!("/api/prompts/{}", urlencoding::encode(prompt_name));
-            match client.get(&url).await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        match response.json::<serde_json::Value>().await {
-                            Ok(cloud_data) => {
-                                let (local_metadata, local_content) = storage.read_prompt(prompt_name)?;
-                                let cloud_content = cloud_data["content"].as_str().unwrap_or("");
-                                let cloud_description = cloud_data["description"].as_str().unwrap_or("");
-
-                                let content_matches = local_content.trim() == cloud_content.trim();
-                                let description_matches = local_metadata.description == cloud_description;
-
-                                if content_matches && description_matches {
-                                    synced += 1;
-                                } else {
-                                    conflicts.push(prompt_name.clone());
-                                }
-                            }
-                            Err(_) => conflicts.push(prompt_name.clone()),
-                        }
-                    } else {
-                        conflicts.push(prompt_name.clone());
+    for prompt_name in &names {
+        match compare_with_cloud(storage, &bases, prompt_name, listing.get(prompt_name)) {
+            Ok(comparison) => match comparison.state {
+                CloudState::InSync => {
+                    synced += 1;
+                    if let Some(remote) = &comparison.remote {
+                        bases.record(prompt_name, remote.hash.clone(), remote.revision);
                     }
                 }
-                Err(_) => conflicts.push(prompt_name.clone()),
-            }
-        } else {
-            // Local only - needs push
-            pending_push.push(prompt_name.clone());
-        }
-    }
-
-    // Check for cloud-only prompts (need pull)
-    for cloud_prompt in &cloud_prompts {
-        if !local_prompts.contains(cloud_prompt) {
-            pending_pull.push(cloud_prompt.clone());
+                CloudState::LocalChange => pending_push.push((prompt_name.clone(), comparison)),
+                CloudState::RemoteChange => pending_pull.push((prompt_name.clone(), comparison)),
+                CloudState::Conflict => conflicts.push((prompt_name.clone(), comparison.detail())),
+            },
+            Err(e) => failed.push((prompt_name.clone(), e)),
         }
     }
 
@@ -1037,11 +1146,20 @@ async fn handle_sync_bidirectional(
     println!("   📤 Need push: {} prompts", pending_push.len());
     println!("   📥 Need pull: {} prompts", pending_pull.len());
     println!("   ⚠️  Conflicts: {} prompts", conflicts.len());
+    if !failed.is_empty() {
+        println!("   ❌ Errors: {} prompts", failed.len());
+        for (prompt_name, e) in &failed {
+            println!("     - {}: {}", prompt_name, e);
+        }
+    }
 
     // Check if everything is already in sync
     if pending_push.is_empty() && pending_pull.is_empty() && conflicts.is_empty() {
+        bases.save(storage)?;
         println!();
-        println!("💚 {} All prompts are already in sync!", "Perfect!".green().bold());
+        if failed.is_empty() {
+            println!("💚 {} All prompts are already in sync!", "Perfect!".green().bold());
+        }
         println!(
             "⏱️  Bidirectional sync completed ({}ms)",
             start.elapsed().as_millis()
@@ -1051,59 +1169,59 @@ async fn handle_sync_bidirectional(
 
     println!();
 
-    // Phase 2: Handle conflicts first
+    // Phase 2: Conflicts are left alone; one-sided changes still go through
     if !conflicts.is_empty() {
-        println!("⚠️  Phase 2: Conflict resolution required");
+        println!("⚠️  Phase 2: Skipping prompts changed on both sides");
         println!("   The following prompts have conflicts:");
-        for conflict in &conflicts {
-            println!("     - {}", conflict);
+        for (conflict, detail) in &conflicts {
+            println!("     - {} ({})", conflict, detail);
         }
         println!();
         println!("💡 {} Resolve conflicts manually:", "Action required:".yellow().bold());
         println!("   For each conflict, run: {}", "ph sync resolve <prompt> --resolution [local|cloud|manual]".bold());
         println!("   Then run {} again to continue sync", "ph sync".bold());
         println!();
-        return Ok(());
     }
 
-    // Phase 3: Push local-only prompts
+    // Phase 3: Push local changes
+    let mut push_success = 0;
     if !pending_push.is_empty() {
         println!("📤 Phase 3: Pushing {} local prompts to cloud...", pending_push.len());
         
-        let mut push_success = 0;
         let mut push_errors = 0;
 
-        for prompt_name in &pending_push {
+        for (prompt_name, comparison) in &pending_push {
             print!("   📤 Pushing '{}'... ", prompt_name);
             
-            match storage.read_prompt(prompt_name) {
-                Ok((metadata, content)) => {
-                    let payload = serde_json::json!({
-                        "prompts": [{
-                            "name": prompt_name,
-                            "content": content,
-                            "description": metadata.description,
-                            "tags": metadata.tags.unwrap_or_default()
-                        }],
-                        "force": false
-                    });
-
-                    match client.post("/api/sync/push", &payload).await {
-                        Ok(response) => {
-                            if response.status().is_success() {
-                                push_success += 1;
-                                println!("✅");
-                            } else {
-                                push_errors += 1;
-                                println!("❌ ({})", response.status());
-                            }
-                        }
-                        Err(e) => {
-                            push_errors += 1;
-                            println!("❌ ({})", e);
-                        }
+            let Some((metadata, content)) = &comparison.local else {
+                continue;
+            };
+            let pushed = async {
+                let request = PushRequest {
+                    prompts: vec![outgoing_prompt(storage, prompt_name, metadata, content, comparison.remote.as_ref(), false)?],
+                    force: false,
+                };
+                let response = send_push(client, &request).await?;
+                Ok::<_, anyhow::Error>((request, response))
+            }
+            .await;
+
+            match pushed {
+                Ok((request, response)) => match response.results.first() {
+                    Some(result) if matches!(result.status, PushStatus::Created | PushStatus::Updated | PushStatus::Unchanged) => {
+                        push_success += 1;
+                        bases.record(prompt_name, request.prompts[0].hash(), result.revision);
+                        println!("✅");
                     }
-                }
+                    Some(result) if result.status == PushStatus::Conflict => {
+                        push_errors += 1;
+                        println!("⚠️  (changed in the cloud meanwhile)");
+                    }
+                    result => {
+                        push_errors += 1;
+                        println!("❌ ({})", result.and_then(|r| r.error.as_deref()).unwrap_or("no result"));
+                    }
+                },
                 Err(e) => {
                     push_errors += 1;
                     println!("❌ ({})", e);
@@ -1115,64 +1233,29 @@ async fn handle_sync_bidirectional(
         println!();
     }
 
-    // Phase 4: Pull cloud-only prompts
+    // Phase 4: Pull cloud changes
+    let mut pull_success = 0;
     if !pending_pull.is_empty() {
         println!("📥 Phase 4: Pulling {} cloud prompts to local...", pending_pull.len());
         
-        let mut pull_success = 0;
         let mut pull_errors = 0;
 
-        for prompt_name in &pending_pull {
+        for (prompt_name, comparison) in &pending_pull {
             print!("   📥 Pulling '{}'... ", prompt_name);
             
-            let url = format!("/api/prompts/{}", urlencoding::encode(prompt_name));
-            match client.get(&url).await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        match response.json::<serde_json::Value>().await {
-                            Ok(cloud_data) => {
-                                let cloud_content = cloud_data["content"].as_str().unwrap_or("");
-                                let cloud_description = cloud_data["description"].as_str().unwrap_or("");
-                                let cloud_tags: Vec<String> = cloud_data["tags"]
-                                    .as_array()
-                                    .map(|arr| {
-                                        arr.iter()
-                                            .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                                            .collect()
-                                    })
-                                    .unwrap_or_default();
-
-                                let metadata = PromptMetadata {
-                                    id: prompt_name.clone(),
-                                    description: cloud_description.to_string(),
-                                    tags: if cloud_tags.is_empty() { None } else { Some(cloud_tags) },
-                                    created_at: Some(chrono::Utc::now().to_rfc3339()),
-                                    updated_at: None,
-                                    version: None,
-                                    git_hash: None,
-                                    parent_version: None,
-                                };
-
-                                match storage.write_prompt(prompt_name, &metadata, cloud_content) {
-                                    Ok(_) => {
-                                        pull_success += 1;
-                                        println!("✅");
-                                    }
-                                    Err(e) => {
-                                        pull_errors += 1;
-                                        println!("❌ ({})", e);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                pull_errors += 1;
-                                println!("❌ ({})", e);
-                            }
-                        }
-                    } else {
-                        pull_errors += 1;
-                        println!("❌ ({})", response.status());
-                    }
+            let local_metadata = comparison.local.clone().map(|(metadata, _)| metadata);
+            let pulled = async {
+                let cloud = pull_cloud_prompt(client, prompt_name).await?;
+                write_cloud_prompt(storage, &cloud, local_metadata)?;
+                Ok::<_, anyhow::Error>(cloud)
+            }
+            .await;
+
+            match pulled {
+                Ok(cloud) => {
+                    pull_success += 1;
+                    bases.record(prompt_name, cloud.hash(), cloud.revision);
+                    println!("✅");
                 }
                 Err(e) => {
                     pull_errors += 1;
@@ -1184,15 +1267,19 @@ async fn handle_sync_bidirectional(
         println!("   📥 Pull results: {} success, {} errors", pull_success, pull_errors);
         println!();
     }
+    bases.save(storage)?;
 
     // Final summary
     println!("✅ {} Bidirectional sync completed!", "Success!".green().bold());
     
-    let total_operations = pending_push.len() + pending_pull.len();
+    let total_operations = push_success + pull_success;
     if total_operations > 0 {
         println!("   📊 Total operations: {}", total_operations);
-        println!("   📤 Pushed: {} prompts", pending_push.len());
-        println!("   📥 Pulled: {} prompts", pending_pull.len());
+        println!("   📤 Pushed: {} prompts", push_success);
+        println!("   📥 Pulled: {} prompts", pull_success);
+    }
+    if !conflicts.is_empty() {
+        println!("   ⚠️  Conflicts left: {} prompts", conflicts.len());
     }
     
     println!(
@@ -1229,88 +1316,39 @@ async fn handle_sync_verify(
     let mut errors = 0;
     let mut missing = 0;
     let mut out_of_sync = 0;
+    let bases = SyncBases::load(storage)?;
+    let listing = fetch_cloud_listing(client).await?;
 
     for prompt_name in &prompts_to_verify {
         if verbose {
             println!("🔍 Verifying '{}'...", prompt_name);
         }
 
-        // Read local prompt
-        let local_result = storage.read_prompt(prompt_name);
-        let (local_metadata, local_content) = match local_result {
-            Ok((metadata, content)) => (metadata, content),
+        let comparison = match compare_with_cloud(storage, &bases, prompt_name, listing.get(prompt_name)) {
+            Ok(comparison) => comparison,
             Err(e) => {
                 if verbose {
-                    println!("❌ Local read error for '{}': {}", prompt_name, e);
+                    println!("❌ '{}' - {}", prompt_name, e);
                 }
                 errors += 1;
                 continue;
             }
         };
 
-        // Check if prompt exists in cloud
-        let url = format!("/api/prompts/{}", urlencoding::encode(prompt_name));
-        match client.get(&url).await {
-            Ok(response) => {
-                let status = response.status();
-                if status.is_success() {
-                    // Parse cloud response
-                    match response.json::<serde_json::Value>().await {
-                        Ok(cloud_data) => {
-                            let cloud_content = cloud_data["content"].as_str().unwrap_or("");
-                            let cloud_description =
-                                cloud_data["description"].as_str().unwrap_or("");
-
-                            // Compare content
-                            let content_matches = local_content.trim() == cloud_content.trim();
-                            let description_matches =
-                                local_metadata.description == cloud_description;
-
-                            if content_matches && description_matches {
-                                verified += 1;
-                                if verbose {
-                                    println!("✅ '{}' - In sync", prompt_name);
-                                }
-                            } else {
-                                out_of_sync += 1;
-                                if verbose {
-                                    println!("⚠️  '{}' - Out of sync", prompt_name);
-                                    if !content_matches {
-                                        println!("   📝 Content differs");
-                                    }
-                                    if !description_matches {
-                                        println!("   📄 Description differs");
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            errors += 1;
-                            if verbose {
-                                println!(
-                                    "❌ '{}' - Failed to parse cloud response: {}",
-                                    prompt_name, e
-                                );
-                            }
-                        }
-                    }
-                } else if status.as_u16() == 404 {
-                    missing += 1;
-                    if verbose {
-                        println!("⚠️  '{}' - Not found in cloud", prompt_name);
-                    }
-                } else {
-                    errors += 1;
-                    if verbose {
-                        println!("❌ '{}' - Cloud API error: {}", prompt_name, status);
-                    }
-                }
+        if comparison.state == CloudState::InSync {
+            verified += 1;
+            if verbose {
+                println!("✅ '{}' - In sync", prompt_name);
             }
-            Err(e) => {
-                errors += 1;
-                if verbose {
-                    println!("❌ '{}' - Request failed: {}", prompt_name, e);
-                }
+        } else if comparison.remote.is_none() {
+            missing += 1;
+            if verbose {
+                println!("⚠️  '{}' - Not found in cloud", prompt_name);
+            }
+        } else {
+            out_of_sync += 1;
+            if verbose {
+                println!("⚠️  '{}' - Out of sync ({})", prompt_name, comparison.detail());
             }
         }
     }
@@ -1401,7 +1439,7 @@ async fn handle_sync_file(
         std::env::current_dir()?.join(path)
     };
     
-    // Handle force flag
+    // Handle force flag; the file is only replaced once the prompt was read
     if local_path.exists() && !force {
         return Err(anyhow::anyhow!(
             "File already exists at {:?}. Use --force to overwrite.",
@@ -1409,7 +1447,7 @@ async fn handle_sync_file(
         ));
     }
     
-    match sync_manager.sync_prompt(&prompt_name, Some(local_path.clone())) {
+    match sync_manager.sync_prompt(&prompt_name, Some(local_path.clone()), force) {
         Ok(created_path) => {
             println!("✅ Created bidirectional sync:");
             println!("   📁 Local file: {:?}", created_path);
@@ -1428,6 +1466,149 @@ async fn handle_sync_file(
     Ok(())
 }
 
+fn handle_sync_watch(
+    storage: &Storage,
+    directory: Option<&str>,
+    delay: Option<u64>,
+    start: Instant,
+) -> Result<()> {
+    use crate::file_watcher::{watch_key, FileWatcher};
+    use crate::sync_manager::{SyncAction, SyncManager};
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    let delay = Duration::from_millis(delay.unwrap_or(100));
+    let root = match directory {
+        Some(dir) => match std::fs::canonicalize(dir) {
+            Ok(path) if path.is_dir() => Some(path),
+            _ => {
+                eprintln!("Error: Directory '{}' not found", dir);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let mut sync_manager = SyncManager::new(storage.clone())?;
+    let mut watched: Vec<(String, PathBuf)> = sync_manager
+        .registry()
+        .get_all_synced()
+        .into_iter()
+        .filter_map(|name| sync_manager.registry().get_sync(name))
+        .filter(|meta| meta.active)
+        .filter(|meta| match &root {
+            Some(root) => std::fs::canonicalize(&meta.local_path)
+                .unwrap_or_else(|_| meta.local_path.clone())
+                .starts_with(root),
+            None => true,
+        })
+        .map(|meta| (meta.prompt_name.clone(), meta.local_path.clone()))
+        .collect();
+    watched.sort();
+
+    if watched.is_empty() {
+        println!("📭 No synced files to watch");
+        println!("💡 Create one with: ph sync sync-file <path>");
+        return Ok(());
+    }
+
+    let mut watcher = FileWatcher::new()?;
+    let mut relevant = HashSet::new();
+    for (prompt_name, local_path) in &watched {
+        let prompt_path = storage.prompt_path(prompt_name);
+        for path in [local_path, &prompt_path] {
+            relevant.extend(watcher.watch_file(path)?);
+        }
+    }
+
+    // Problems are reported once, then again only after they were cleared
+    let mut reported: HashSet<String> = HashSet::new();
+    let mut sync_all = |sync_manager: &mut SyncManager| {
+        for (prompt_name, local_path) in &watched {
+            let action = match sync_manager.reconcile(prompt_name) {
+                Ok(action) => action,
+                Err(e) => {
+                    eprintln!("❌ {}: {}", prompt_name, e);
+                    continue;
+                }
+            };
+            let time = chrono::Local::now().format("%H:%M:%S");
+            let file = local_path.display();
+            match action {
+                SyncAction::Unchanged => {
+                    reported.remove(prompt_name);
+                }
+                SyncAction::FromFile => {
+                    reported.remove(prompt_name);
+                    println!("📥 [{}] {} → {}", time, file, prompt_name.cyan());
+                }
+                SyncAction::ToFile => {
+                    reported.remove(prompt_name);
+                    println!("📤 [{}] {} → {}", time, prompt_name.cyan(), file);
+                }
+                SyncAction::Conflict | SyncAction::MissingFile | SyncAction::MissingPrompt => {
+                    if !reported.insert(prompt_name.clone()) {
+                        continue;
+                    }
+                    match action {
+                        SyncAction::Conflict => {
+                            println!(
+                                "⚠️  [{}] {} and {} both changed; neither was overwritten",
+                                time,
+                                prompt_name.cyan(),
+                                file
+                            );
+                            println!("   💡 Compare with: ph diff {} {}", prompt_name, file);
+                            println!("   💡 Then make both sides match to resume syncing");
+                        }
+                        SyncAction::MissingFile => {
+                            println!("⚠️  [{}] {} was removed; {} is unchanged", time, file, prompt_name.cyan());
+                        }
+                        _ => {
+                            println!("⚠️  [{}] Prompt {} was removed; {} is unchanged", time, prompt_name.cyan(), file);
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    println!(
+        "👀 Watching {} synced file(s) (debounce {}ms). Press Ctrl+C to stop.",
+        watched.len(),
+        delay.as_millis()
+    );
+    for (prompt_name, local_path) in &watched {
+        println!("   {} ↔ {}", prompt_name.cyan(), local_path.display());
+    }
+
+    // Catch up on anything that changed while nobody was watching
+    sync_all(&mut sync_manager);
+
+    // Compare in the same form the registered paths were keyed in
+    let is_relevant = |path: &Path| relevant.contains(&watch_key(path));
+    let mut pending: Option<Instant> = None;
+    while !crate::signals::is_shutdown_requested() {
+        let changed = watcher.wait(Duration::from_millis(100))?;
+        if changed.iter().any(|path| is_relevant(path)) {
+            // Editors often write in several steps; wait until things go quiet
+            pending = Some(Instant::now());
+        }
+        if pending.is_some_and(|last| last.elapsed() >= delay) {
+            pending = None;
+            sync_all(&mut sync_manager);
+        }
+    }
+
+    if pending.is_some() {
+        sync_all(&mut sync_manager);
+    }
+
+    println!("✓ Stopped watching ({}ms)", start.elapsed().as_millis());
+    Ok(())
+}
+
 // =============================================================================
 // Advanced sync functions temporarily disabled
 // =============================================================================
@@ -1441,7 +1622,7 @@ async fn handle_sync_file(
 // - handle_file_status: Show detailed sync status for file sync
 // - handle_sync_repair: Repair broken sync relationships
 // - handle_sync_conflicts: List all sync conflicts
-// - handle_sync_watch: Watch files for changes and auto-sync
 //
 // For now, basic file sync is available via the `sync-file` command and
-// the `ph new -s` flag for creating prompts with bidirectional sync.
+// the `ph new -s` flag for creating prompts with bidirectional sync, and
+// `ph sync watch` keeps those links up to date.
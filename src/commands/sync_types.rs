@@ -19,7 +19,10 @@ impl SimpleSyncManager {
     }
     
     /// Create a bidirectional sync for a prompt
-    pub fn sync_prompt(&self, prompt_name: &str, local_path: Option<PathBuf>) -> Result<PathBuf> {
+    ///
+    /// With `force`, an existing file is overwritten, but only once the prompt
+    /// has been read, so a missing prompt never costs the user their file.
+    pub fn sync_prompt(&self, prompt_name: &str, local_path: Option<PathBuf>, force: bool) -> Result<PathBuf> {
         // Smart default path resolution
         let resolved_path = match local_path {
             Some(path) => {
@@ -33,7 +36,7 @@ impl SimpleSyncManager {
                 // Smart default: ./prompt-name.md (with team prefix stripping)
                 let file_name = if prompt_name.starts_with('@') {
                     // Strip team prefix: @team/api-design -> api-design.md
-                    prompt_name.split('/').last().unwrap_or(prompt_name)
+                    prompt_name.split('/').next_back().unwrap_or(prompt_name)
                 } else {
                     prompt_name
                 };
@@ -44,25 +47,23 @@ impl SimpleSyncManager {
             }
         };
         
-        // Check for conflicts
-        if resolved_path.exists() {
-            return Err(anyhow::anyhow!(
-                "File already exists at {:?}. Use --force to overwrite or choose a different path.",
-                resolved_path
-            ));
-        }
-        
         // Read prompt content
         let (metadata, content) = self.storage.read_prompt(prompt_name)?;
         
         // Create file content with frontmatter
-        let file_content = format!(
-            "---\nprompt: {}\ndescription: {}\ntags: {}\n---\n\n{}",
-            prompt_name,
-            metadata.description,
-            metadata.tags.unwrap_or_default().join(", "),
-            content
-        );
+        let file_content = crate::sync_manager::render_file_content(prompt_name, &metadata, &content);
+        
+        // Check for conflicts; a file that already holds this prompt (e.g. just
+        // written by `-f`) is safe to take over
+        if resolved_path.exists() && !force {
+            let existing = std::fs::read_to_string(&resolved_path).unwrap_or_default();
+            if existing.trim() != content.trim() && existing != file_content {
+                return Err(anyhow::anyhow!(
+                    "File already exists at {:?}. Use --force to overwrite or choose a different path.",
+                    resolved_path
+                ));
+            }
+        }
         
         // Create directory if needed
         if let Some(parent) = resolved_path.parent() {
@@ -72,6 +73,33 @@ impl SimpleSyncManager {
         // Write file
         std::fs::write(&resolved_path, &file_content)?;
         
+        // Register the link so `ph sync watch` and `ph sync status` see it
+        crate::sync_manager::SyncManager::new(self.storage.clone())?
+            .record_link(prompt_name, resolved_path.clone())?;
+        
         Ok(resolved_path)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forced_sync_keeps_file_when_prompt_is_missing() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new_with_base(temp.path().join("base")).unwrap();
+        let file = temp.path().join("notes.md");
+        std::fs::write(&file, "notes\n").unwrap();
+
+        let manager = SimpleSyncManager::new(storage.clone()).unwrap();
+        assert!(manager.sync_prompt("notes", Some(file.clone()), true).is_err());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "notes\n");
+
+        let (metadata, _) = storage.parse_prompt_content("from the library").unwrap();
+        storage.write_prompt("notes", &metadata, "from the library").unwrap();
+        assert!(manager.sync_prompt("notes", Some(file.clone()), false).is_err());
+        manager.sync_prompt("notes", Some(file.clone()), true).unwrap();
+        assert!(std::fs::read_to_string(&file).unwrap().ends_with("from the library"));
+    }
+}
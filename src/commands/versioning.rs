@@ -1,73 +1,134 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
 use colored::*;
+use serde::Serialize;
 use std::fs;
 use std::time::Instant;
 
-use crate::{init_telemetry, record_command_metric, Storage};
+use super::common::{resolve_prompt_for_write, resolve_prompt_name};
+use crate::changelog::{
+    bump_version, latest_semver, parse_semver, render_bank_changelog, render_changelog, Bump,
+    ChangelogEntry,
+};
+use crate::revisions::{content_hash, RetentionConfig, Tag};
+use crate::{init_telemetry, record_command_metric, split_version, IoOptions, Storage};
+
+#[derive(Subcommand)]
+pub enum VersionsCommands {
+    /// Drop old revisions according to the retention rules in config
+    Prune {
+        /// Show what would be removed without removing it
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+        /// Also remove histories of prompts that no longer exist
+        #[arg(long = "orphans")]
+        orphans: bool,
+    },
+}
+
+/// One entry in `ph versions --json`: tags newest first, then revisions newest first
+#[derive(Serialize)]
+struct VersionItem {
+    /// Tag name, or the revision label (`r4`)
+    version: String,
+    hash: Option<String>,
+    created_at: Option<String>,
+    message: Option<String>,
+    /// "tag" or "revision"
+    kind: &'static str,
+    revision: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operation: Option<String>,
+}
+
+/// `ph changelog --json`
+#[derive(Serialize)]
+struct ChangelogOutput {
+    name: String,
+    /// "prompt" or "bank"
+    kind: &'static str,
+    markdown: String,
+}
 
 pub fn handle_version(
     storage: &Storage,
     name: &str,
-    tag: &str,
+    tag: Option<&str>,
+    bump: Option<&str>,
     message: Option<&str>,
     start: Instant,
 ) -> Result<()> {
     let mut telemetry = Some(init_telemetry(storage.base_dir().clone()).ok()).flatten();
 
-    // Find the prompt
+    // Tagging stamps the prompt, so require an exact name (root, bank or team)
+    let resolved_name = resolve_prompt_for_write(storage, name, "version")?;
+    let name = resolved_name.as_str();
     let prompt_path = storage.prompt_path(name);
-    if !prompt_path.exists() {
-        eprintln!("{}", "❌ Prompt not found".red());
-        std::process::exit(1);
-    }
 
-    // Read current content
-    let content = fs::read_to_string(&prompt_path)?;
-
-    // Create version directory if it doesn't exist
-    let versions_dir = prompt_path
-        .parent()
-        .unwrap()
-        .join(format!("{}.versions", name));
-    fs::create_dir_all(&versions_dir)?;
-
-    // Generate Git hash of current content
-    let git_hash = format!("{:x}", md5::compute(&content));
-
-    // Check if this exact version already exists
-    let version_file = versions_dir.join(format!("{}.md", tag));
-    if version_file.exists() {
+    let store = storage.revisions();
+    let existing_tags = store.tags(name)?;
+    let mut metadata = storage.read_prompt_metadata(name)?;
+    let parent_version = metadata.version.clone();
+
+    // An explicit tag, or the next semantic version after the current one
+    let tag = match (tag, bump) {
+        (Some(tag), _) => tag.to_string(),
+        (None, Some(bump)) => {
+            let current = parent_version
+                .as_deref()
+                .filter(|v| parse_semver(v).is_some())
+                .or_else(|| latest_semver(existing_tags.keys().map(String::as_str)));
+            match bump.parse::<Bump>().and_then(|bump| bump_version(current, bump)) {
+                Ok(next) => next,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        (None, None) => return Err(anyhow!("Give a version tag or --bump")),
+    };
+    let tag = tag.as_str();
+    if existing_tags.contains_key(tag) {
         eprintln!("{}", format!("❌ Version '{}' already exists", tag).red());
         std::process::exit(1);
     }
 
-    // Create version metadata
-    let timestamp = chrono::Utc::now().to_rfc3339();
-    let version_content = format!(
-        "---\nversion: {}\ngit_hash: {}\ncreated_at: {}\nmessage: {}\n---\n\n{}",
-        tag,
-        git_hash,
-        timestamp,
-        message.unwrap_or(""),
-        content
-    );
-
-    // Write version file
-    fs::write(&version_file, version_content)?;
-
-    // Update original prompt metadata with version info
-    if let Ok(mut metadata) = storage.read_prompt_metadata(name) {
-        metadata.version = Some(tag.to_string());
-        metadata.git_hash = Some(git_hash.clone());
-        metadata.updated_at = Some(timestamp);
-        let _ = storage.write_prompt_metadata(name, &metadata);
-    }
-
+    // Stamp the version into the prompt, then tag the revision that produces
+    let content = fs::read_to_string(&prompt_path)?;
+    let (_, body) = storage.parse_prompt_content(&content)?;
+    metadata.version = Some(tag.to_string());
+    metadata.parent_version = parent_version.clone();
+    metadata.git_hash = Some(content_hash(&body));
+    metadata.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    storage.write_prompt_metadata(name, &metadata)?;
+    storage.record_revision(name)?;
+    let head = store
+        .head(name)?
+        .ok_or_else(|| anyhow!("No revision recorded for '{}'", name))?;
+    store.set_tag(name, tag, head.number, message)?;
+
+    storage.append_changelog(
+        name,
+        &ChangelogEntry {
+            version: tag.to_string(),
+            parent_version: parent_version.clone(),
+            revision: head.number,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            message: message.filter(|m| !m.is_empty()).map(str::to_string),
+        },
+    )?;
+
+    let parent = parent_version
+        .map(|v| format!(", after {}", v))
+        .unwrap_or_default();
     println!(
-        "Created version '{}' for '{}' ({})",
+        "Created version '{}' for '{}' ({}, {}{})",
         tag,
         name,
-        &git_hash[..8]
+        head.label(),
+        head.short_hash(),
+        parent
     );
     if let Some(msg) = message {
         println!("Message: {}", msg);
@@ -77,22 +138,48 @@ pub fn handle_version(
     Ok(())
 }
 
-pub fn handle_versions(storage: &Storage, name: &str, verbose: bool, start: Instant) -> Result<()> {
+pub fn handle_versions(
+    storage: &Storage,
+    name: &str,
+    verbose: bool,
+    io_options: &IoOptions,
+    start: Instant,
+) -> Result<()> {
     let mut telemetry = Some(init_telemetry(storage.base_dir().clone()).ok()).flatten();
 
-    // Check if prompt exists
-    let prompt_path = storage.prompt_path(name);
-    if !prompt_path.exists() {
-        eprintln!("{}", "❌ Prompt not found".red());
-        std::process::exit(1);
+    let resolved_name = resolve_prompt_name(storage, name)?;
+    let name = split_version(&resolved_name).0;
+
+    let store = storage.revisions();
+    let log = store.log(name)?;
+    let mut tags: Vec<(String, Tag)> = store.tags(name)?.into_iter().collect();
+    tags.sort_by(|a, b| b.1.created_at.cmp(&a.1.created_at));
+    let hash_of = |number: u64| log.iter().find(|r| r.number == number).map(|r| r.hash.clone());
+
+    if io_options.is_structured() {
+        let tag_items = tags.iter().map(|(tag, info)| VersionItem {
+            version: tag.clone(),
+            hash: hash_of(info.revision),
+            created_at: Some(info.created_at.clone()),
+            message: info.message.clone(),
+            kind: "tag",
+            revision: info.revision,
+            operation: None,
+        });
+        let revision_items = log.iter().rev().map(|revision| VersionItem {
+            version: revision.label(),
+            hash: Some(revision.hash.clone()),
+            created_at: Some(revision.created_at.clone()),
+            message: None,
+            kind: "revision",
+            revision: revision.number,
+            operation: Some(revision.operation.clone()),
+        });
+        let items: Vec<VersionItem> = tag_items.chain(revision_items).collect();
+        return io_options.emit(&items);
     }
 
-    // Check for versions directory
-    let versions_dir = prompt_path
-        .parent()
-        .unwrap()
-        .join(format!("{}.versions", name));
-    if !versions_dir.exists() {
+    if log.is_empty() && tags.is_empty() {
         println!(
             "{}",
             format!("📝 No versions found for '{}'", name).yellow()
@@ -100,68 +187,23 @@ pub fn handle_versions(storage: &Storage, name: &str, verbose: bool, start: Inst
         return Ok(());
     }
 
-    // List version files
-    let mut versions = Vec::new();
-    for entry in fs::read_dir(&versions_dir)? {
-        let entry = entry?;
-        if let Some(name) = entry.file_name().to_str() {
-            if name.ends_with(".md") {
-                let version_name = name.trim_end_matches(".md");
-                let content = fs::read_to_string(entry.path())?;
-
-                // Parse metadata
-                if let Some(metadata_end) = content.find("\n---\n") {
-                    let metadata_section = &content[4..metadata_end]; // Skip initial ---
-                    let mut version_info = std::collections::HashMap::<String, String>::new();
-
-                    for line in metadata_section.lines() {
-                        if let Some((key, value)) = line.split_once(": ") {
-                            version_info.insert(key.trim().to_string(), value.trim().to_string());
-                        }
-                    }
-
-                    versions.push((version_name.to_string(), version_info));
-                }
-            }
-        }
-    }
+    println!("{}", format!("📚 Version history for '{}'", name).bold());
 
-    if versions.is_empty() {
-        println!(
-            "{}",
-            format!("📝 No versions found for '{}'", name).yellow()
-        );
-        return Ok(());
+    if !tags.is_empty() {
+        println!();
+        println!("{}", "Tags:".bold());
     }
-
-    // Sort by creation date (newest first)
-    versions.sort_by(|a, b| {
-        let a_date = a.1.get("created_at").map(|s| s.as_str()).unwrap_or("");
-        let b_date = b.1.get("created_at").map(|s| s.as_str()).unwrap_or("");
-        b_date.cmp(a_date)
-    });
-
-    println!("{}", format!("📚 Version history for '{}'", name).bold());
-    println!();
-
-    for (version, info) in &versions {
-        let hash = info
-            .get("git_hash")
-            .map(|s| s.as_str())
-            .unwrap_or("unknown");
-        let date = info
-            .get("created_at")
-            .map(|s| s.as_str())
-            .unwrap_or("unknown");
-        let message = info.get("message").map(|s| s.as_str()).unwrap_or("");
+    for (tag, info) in &tags {
+        let hash = hash_of(info.revision).unwrap_or_else(|| "unknown".to_string());
+        let short = &hash[..hash.len().min(8)];
+        let message = info.message.as_deref().unwrap_or("");
 
         if verbose {
-            println!("{} {} ({})", "📌".cyan(), version.bold(), &hash[..8]);
-            println!("   📅 {}", date.dimmed());
+            println!("{} {} → r{} ({})", "📌".cyan(), tag.bold(), info.revision, short);
+            println!("   📅 {}", info.created_at.dimmed());
             if !message.is_empty() {
                 println!("   💬 {}", message);
             }
-            println!();
         } else {
             let msg_display = if message.is_empty() {
                 String::new()
@@ -169,15 +211,57 @@ pub fn handle_versions(storage: &Storage, name: &str, verbose: bool, start: Inst
                 format!(" - {}", message)
             };
             println!(
-                "{} {} ({}){}",
+                "{} {} → r{} ({}){}",
                 "📌".cyan(),
-                version.bold(),
-                &hash[..8],
+                tag.bold(),
+                info.revision,
+                short,
                 msg_display
             );
         }
     }
 
+    if !log.is_empty() {
+        println!();
+        println!("{}", "Revisions:".bold());
+    }
+    let shown = if verbose { log.len() } else { log.len().min(10) };
+    for revision in log.iter().rev().take(shown) {
+        let names: Vec<&str> = tags
+            .iter()
+            .filter(|(_, info)| info.revision == revision.number)
+            .map(|(tag, _)| tag.as_str())
+            .collect();
+        let date = chrono::DateTime::parse_from_rfc3339(&revision.created_at)
+            .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| revision.created_at.clone());
+        let head = if Some(revision.number) == log.last().map(|r| r.number) {
+            " (head)".green().to_string()
+        } else {
+            String::new()
+        };
+        let labels = if names.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", names.join(", ")).cyan().to_string()
+        };
+        println!(
+            "  {:<5} {}  {}  {:<10}{}{}",
+            revision.label(),
+            revision.short_hash().dimmed(),
+            date.dimmed(),
+            revision.operation,
+            labels,
+            head
+        );
+    }
+    if shown < log.len() {
+        println!(
+            "  {}",
+            format!("… {} older revisions (use --verbose)", log.len() - shown).dimmed()
+        );
+    }
+
     record_command_metric(
         &mut telemetry,
         "versions",
@@ -198,66 +282,88 @@ pub fn handle_rollback(
 ) -> Result<()> {
     let mut telemetry = Some(init_telemetry(storage.base_dir().clone()).ok()).flatten();
 
-    // Check if prompt exists
-    let prompt_path = storage.prompt_path(name);
-    if !prompt_path.exists() {
-        eprintln!("{}", "❌ Prompt not found".red());
-        std::process::exit(1);
-    }
+    // Rolling back overwrites the prompt, so require an exact name
+    let resolved_name = resolve_prompt_for_write(storage, name, "roll back")?;
+    let name = resolved_name.as_str();
 
-    // Check if version exists
-    let versions_dir = prompt_path
-        .parent()
-        .unwrap()
-        .join(format!("{}.versions", name));
-    let version_file = versions_dir.join(format!("{}.md", version));
-    if !version_file.exists() {
+    // Accept a tag, a revision label (r3) or a hash prefix
+    let Some((revision, content)) = storage.read_revision(name, version)? else {
         eprintln!("{}", format!("❌ Version '{}' not found", version).red());
         std::process::exit(1);
-    }
+    };
 
     // Create backup if requested
+    let store = storage.revisions();
     if backup {
-        let backup_tag = format!("backup-{}", chrono::Utc::now().timestamp());
-        handle_version(
-            storage,
-            name,
-            &backup_tag,
-            Some("Pre-rollback backup"),
-            start,
-        )?;
-        println!(
-            "{}",
-            format!("💾 Created backup version '{}'", backup_tag).blue()
-        );
+        storage.snapshot_revision(name)?;
+        if let Some(head) = store.head(name)? {
+            let backup_tag = format!("backup-{}", chrono::Utc::now().timestamp());
+            store.set_tag(name, &backup_tag, head.number, Some("Pre-rollback backup"))?;
+            println!(
+                "{}",
+                format!("💾 Created backup version '{}' ({})", backup_tag, head.label()).blue()
+            );
+        }
     }
 
-    // Read version content
-    let version_content = fs::read_to_string(&version_file)?;
+    // Restore the revision as a new revision, so the rollback itself can be undone
+    let (mut metadata, body) = storage.parse_prompt_content(&content)?;
+    if store.tags(name)?.contains_key(version) {
+        metadata.version = Some(version.to_string());
+    }
+    metadata.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    storage.write_prompt(name, &metadata, &body)?;
 
-    // Extract the content (after metadata)
-    if let Some(content_start) = version_content.find("\n---\n") {
-        let content = &version_content[content_start + 5..]; // Skip \n---\n
+    println!(
+        "Rolled back '{}' to version '{}' ({})",
+        name,
+        version,
+        revision.label()
+    );
 
-        // Write to main prompt file
-        fs::write(&prompt_path, content)?;
+    record_command_metric(
+        &mut telemetry,
+        "rollback",
+        start.elapsed(),
+        true,
+        None,
+        None,
+    );
+    Ok(())
+}
 
-        // Update metadata
-        if let Ok(mut metadata) = storage.read_prompt_metadata(name) {
-            metadata.version = Some(version.to_string());
-            metadata.updated_at = Some(chrono::Utc::now().to_rfc3339());
-            let _ = storage.write_prompt_metadata(name, &metadata);
-        }
+pub fn handle_changelog(
+    storage: &Storage,
+    name: &str,
+    io_options: &IoOptions,
+    start: Instant,
+) -> Result<()> {
+    let mut telemetry = Some(init_telemetry(storage.base_dir().clone()).ok()).flatten();
 
-        println!("Rolled back '{}' to version '{}'", name, version);
+    // A bank name renders every versioned prompt in the bank
+    let is_bank = !storage.prompt_exists(name) && storage.base_dir().join("banks").join(name).is_dir();
+    let (name, kind, markdown) = if is_bank {
+        (name.to_string(), "bank", render_bank_changelog(storage, name)?)
     } else {
-        eprintln!("Invalid version file format");
-        std::process::exit(1);
+        let resolved_name = resolve_prompt_name(storage, name)?;
+        let name = split_version(&resolved_name).0.to_string();
+        let markdown = render_changelog(storage, &name)?;
+        (name, "prompt", markdown)
+    };
+
+    if io_options.is_structured() {
+        io_options.emit(&ChangelogOutput {
+            name,
+            kind,
+            markdown,
+        })?;
+    } else {
+        print!("{}", markdown);
     }
 
     record_command_metric(
         &mut telemetry,
-        "rollback",
+        "changelog",
         start.elapsed(),
         true,
         None,
@@ -265,3 +371,60 @@ pub fn handle_rollback(
     );
     Ok(())
 }
+
+pub fn handle_versions_prune(
+    storage: &Storage,
+    dry_run: bool,
+    orphans: bool,
+    io_options: &IoOptions,
+    start: Instant,
+) -> Result<()> {
+    let mut telemetry = Some(init_telemetry(storage.base_dir().clone()).ok()).flatten();
+
+    let config = RetentionConfig::load(storage.base_dir());
+    if !config.is_configured() && !orphans {
+        eprintln!("Error: No retention rules configured, so every revision is kept");
+        eprintln!("Set one with: ph config versions \"keep-last 20\" or ph config versions \"max-age 90d\"");
+        std::process::exit(1);
+    }
+
+    let report = storage.revisions().prune(&config, orphans, dry_run)?;
+    if io_options.is_structured() {
+        return io_options.emit(&report);
+    }
+
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for history in &report.histories {
+        let detail = if history.orphan {
+            "orphaned history".to_string()
+        } else {
+            let labels: Vec<String> = history.revisions.iter().map(|n| format!("r{}", n)).collect();
+            labels.join(", ")
+        };
+        let tags = if history.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" (tags: {})", history.tags.join(", "))
+        };
+        println!("  {} {}{}", history.name.bold(), detail.dimmed(), tags.yellow());
+    }
+
+    let revisions: usize = report.histories.iter().map(|h| h.revisions.len()).sum();
+    if report.histories.is_empty() && report.blobs == 0 {
+        println!("✓ Nothing to prune ({})", config.describe());
+    } else {
+        println!(
+            "{} {} {} revision(s) from {} prompt(s), {} blob(s), {} KB ({}ms)",
+            if dry_run { "🔍" } else { "🧹" },
+            verb,
+            revisions,
+            report.histories.len(),
+            report.blobs,
+            report.bytes.div_ceil(1024),
+            start.elapsed().as_millis()
+        );
+    }
+
+    record_command_metric(&mut telemetry, "versions prune", start.elapsed(), true, None, None);
+    Ok(())
+}
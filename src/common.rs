@@ -1,7 +1,7 @@
 // Common utility functions used across commands
 
 use crate::storage::Storage;
-use crate::{MatchResult, Matcher, Prompt};
+use crate::{MatchResult, Matcher, Prompt, ShortCodeRegistry};
 use anyhow::{Context, Result};
 use colored::Colorize;
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
@@ -129,19 +129,17 @@ pub fn resolve_prompt_name(storage: &Storage, query: &str) -> Result<String> {
 
     // Regular prompt resolution (no bank specified)
     let prompt_names = storage.list_prompts()?;
+    let short_codes = ShortCodeRegistry::load_for(storage, &prompt_names)?;
     let mut prompts = Vec::new();
 
     for prompt_name in &prompt_names {
         if let Ok((metadata, _)) = storage.read_prompt(prompt_name) {
             prompts.push(Prompt {
                 name: prompt_name.clone(),
-                short_code: Matcher::generate_short_code(
-                    prompt_name,
-                    &prompts
-                        .iter()
-                        .map(|p: &Prompt| p.short_code.clone())
-                        .collect::<Vec<_>>(),
-                ),
+                short_code: short_codes
+                    .code_for(prompt_name)
+                    .unwrap_or_default()
+                    .to_string(),
                 description: metadata.description,
                 version: metadata.version,
                 created_at: metadata.created_at,
@@ -3,7 +3,7 @@ use std::fs;
 use std::io::{self, Read, Write};
 use std::path::PathBuf;
 // Removed unused imports
-use crate::storage::Storage;
+use crate::storage::{is_file_reference, Storage};
 use crate::template::TemplateProcessor;
 
 pub struct Composer {
@@ -63,10 +63,7 @@ impl Composer {
 
         // Chain prompts with fuzzy matching
         for prompt_name in prompt_names.iter() {
-            let prompt_body = if prompt_name.starts_with('~')
-                || prompt_name.starts_with('/')
-                || prompt_name.contains('.')
-            {
+            let prompt_body = if is_file_reference(prompt_name) {
                 // It's a file path - expand and read
                 let expanded_path = shellexpand::tilde(prompt_name);
                 let path = PathBuf::from(expanded_path.as_ref());
@@ -107,10 +104,7 @@ impl Composer {
         let mut current_input = input.to_string();
 
         for prompt_name in prompt_names {
-            let prompt_body = if prompt_name.starts_with('~')
-                || prompt_name.starts_with('/')
-                || prompt_name.contains('.')
-            {
+            let prompt_body = if is_file_reference(prompt_name) {
                 // It's a file path - expand and read
                 let expanded_path = shellexpand::tilde(prompt_name);
                 let path = PathBuf::from(expanded_path.as_ref());
@@ -261,6 +255,7 @@ mod tests {
             version: None,
             git_hash: None,
             parent_version: None,
+            source: None,
         };
         let body1 = "Format this text nicely:\n{input}";
         storage.write_prompt("format", &metadata1, body1).unwrap();
@@ -274,6 +269,7 @@ mod tests {
             version: None,
             git_hash: None,
             parent_version: None,
+            source: None,
         };
         let body2 = "Summarize the following:\n{input}";
         storage
@@ -20,7 +20,7 @@ pub fn format_prompt_not_found(prompt_name: &str, available_prompts: &[String])
         })
         .collect();
     
-    suggestions.sort_by(|a, b| b.1.cmp(&a.1));
+    suggestions.sort_by_key(|s| std::cmp::Reverse(s.1));
     
     if !suggestions.is_empty() {
         message.push_str(&"Did you mean one of these?\n".yellow().to_string());
@@ -58,7 +58,7 @@ pub fn format_command_typo(command: &str, available_commands: &[&str]) -> String
             })
             .collect();
         
-        suggestions.sort_by(|a, b| b.1.cmp(&a.1));
+        suggestions.sort_by_key(|s| std::cmp::Reverse(s.1));
         
         if !suggestions.is_empty() && suggestions[0].1 > 50 {
             message.push_str(&format!("Did you mean '{}'?\n\n", suggestions[0].0).yellow().to_string());
@@ -113,7 +113,7 @@ pub fn format_prompt_syntax_error(file: &str, error: &str) -> String {
     message.push_str(&"description: What this prompt does\n".green().to_string());
     message.push_str(&"tags: [optional, tags]\n".green().dimmed().to_string());
     message.push_str(&"---\n".dimmed().to_string());
-    message.push_str(&"\nYour prompt content here\n".to_string());
+    message.push_str("\nYour prompt content here\n");
     
     message
 }
@@ -0,0 +1,286 @@
+//! Directory change notifications for `ph sync watch`
+//!
+//! On Linux this is a thin wrapper over inotify. Other platforms fall back to
+//! comparing modification times, which is slower to notice changes but needs
+//! nothing from the OS.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Watches a set of directories and reports the paths that changed in them
+pub struct FileWatcher {
+    inner: imp::Watcher,
+}
+
+impl FileWatcher {
+    /// Create a watcher with no directories
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            inner: imp::Watcher::new()?,
+        })
+    }
+
+    /// Start watching a directory (not recursive); watching it twice is a no-op
+    pub fn watch_dir(&mut self, dir: &Path) -> Result<()> {
+        self.inner.watch_dir(dir)
+    }
+
+    /// Watch the directory holding `path` and return the keys its changes show up under
+    ///
+    /// A symlinked file also changes where its target lives, so that directory
+    /// is watched too.
+    pub fn watch_file(&mut self, path: &Path) -> Result<Vec<PathBuf>> {
+        let mut keys = vec![watch_key(path)];
+        if let Ok(target) = std::fs::canonicalize(path) {
+            if !keys.contains(&target) {
+                keys.push(target);
+            }
+        }
+        for key in &keys {
+            if let Some(dir) = key.parent().filter(|dir| dir.is_dir()) {
+                self.watch_dir(dir)?;
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Wait up to `timeout` for changes and return the paths involved
+    ///
+    /// An empty result means nothing changed before the timeout.
+    pub fn wait(&mut self, timeout: Duration) -> Result<Vec<PathBuf>> {
+        let mut paths = self.inner.wait(timeout)?;
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+}
+
+/// The form watched and reported paths are compared in: the file name
+/// under its canonical directory
+///
+/// Relative paths resolve against the current directory, and unlike
+/// `fs::canonicalize` this still works once the file itself is gone.
+pub fn watch_key(path: &Path) -> PathBuf {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return path.to_path_buf();
+    };
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    std::fs::canonicalize(dir)
+        .map(|dir| dir.join(name))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use anyhow::{Context, Result};
+    use std::collections::HashMap;
+    use std::ffi::{CString, OsStr};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    const EVENTS: u32 = libc::IN_CLOSE_WRITE
+        | libc::IN_MODIFY
+        | libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO;
+
+    pub struct Watcher {
+        fd: libc::c_int,
+        dirs: HashMap<libc::c_int, PathBuf>,
+    }
+
+    impl Watcher {
+        pub fn new() -> Result<Self> {
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error()).context("Failed to initialise inotify");
+            }
+            Ok(Self {
+                fd,
+                dirs: HashMap::new(),
+            })
+        }
+
+        pub fn watch_dir(&mut self, dir: &Path) -> Result<()> {
+            let c_path = CString::new(dir.as_os_str().as_bytes())
+                .with_context(|| format!("Invalid directory path {:?}", dir))?;
+            let wd = unsafe { libc::inotify_add_watch(self.fd, c_path.as_ptr(), EVENTS) };
+            if wd < 0 {
+                return Err(std::io::Error::last_os_error())
+                    .with_context(|| format!("Failed to watch {}", dir.display()));
+            }
+            // inotify hands back the same descriptor for a directory it already watches
+            self.dirs.insert(wd, dir.to_path_buf());
+            Ok(())
+        }
+
+        pub fn wait(&mut self, timeout: Duration) -> Result<Vec<PathBuf>> {
+            let mut pollfd = libc::pollfd {
+                fd: self.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let ready = unsafe { libc::poll(&mut pollfd, 1, timeout.as_millis() as libc::c_int) };
+            if ready < 0 {
+                let err = std::io::Error::last_os_error();
+                // A signal (e.g. Ctrl+C) interrupts poll; the caller checks for shutdown
+                if err.kind() == std::io::ErrorKind::Interrupted {
+                    return Ok(Vec::new());
+                }
+                return Err(err).context("Failed to wait for file changes");
+            }
+            if ready == 0 {
+                return Ok(Vec::new());
+            }
+
+            let mut paths = Vec::new();
+            let mut buffer = [0u8; 4096];
+            loop {
+                let len = unsafe {
+                    libc::read(self.fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len())
+                };
+                if len <= 0 {
+                    break;
+                }
+                let len = len as usize;
+                let header = std::mem::size_of::<libc::inotify_event>();
+                let mut offset = 0;
+                while offset + header <= len {
+                    let event = unsafe {
+                        std::ptr::read_unaligned(buffer.as_ptr().add(offset) as *const libc::inotify_event)
+                    };
+                    let name_start = offset + header;
+                    let name_end = (name_start + event.len as usize).min(len);
+                    if let Some(dir) = self.dirs.get(&event.wd) {
+                        let name = &buffer[name_start..name_end];
+                        let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+                        if name.is_empty() {
+                            paths.push(dir.clone());
+                        } else {
+                            paths.push(dir.join(OsStr::from_bytes(name)));
+                        }
+                    }
+                    offset = name_end;
+                }
+            }
+            Ok(paths)
+        }
+    }
+
+    impl Drop for Watcher {
+        fn drop(&mut self) {
+            unsafe {
+                libc::close(self.fd);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use anyhow::Result;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant, SystemTime};
+
+    type Snapshot = HashMap<PathBuf, (Option<SystemTime>, u64)>;
+
+    pub struct Watcher {
+        dirs: HashMap<PathBuf, Snapshot>,
+    }
+
+    impl Watcher {
+        pub fn new() -> Result<Self> {
+            Ok(Self {
+                dirs: HashMap::new(),
+            })
+        }
+
+        pub fn watch_dir(&mut self, dir: &Path) -> Result<()> {
+            if !self.dirs.contains_key(dir) {
+                self.dirs.insert(dir.to_path_buf(), snapshot(dir));
+            }
+            Ok(())
+        }
+
+        pub fn wait(&mut self, timeout: Duration) -> Result<Vec<PathBuf>> {
+            let deadline = Instant::now() + timeout;
+            loop {
+                let mut paths = Vec::new();
+                for (dir, previous) in self.dirs.iter_mut() {
+                    let current = snapshot(dir);
+                    for (path, state) in &current {
+                        if previous.get(path) != Some(state) {
+                            paths.push(path.clone());
+                        }
+                    }
+                    paths.extend(previous.keys().filter(|p| !current.contains_key(*p)).cloned());
+                    *previous = current;
+                }
+                if !paths.is_empty() || Instant::now() >= deadline {
+                    return Ok(paths);
+                }
+                std::thread::sleep(Duration::from_millis(50).min(timeout));
+            }
+        }
+    }
+
+    fn snapshot(dir: &Path) -> Snapshot {
+        let mut files = HashMap::new();
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                if let Ok(meta) = entry.metadata() {
+                    files.insert(entry.path(), (meta.modified().ok(), meta.len()));
+                }
+            }
+        }
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reports_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut watcher = FileWatcher::new().unwrap();
+        watcher.watch_dir(temp_dir.path()).unwrap();
+        watcher.watch_dir(temp_dir.path()).unwrap();
+
+        assert!(watcher.wait(Duration::from_millis(10)).unwrap().is_empty());
+
+        let file = temp_dir.path().join("notes.md");
+        std::fs::write(&file, "hello").unwrap();
+        let changed = watcher.wait(Duration::from_secs(2)).unwrap();
+        assert_eq!(changed, vec![file]);
+    }
+
+    #[test]
+    fn test_matches_files_registered_by_relative_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.md");
+        std::fs::write(&file, "hello").unwrap();
+
+        // The same file, spelled relative to wherever the tests run from
+        let cwd = std::env::current_dir().unwrap();
+        let mut relative = PathBuf::new();
+        for _ in cwd.components().skip(1) {
+            relative.push("..");
+        }
+        relative.push(file.strip_prefix("/").unwrap());
+        assert!(relative.is_relative());
+
+        let mut watcher = FileWatcher::new().unwrap();
+        let keys = watcher.watch_file(&relative).unwrap();
+        std::fs::write(&file, "edited").unwrap();
+        let changed = watcher.wait(Duration::from_secs(2)).unwrap();
+        assert!(!changed.is_empty());
+        assert!(changed.iter().all(|path| keys.contains(&watch_key(path))), "{:?} vs {:?}", changed, keys);
+    }
+}
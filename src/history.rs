@@ -2,51 +2,161 @@
 //!
 //! Provides functionality to track command usage history, enabling features
 //! like usage analytics, frequently used prompts, and command replay.
+//!
+//! History is an append-only JSONL log (`history.jsonl`) that is rotated into
+//! `history.1.jsonl`, `history.2.jsonl`, ... once it grows past a size limit.
+//! Every entry has a stable id. How much content is kept is controlled by the
+//! `[history]` section of config.toml.
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
 use std::path::PathBuf;
 
+/// How much command content is written to history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryContent {
+    /// Only the command, timing and status (privacy opt-out)
+    None,
+    /// Short previews of input and output
+    #[default]
+    Preview,
+    /// Full input, variables and rendered output, up to `max_field_bytes`
+    Full,
+}
+
+/// History settings from the `[history]` section of config.toml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// What to store for each command
+    #[serde(default)]
+    pub content: HistoryContent,
+    /// Cap for each stored input/output field
+    #[serde(default = "default_max_field_bytes")]
+    pub max_field_bytes: usize,
+    /// Size at which the active log is rotated
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+    /// Number of rotated logs to keep
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+}
+
+fn default_max_field_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_max_file_bytes() -> u64 {
+    5 * 1024 * 1024
+}
+
+fn default_max_files() -> usize {
+    5
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            content: HistoryContent::default(),
+            max_field_bytes: default_max_field_bytes(),
+            max_file_bytes: default_max_file_bytes(),
+            max_files: default_max_files(),
+        }
+    }
+}
+
+impl HistoryConfig {
+    /// Read the `[history]` section from `config.toml` in the base directory
+    pub fn load(base_dir: &std::path::Path) -> Self {
+        fs::read_to_string(base_dir.join("config.toml"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Value>().ok())
+            .and_then(|config| config.get("history").cloned())
+            .and_then(|table| table.try_into().ok())
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HistoryEntry {
+    /// Stable identifier used by `ph history show|replay|diff`
+    #[serde(default)]
+    pub id: String,
     pub timestamp: DateTime<Utc>,
     pub command: String,
     pub input_preview: String,
     pub output_preview: String,
     pub success: bool,
     pub duration_ms: u128,
+    /// Resolved prompt name, used for replay
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Full input (only with `content = "full"`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    /// Template variables used while rendering (only with `content = "full"`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vars: BTreeMap<String, String>,
+    /// Full rendered output (only with `content = "full"`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Whether input or output was cut at `max_field_bytes`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
 }
 
 pub struct HistoryTracker {
-    history_file: PathBuf,
-    max_entries: usize,
+    history_dir: PathBuf,
+    config: HistoryConfig,
 }
 
 impl HistoryTracker {
     pub fn new(base_dir: PathBuf) -> Self {
+        let config = HistoryConfig::load(&base_dir);
+        Self::with_config(base_dir, config)
+    }
+
+    pub fn with_config(base_dir: PathBuf, config: HistoryConfig) -> Self {
         Self {
-            history_file: base_dir.join("history.json"),
-            max_entries: 1000,
+            history_dir: base_dir,
+            config,
         }
     }
 
     pub fn record(&self, entry: HistoryEntry) -> Result<()> {
-        let mut history = self.load_history().unwrap_or_default();
+        fs::create_dir_all(&self.history_dir)
+            .with_context(|| format!("Failed to create directory: {:?}", self.history_dir))?;
 
-        // Add new entry at the beginning
-        history.insert(0, entry);
+        self.migrate_legacy()?;
 
-        // Keep only the most recent entries
-        if history.len() > self.max_entries {
-            history.truncate(self.max_entries);
-        }
+        let entry = self.apply_policy(entry);
+        let mut line =
+            serde_json::to_string(&entry).with_context(|| "Failed to serialize history entry")?;
+        line.push('\n');
+
+        self.rotate_if_needed(line.len() as u64)?;
+
+        let path = self.log_path(0);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open history file: {:?}", path))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write history file: {:?}", path))?;
 
-        self.save_history(&history)?;
         Ok(())
     }
 
+    /// Whether full input, variables and output are kept (`content = "full"`)
+    pub fn stores_full_content(&self) -> bool {
+        self.config.content == HistoryContent::Full
+    }
+
     pub fn get_recent(&self, limit: usize) -> Result<Vec<HistoryEntry>> {
         let history = self.load_history().unwrap_or_default();
         Ok(history.into_iter().take(limit).collect())
@@ -57,6 +167,21 @@ impl HistoryTracker {
         Ok(history.into_iter().next())
     }
 
+    /// Find an entry by id or unique id prefix
+    pub fn find(&self, id: &str) -> Result<HistoryEntry> {
+        let history = self.load_history()?;
+        let mut matches = history.into_iter().filter(|entry| entry.id.starts_with(id));
+
+        match (matches.next(), matches.next()) {
+            (Some(entry), None) => Ok(entry),
+            (Some(_), Some(_)) => Err(anyhow!(
+                "History id '{}' is ambiguous, use more characters",
+                id
+            )),
+            (None, _) => Err(anyhow!("No history entry with id '{}'", id)),
+        }
+    }
+
     pub fn search(&self, query: &str) -> Result<Vec<HistoryEntry>> {
         let history = self.load_history().unwrap_or_default();
         Ok(history
@@ -84,32 +209,156 @@ impl HistoryTracker {
             .collect())
     }
 
+    /// Load all entries, newest first
     fn load_history(&self) -> Result<Vec<HistoryEntry>> {
-        if !self.history_file.exists() {
+        // Migrate first so legacy entries keep the ids they are shown with
+        let migrated = self.migrate_legacy().is_ok();
+        let mut history = Vec::new();
+
+        // Oldest rotated log first, so pushing keeps chronological order
+        for index in (0..=self.config.max_files).rev() {
+            let path = self.log_path(index);
+            if !path.exists() {
+                continue;
+            }
+
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read history file: {:?}", path))?;
+
+            // Skip lines that fail to parse (e.g. a write cut short by a crash)
+            history.extend(
+                content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok()),
+            );
+        }
+
+        history.reverse();
+
+        // A read-only library cannot migrate; show the legacy entries as they are
+        if !migrated {
+            history.extend(self.load_legacy()?);
+        }
+
+        Ok(history)
+    }
+
+    /// Strip or cap content according to the configured policy
+    fn apply_policy(&self, mut entry: HistoryEntry) -> HistoryEntry {
+        match self.config.content {
+            HistoryContent::None => {
+                entry.input_preview.clear();
+                entry.output_preview.clear();
+                entry.input = None;
+                entry.output = None;
+                entry.vars.clear();
+            }
+            HistoryContent::Preview => {
+                entry.input = None;
+                entry.output = None;
+                entry.vars.clear();
+            }
+            HistoryContent::Full => {
+                let cap = self.config.max_field_bytes;
+                for field in [&mut entry.input, &mut entry.output].into_iter().flatten() {
+                    if field.len() > cap {
+                        field.truncate(floor_char_boundary(field, cap));
+                        entry.truncated = true;
+                    }
+                }
+            }
+        }
+
+        entry
+    }
+
+    /// Rotate history.jsonl -> history.1.jsonl -> ... when the next write would exceed the cap
+    fn rotate_if_needed(&self, incoming: u64) -> Result<()> {
+        let current = self.log_path(0);
+        let size = fs::metadata(&current).map(|m| m.len()).unwrap_or(0);
+
+        if size == 0 || size + incoming <= self.config.max_file_bytes {
+            return Ok(());
+        }
+
+        let oldest = self.log_path(self.config.max_files);
+        if oldest.exists() {
+            fs::remove_file(&oldest)
+                .with_context(|| format!("Failed to remove old history: {:?}", oldest))?;
+        }
+
+        for index in (0..self.config.max_files).rev() {
+            let from = self.log_path(index);
+            if from.exists() {
+                fs::rename(&from, self.log_path(index + 1))
+                    .with_context(|| format!("Failed to rotate history file: {:?}", from))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn log_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.history_dir.join("history.jsonl")
+        } else {
+            self.history_dir.join(format!("history.{}.jsonl", index))
+        }
+    }
+
+    fn legacy_path(&self) -> PathBuf {
+        self.history_dir.join("history.json")
+    }
+
+    /// Read entries from the old single-file history.json (newest first)
+    fn load_legacy(&self) -> Result<Vec<HistoryEntry>> {
+        let path = self.legacy_path();
+        if !path.exists() {
             return Ok(Vec::new());
         }
 
-        let content = fs::read_to_string(&self.history_file)
-            .with_context(|| format!("Failed to read history file: {:?}", self.history_file))?;
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read history file: {:?}", path))?;
 
-        let history: Vec<HistoryEntry> =
+        let mut history: Vec<HistoryEntry> =
             serde_json::from_str(&content).with_context(|| "Failed to parse history file")?;
+        for entry in &mut history {
+            if entry.id.is_empty() {
+                entry.id = new_entry_id();
+            }
+        }
 
         Ok(history)
     }
 
-    fn save_history(&self, history: &[HistoryEntry]) -> Result<()> {
-        // Ensure directory exists
-        if let Some(parent) = self.history_file.parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+    /// Move entries from history.json into the JSONL log, oldest first
+    fn migrate_legacy(&self) -> Result<()> {
+        let legacy = self.load_legacy().unwrap_or_default();
+        if legacy.is_empty() {
+            if self.legacy_path().exists() {
+                let _ = fs::remove_file(self.legacy_path());
+            }
+            return Ok(());
+        }
+
+        let mut content = String::new();
+        for entry in legacy.into_iter().rev() {
+            content.push_str(&serde_json::to_string(&entry)?);
+            content.push('\n');
         }
 
-        let content =
-            serde_json::to_string_pretty(history).with_context(|| "Failed to serialize history")?;
+        let path = self.log_path(0);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open history file: {:?}", path))?;
+        file.write_all(content.as_bytes())
+            .with_context(|| format!("Failed to write history file: {:?}", path))?;
 
-        fs::write(&self.history_file, content)
-            .with_context(|| format!("Failed to write history file: {:?}", self.history_file))?;
+        fs::remove_file(self.legacy_path())
+            .with_context(|| "Failed to remove migrated history.json")?;
 
         Ok(())
     }
@@ -124,21 +373,39 @@ impl HistoryEntry {
         duration_ms: u128,
     ) -> Self {
         Self {
+            id: new_entry_id(),
             timestamp: Utc::now(),
             command,
             input_preview: Self::truncate_preview(input),
             output_preview: Self::truncate_preview(output),
             success,
             duration_ms,
+            prompt: None,
+            input: Some(input.to_string()),
+            vars: BTreeMap::new(),
+            output: Some(output.to_string()),
+            truncated: false,
         }
     }
 
+    /// Attach the resolved prompt name so the entry can be replayed
+    pub fn with_prompt(mut self, prompt: &str) -> Self {
+        self.prompt = Some(prompt.to_string());
+        self
+    }
+
+    /// Attach the template variables used while rendering
+    pub fn with_vars(mut self, vars: BTreeMap<String, String>) -> Self {
+        self.vars = vars;
+        self
+    }
+
     fn truncate_preview(text: &str) -> String {
         const MAX_PREVIEW: usize = 100;
         if text.len() <= MAX_PREVIEW {
             text.to_string()
         } else {
-            format!("{}...", &text[..MAX_PREVIEW])
+            format!("{}...", &text[..floor_char_boundary(text, MAX_PREVIEW)])
         }
     }
 
@@ -153,8 +420,9 @@ impl HistoryEntry {
         let timestamp = self.timestamp.format("%Y-%m-%d %H:%M:%S");
 
         println!(
-            "{} {} {} ({}ms)",
+            "{} {} {} {} ({}ms)",
             status,
+            self.id.yellow(),
             timestamp.to_string().dimmed(),
             self.command.bold(),
             self.duration_ms
@@ -170,6 +438,22 @@ impl HistoryEntry {
     }
 }
 
+/// Short random id, stable for the lifetime of the entry
+fn new_entry_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..10].to_string()
+}
+
+/// Largest index <= `index` that falls on a char boundary
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    (0..=index)
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,4 +494,67 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].command, "use api-design");
     }
+
+    #[test]
+    fn test_full_content_caps_and_rotation() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = HistoryConfig {
+            content: HistoryContent::Full,
+            max_field_bytes: 8,
+            max_file_bytes: 400,
+            max_files: 1,
+        };
+        let tracker = HistoryTracker::with_config(temp_dir.path().to_path_buf(), config);
+
+        let mut ids = Vec::new();
+        for i in 0..10 {
+            let entry = HistoryEntry::new(format!("use p{}", i), "input", "a long output", true, 1);
+            ids.push(entry.id.clone());
+            tracker.record(entry).unwrap();
+        }
+
+        let last = tracker.find(&ids[9]).unwrap();
+        assert_eq!(last.input.as_deref(), Some("input"));
+        assert_eq!(last.output.as_deref(), Some("a long o"));
+        assert!(last.truncated);
+
+        // Only the active log and one rotated log are kept
+        assert!(temp_dir.path().join("history.1.jsonl").exists());
+        assert!(!temp_dir.path().join("history.2.jsonl").exists());
+        assert!(tracker.find(&ids[0]).is_err());
+        assert_eq!(tracker.get_last().unwrap().unwrap().id, ids[9]);
+    }
+
+    #[test]
+    fn test_privacy_opt_out_and_legacy_migration() {
+        let temp_dir = TempDir::new().unwrap();
+        let legacy = vec![HistoryEntry::new("use old".to_string(), "", "", true, 1)];
+        fs::write(
+            temp_dir.path().join("history.json"),
+            serde_json::to_string(&legacy).unwrap(),
+        )
+        .unwrap();
+
+        let config = HistoryConfig {
+            content: HistoryContent::None,
+            ..HistoryConfig::default()
+        };
+        let tracker = HistoryTracker::with_config(temp_dir.path().to_path_buf(), config);
+        tracker
+            .record(HistoryEntry::new(
+                "use secret".to_string(),
+                "token",
+                "token",
+                true,
+                1,
+            ))
+            .unwrap();
+
+        let history = tracker.get_recent(10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].command, "use secret");
+        assert!(history[0].input_preview.is_empty() && history[0].input.is_none());
+        assert_eq!(history[1].command, "use old");
+        assert!(!temp_dir.path().join("history.json").exists());
+    }
 }
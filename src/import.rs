@@ -306,6 +306,7 @@ impl Importer {
             },
             git_hash: None,
             parent_version: None,
+            source: None,
         };
 
         Ok((metadata, content.to_string()))
@@ -579,6 +580,11 @@ impl ImportResult {
         self.errors.push((file, error));
     }
 
+    /// Names of the prompts written by this import
+    pub fn imported(&self) -> &[String] {
+        &self.imported
+    }
+
     pub fn display(&self) {
         println!("Import complete");
 
@@ -1,12 +1,15 @@
 use anyhow::Result;
 use chrono::Utc;
 use is_terminal::IsTerminal;
+use serde::Serialize;
+use serde_yaml::Value as YamlValue;
 use std::time::Instant;
 
 use crate::{Clipboard, Storage};
 
 /// Command categories for smart default behavior
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Default)]
 pub enum CommandCategory {
     /// Text transformation commands (use, show, clean) - auto-clipboard in TTY
     TextTransform,
@@ -15,9 +18,119 @@ pub enum CommandCategory {
     /// Creation commands (new) - auto-clipboard when content created
     Creation,
     /// Utility commands (compose, diff, etc) - context-dependent
+    #[default]
     Utility,
 }
 
+/// Output format of read commands (`--json`, `--output-format`)
+///
+/// Structured formats print only data on stdout: no colors, emoji or timing
+/// lines. Progress messages go to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Human-readable, colored output
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    /// Tab-separated values with a header row
+    Tsv,
+}
+
+impl OutputFormat {
+    /// Whether the format is meant for scripts rather than people
+    pub fn is_structured(self) -> bool {
+        self != OutputFormat::Text
+    }
+
+    /// Render a serializable value in this format
+    pub fn render<T: Serialize>(self, value: &T) -> Result<String> {
+        match self {
+            OutputFormat::Text | OutputFormat::Json => {
+                Ok(format!("{}\n", serde_json::to_string_pretty(value)?))
+            }
+            OutputFormat::Yaml => Ok(serde_yaml::to_string(value)?),
+            OutputFormat::Tsv => Ok(to_tsv(&serde_yaml::to_value(value)?)),
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => Err(anyhow::anyhow!(
+                "Unknown output format '{}'. Use: text, json, yaml, tsv",
+                s
+            )),
+        }
+    }
+}
+
+/// Lists of objects become a header row plus one row per item; a single
+/// object becomes `key<TAB>value` rows. Columns keep the schema's field order.
+fn to_tsv(value: &YamlValue) -> String {
+    let mut out = String::new();
+    match value {
+        YamlValue::Sequence(items) => {
+            let columns: Vec<&YamlValue> = match items.first() {
+                Some(YamlValue::Mapping(first)) => first.keys().collect(),
+                _ => Vec::new(),
+            };
+            if columns.is_empty() {
+                for item in items {
+                    out.push_str(&tsv_cell(item));
+                    out.push('\n');
+                }
+                return out;
+            }
+            let header: Vec<String> = columns.iter().map(|c| tsv_cell(c)).collect();
+            out.push_str(&header.join("\t"));
+            out.push('\n');
+            for item in items {
+                let row: Vec<String> = columns
+                    .iter()
+                    .map(|c| item.get(*c).map(tsv_cell).unwrap_or_default())
+                    .collect();
+                out.push_str(&row.join("\t"));
+                out.push('\n');
+            }
+        }
+        YamlValue::Mapping(map) => {
+            for (key, value) in map {
+                out.push_str(&format!("{}\t{}\n", tsv_cell(key), tsv_cell(value)));
+            }
+        }
+        other => {
+            out.push_str(&tsv_cell(other));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn tsv_cell(value: &YamlValue) -> String {
+    let text = match value {
+        YamlValue::Null => String::new(),
+        YamlValue::Bool(b) => b.to_string(),
+        YamlValue::Number(n) => n.to_string(),
+        YamlValue::String(s) => s.clone(),
+        YamlValue::Sequence(items) if items.iter().all(|i| !i.is_mapping() && !i.is_sequence()) => {
+            items.iter().map(tsv_cell).collect::<Vec<_>>().join(",")
+        }
+        other => serde_json::to_string(other).unwrap_or_default(),
+    };
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
 /// Unified I/O options for all commands that support file operations
 #[derive(Debug, Clone, Default)]
 pub struct IoOptions {
@@ -27,13 +140,14 @@ pub struct IoOptions {
     pub file: Option<String>,
     pub quiet: bool,
     pub command_category: CommandCategory,
+    /// Replace detected secrets with placeholders before output
+    pub redact: bool,
+    /// Text for people, or a structured format for scripts
+    pub format: OutputFormat,
+    /// Origin recorded in the metadata of prompts saved with `-s`
+    pub source: Option<String>,
 }
 
-impl Default for CommandCategory {
-    fn default() -> Self {
-        CommandCategory::Utility
-    }
-}
 
 impl IoOptions {
     /// Create IoOptions from command line arguments
@@ -51,6 +165,9 @@ impl IoOptions {
             file: file.map(|s| s.to_string()),
             quiet,
             command_category: CommandCategory::default(),
+            redact: false,
+            format: OutputFormat::default(),
+            source: None,
         }
     }
     
@@ -60,6 +177,56 @@ impl IoOptions {
         self
     }
 
+    /// Enable secret redaction of the output
+    pub fn with_redaction(mut self, redact: bool) -> Self {
+        self.redact = redact;
+        self
+    }
+
+    /// Record where saved content came from
+    pub fn with_source(mut self, source: Option<String>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Set the output format
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Whether output must be machine-readable (no decoration on stdout)
+    pub fn is_structured(&self) -> bool {
+        self.format.is_structured()
+    }
+
+    /// Print structured data in the selected format
+    ///
+    /// Honors `-f` (write to file) and `-c` (copy to clipboard); confirmations
+    /// go to stderr so stdout only carries data.
+    pub fn emit<T: Serialize>(&self, value: &T) -> Result<()> {
+        let rendered = self.format.render(value)?;
+
+        if self.clipboard {
+            let mut clipboard_handler = Clipboard::new();
+            if !clipboard_handler.copy_to_clipboard(&rendered)? && !self.quiet {
+                eprintln!("Clipboard unavailable");
+            }
+        }
+
+        match self.file.as_deref() {
+            Some(path) if !path.is_empty() => {
+                std::fs::write(path, &rendered)?;
+                if !self.quiet {
+                    eprintln!("Wrote to '{}'", path);
+                }
+            }
+            _ => print!("{}", rendered),
+        }
+
+        Ok(())
+    }
+
     /// Apply unified I/O logic to content
     pub fn apply_unified_io(
         &self,
@@ -90,7 +257,11 @@ impl IoOptions {
             clipboard_success = clipboard_handler.copy_to_clipboard(content)?;
             if !self.quiet {
                 if clipboard_success {
-                    println!("Copied to clipboard ({}ms)", start.elapsed().as_millis());
+                    println!(
+                        "Copied to clipboard{} ({}ms)",
+                        clipboard_handler.via(),
+                        start.elapsed().as_millis()
+                    );
                 } else {
                     eprintln!("Clipboard unavailable - showing content below:");
                 }
@@ -108,6 +279,7 @@ impl IoOptions {
                 version: None,
                 git_hash: None,
                 parent_version: None,
+                source: self.source.clone(),
             };
             storage.write_prompt(name, &metadata, content)?;
             if !self.quiet {
@@ -152,7 +324,7 @@ impl IoOptions {
                 // Try to create bidirectional sync between file and prompt
                 if let Ok(sync_manager) = crate::commands::SimpleSyncManager::new(storage.clone()) {
                     let file_path = std::path::PathBuf::from(&file_path);
-                    if let Err(e) = sync_manager.sync_prompt(prompt_name, Some(file_path.clone())) {
+                    if let Err(e) = sync_manager.sync_prompt(prompt_name, Some(file_path.clone()), false) {
                         if !self.quiet {
                             eprintln!("Warning: Could not create bidirectional sync: {}", e);
                             eprintln!("File saved, but changes won't sync automatically.");
@@ -274,7 +446,11 @@ impl IoOptions {
             let clipboard_success = clipboard_handler.copy_to_clipboard(content)?;
             if !self.quiet {
                 if clipboard_success {
-                    println!("Copied to clipboard ({}ms)", start.elapsed().as_millis());
+                    println!(
+                        "Copied to clipboard{} ({}ms)",
+                        clipboard_handler.via(),
+                        start.elapsed().as_millis()
+                    );
                 } else {
                     eprintln!("Clipboard unavailable - showing content below:");
                 }
@@ -360,4 +536,40 @@ mod tests {
         let opts = IoOptions::new(None, None, false, Some("file.txt"), false);
         assert!(opts.has_output_operations()); // Has file
     }
+
+    #[test]
+    fn test_structured_output_formats() {
+        #[derive(Serialize)]
+        struct Row {
+            name: String,
+            tags: Vec<String>,
+            description: String,
+        }
+        let rows = vec![
+            Row {
+                name: "review".to_string(),
+                tags: vec!["code".to_string(), "qa".to_string()],
+                description: "Review\tcode".to_string(),
+            },
+            Row {
+                name: "debug".to_string(),
+                tags: vec![],
+                description: "Find bugs".to_string(),
+            },
+        ];
+
+        let tsv = OutputFormat::Tsv.render(&rows).unwrap();
+        assert_eq!(
+            tsv,
+            "name\ttags\tdescription\nreview\tcode,qa\tReview\\tcode\ndebug\t\tFind bugs\n"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&OutputFormat::Json.render(&rows).unwrap()).unwrap();
+        assert_eq!(json[0]["tags"][1], "qa");
+        assert!(OutputFormat::Yaml.render(&rows).unwrap().contains("- name: review"));
+
+        assert_eq!("yml".parse::<OutputFormat>().unwrap(), OutputFormat::Yaml);
+        assert!("xml".parse::<OutputFormat>().is_err());
+        assert!(!IoOptions::default().is_structured());
+    }
 }
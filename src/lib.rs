@@ -39,25 +39,38 @@
 //! - [`tui`]: Terminal user interface components (feature-gated)
 
 pub mod cache;
+pub mod changelog;
 pub mod clean;
 pub mod cli;
 pub mod clipboard;
+pub mod cloud_sync;
 pub mod commands;
 pub mod common;
 pub mod edit;
 pub mod error_help;
+pub mod file_watcher;
 pub mod health;
 pub mod history;
 pub mod io_options;
 pub mod logging;
 pub mod matching;
+pub mod merge;
+pub mod patch;
 pub mod perf_verify;
+pub mod redaction;
+pub mod revisions;
 pub mod signals;
 pub mod storage;
 pub mod security;
+pub mod security_detectors;
+pub mod security_policy;
+pub mod security_report;
+pub mod short_codes;
+pub mod similarity;
 pub mod sync_manager;
 pub mod telemetry;
 pub mod template;
+pub mod transcript;
 
 #[cfg(feature = "compose")]
 pub mod compose;
@@ -72,23 +85,30 @@ pub mod tui;
 
 pub use cache::{DirectoryCache, PromptCache};
 pub use cli::{Cli, Commands, VarsCommands};
-pub use clipboard::Clipboard;
+pub use clipboard::{Clipboard, ClipboardBackend, ClipboardConfig, ClipboardEnvironment};
 pub use commands::configuration::{get_editor_command_for_file, load_editor_config, EditorConfig};
 pub use health::{ComponentHealth, HealthMonitor, HealthReport, HealthStatus, SystemMetrics};
-pub use history::{HistoryEntry, HistoryTracker};
-pub use io_options::{IoOptions, CommandCategory};
+pub use history::{HistoryConfig, HistoryContent, HistoryEntry, HistoryTracker};
+pub use io_options::{IoOptions, CommandCategory, OutputFormat};
 pub use logging::{init_logging, log_command_execution, log_security_event, log_storage_operation, LogConfig};
-pub use matching::{MatchResult, Matcher, Prompt};
+pub use matching::{
+    exact_matching_enabled, set_exact_matching, MatchResult, Matcher, Prompt, ResolutionPolicy,
+};
 pub use perf_verify::{PerformanceReport, PerformanceVerifier};
 pub use signals::{
     is_shutdown_requested, request_shutdown, ShutdownAware, ShutdownConfig, ShutdownHandler,
 };
-pub use storage::{PromptMetadata, Storage};
+pub use redaction::{Redaction, RedactionAllowList, Redactor};
+pub use revisions::{set_revision_operation, RetentionConfig, Revision, RevisionStore};
+pub use storage::{is_file_reference, split_version, with_version, PromptMetadata, Storage};
 pub use security::{
-    SecurityAuditReport, SecurityConfig, SecurityIssue, SecurityIssueType, SecurityScanResult, 
+    Confidence, GateDecision, GateResult, SecurityAuditReport, SecurityConfig, SecurityIssue, SecurityIssueType, SecurityScanResult, 
     SecurityScanner, SecuritySeverity, SecurityStatus,
 };
-pub use sync_manager::{SyncManager, SyncMetadata, SyncRegistry, SyncStatus, SyncStatusType};
+pub use security_detectors::{shannon_entropy, SecretDetector};
+pub use security_policy::{SecurityBaseline, SecurityPolicy};
+pub use short_codes::{ShortCodeEntry, ShortCodeRegistry};
+pub use sync_manager::{SyncAction, SyncManager, SyncMetadata, SyncRegistry, SyncStatus, SyncStatusType};
 pub use telemetry::{
     format_time_saved, generate_contribution_graph_html, init_telemetry, record_command_metric,
     record_performance_metric, TelemetryCollector,
@@ -101,6 +121,6 @@ pub use compose::{parse_prompt_list, Composer};
 pub use import::{ImportResult, Importer};
 #[cfg(feature = "registry")]
 pub use registry::{
-    default_registry_url, InstallResult, PackagePrompt, PublishRequest, RegistryClient,
+    default_registry_url, BlockedPrompt, InstallResult, PackagePrompt, PublishRequest, RegistryClient,
     ShareResponse, SuggestionResponse, PublicShareRequest, InviteShareRequest,
 };
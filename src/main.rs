@@ -7,9 +7,13 @@ use prompthive::error_help;
 #[cfg(feature = "registry")]
 use prompthive::RegistryClient;
 use prompthive::{
-    init_logging, init_telemetry, log_command_execution, record_command_metric, CommandCategory,
-    HistoryEntry, HistoryTracker, IoOptions, LogConfig,
-    MatchResult, Matcher, PerformanceVerifier, Prompt, ShutdownHandler, Storage,
+    exact_matching_enabled, init_logging, init_telemetry, log_command_execution,
+    record_command_metric, set_exact_matching, set_revision_operation, ClipboardBackend, ClipboardConfig,
+    ClipboardEnvironment, CommandCategory, GateDecision, GateResult,
+    HistoryConfig, HistoryContent, HistoryEntry, HistoryTracker, IoOptions, LogConfig,
+    MatchResult, Matcher, OutputFormat, PerformanceVerifier, Prompt, RedactionAllowList,
+    ResolutionPolicy, RetentionConfig, SecurityScanner, ShortCodeRegistry, ShutdownHandler, Storage,
+    is_file_reference, split_version, with_version,
     TelemetryCollector,
 };
 
@@ -21,7 +25,10 @@ use prompthive::Importer;
 
 // Re-export for command modules
 pub use prompthive::storage::PromptMetadata;
-pub use prompthive::{clean, edit};
+pub use prompthive::{
+    changelog, clean, cloud_sync, edit, file_watcher, merge, patch, redaction, revisions, security,
+    security_policy, signals, similarity, sync_manager, transcript,
+};
 
 #[cfg(feature = "registry")]
 pub use prompthive::{PackagePrompt, PublishRequest};
@@ -39,7 +46,7 @@ use crate::commands::core::{handle_use, handle_show, handle_edit, handle_ls, han
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load environment variables from .env file
-    if let Err(_) = dotenvy::dotenv() {
+    if dotenvy::dotenv().is_err() {
         // .env file not found or readable - this is fine, use system env vars
     }
 
@@ -64,9 +71,9 @@ async fn main() -> Result<()> {
                     let available_commands = vec![
                         "use", "u", "show", "s", "new", "n", "edit", "e", "delete", "d", "rm",
                         "ls", "l", "list", "find", "f", "tui", "t", "compose", "c", "clean", "x",
-                        "diff", "merge", "import", "version", "versions", "rollback", "rename", "r", "mv",
+                        "diff", "merge", "apply", "import", "version", "versions", "changelog", "doctor", "rollback", "rename", "r", "mv",
                         "search", "install", "publish", "sync", "login", "logout", "banks", "init",
-                        "completion", "stats", "config"
+                        "completion", "stats", "config", "alias", "similar", "dedupe", "redact", "extract"
                     ];
                     
                     use crate::error_help;
@@ -93,6 +100,31 @@ async fn main() -> Result<()> {
     let storage = Storage::new()?;
     storage.init()?;
 
+    // Label automatic revisions with the command that made them
+    if let Some(command) = &cli.command {
+        set_revision_operation(command.name());
+    }
+
+    // Exact name resolution can be forced per run (--exact) or in config
+    let matching_config = commands::configuration::load_matching_config().unwrap_or_default();
+    set_exact_matching(cli.exact || matching_config.exact);
+
+    // Machine-readable output for read commands (--json / --output-format)
+    let output_format = match (cli.json, cli.output_format.as_deref()) {
+        (true, _) => OutputFormat::Json,
+        (false, Some(format)) => match format.parse::<OutputFormat>() {
+            Ok(format) => format,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        (false, None) => OutputFormat::Text,
+    };
+    let query_io = IoOptions::default()
+        .with_category(CommandCategory::Query)
+        .with_format(output_format);
+
     // Initialize telemetry only if not in performance mode
     let mut telemetry = if env::var("PROMPTHIVE_PERF_MODE").is_ok() {
         None
@@ -148,6 +180,7 @@ async fn main() -> Result<()> {
             file,
             quiet,
             with_directives,
+            redact,
         } => {
             let io_options = IoOptions::new(
                 save.as_deref(),
@@ -155,7 +188,8 @@ async fn main() -> Result<()> {
                 clipboard,
                 file.as_deref(),
                 quiet,
-            ).with_category(CommandCategory::TextTransform);
+            ).with_category(CommandCategory::TextTransform)
+            .with_redaction(redact);
             let result = handle_use(
                 &storage,
                 &name,
@@ -196,7 +230,8 @@ async fn main() -> Result<()> {
                 clipboard,
                 file.as_deref(),
                 quiet,
-            ).with_category(CommandCategory::TextTransform);
+            ).with_category(CommandCategory::TextTransform)
+            .with_format(output_format);
             let result = handle_show(&storage, &name, edit, &io_options, start);
             let success = result.is_ok();
             let error_type = if let Err(ref e) = result {
@@ -242,11 +277,16 @@ async fn main() -> Result<()> {
             quiet,
         } => {
             let io_options = IoOptions::new(save.as_deref(), append.as_deref(), clipboard, file.as_deref(), quiet)
-                .with_category(CommandCategory::Query);
+                .with_category(CommandCategory::Query)
+                .with_format(output_format);
             handle_find(&storage, &query, &io_options, start)?;
         }
-        Commands::Install { package } => {
-            crate::commands::registry::handle_install(&storage, &package, start).await?;
+        Commands::Install {
+            package,
+            allow_insecure,
+        } => {
+            crate::commands::registry::handle_install(&storage, &package, allow_insecure, start)
+                .await?;
         }
         Commands::Publish {
             name,
@@ -254,6 +294,7 @@ async fn main() -> Result<()> {
             version,
             tags,
             bank,
+            allow_insecure,
         } => {
             crate::commands::registry::handle_publish(
                 &storage,
@@ -262,6 +303,7 @@ async fn main() -> Result<()> {
                 &version,
                 tags.as_deref(),
                 &bank,
+                allow_insecure,
                 start,
             )
             .await?;
@@ -330,7 +372,8 @@ async fn main() -> Result<()> {
             quiet,
         } => {
             let io_options = IoOptions::new(save.as_deref(), append.as_deref(), clipboard, file.as_deref(), quiet)
-                .with_category(CommandCategory::Query);
+                .with_category(CommandCategory::Query)
+                .with_format(output_format);
             handle_ls(&storage, &io_options, start)?;
         }
         Commands::Delete { name } => {
@@ -353,21 +396,47 @@ async fn main() -> Result<()> {
             let mut telemetry = init_telemetry(storage.base_dir().to_path_buf()).ok();
             commands::handle_config(&mut telemetry, &category, &action, start)?;
         }
-        Commands::Security { all: _, format: _, summary: _ } => {
-            // TODO: Implement security scanning
-            eprintln!("Security scanning not yet implemented");
+        Commands::Security {
+            prompt,
+            all,
+            format,
+            summary,
+            fail_on,
+            update_baseline,
+            no_baseline,
+        } => {
+            commands::security::handle_security(
+                &storage,
+                prompt.as_deref(),
+                all,
+                &format,
+                summary,
+                fail_on.as_deref(),
+                update_baseline,
+                no_baseline,
+                start,
+            )?;
         }
         Commands::Stats => {
-            commands::statistics::handle_stats(start)?;
+            commands::statistics::handle_stats(&storage, &query_io, start)?;
         }
         Commands::History {
-            limit: _,
-            search: _,
-            success_only: _,
-            last: _,
+            action,
+            limit,
+            search,
+            success_only,
+            last,
         } => {
-            // TODO: Implement history command
-            eprintln!("History command not yet implemented");
+            commands::history::handle_history(
+                &storage,
+                &action,
+                limit,
+                search.as_deref(),
+                success_only,
+                last,
+                &query_io,
+                start,
+            )?;
         }
         Commands::Batch {
             query: _,
@@ -387,6 +456,8 @@ async fn main() -> Result<()> {
         }
         Commands::Clean {
             text,
+            mode,
+            keep_blank_lines,
             save,
             append,
             clipboard,
@@ -400,13 +471,54 @@ async fn main() -> Result<()> {
                 file.as_deref(),
                 quiet,
             ).with_category(CommandCategory::TextTransform);
-            commands::clean::handle_clean(text.as_deref(), &io_options, start)?;
+            commands::clean::handle_clean(
+                text.as_deref(),
+                &mode,
+                keep_blank_lines,
+                &io_options,
+                start,
+            )?;
+        }
+        Commands::Extract {
+            source,
+            format,
+            role,
+            turn,
+            list,
+            save,
+            append,
+            clipboard,
+            file,
+            quiet,
+        } => {
+            let io_options = IoOptions::new(
+                save.as_deref(),
+                append.as_deref(),
+                clipboard,
+                file.as_deref(),
+                quiet,
+            )
+            .with_category(CommandCategory::TextTransform)
+            .with_format(output_format);
+            commands::extract::handle_extract(
+                source.as_deref(),
+                &format,
+                &role,
+                turn.as_deref(),
+                list,
+                &io_options,
+                start,
+            )?;
         }
         Commands::Diff {
             prompt1,
             prompt2,
             format,
             context,
+            word_diff,
+            metadata,
+            stat,
+            tool,
             output,
         } => {
             commands::handle_diff(
@@ -415,6 +527,10 @@ async fn main() -> Result<()> {
                 &prompt2,
                 &format,
                 context,
+                word_diff,
+                metadata,
+                stat,
+                tool,
                 output.as_deref(),
                 start,
             )?;
@@ -425,16 +541,33 @@ async fn main() -> Result<()> {
             backup,
             preview,
             interactive,
+            no_conflicts,
+            tool,
+            continue_merge,
+            abort,
         } => {
-            commands::handle_merge(
-                &storage,
-                &source,
-                &target,
-                backup,
-                preview,
-                interactive,
-                start,
-            )?;
+            if continue_merge {
+                commands::handle_merge_continue(&storage, start)?;
+            } else if abort {
+                commands::handle_merge_abort(&storage, start)?;
+            } else if let (Some(source), Some(target)) = (source, target) {
+                let options = commands::MergeOptions {
+                    backup,
+                    preview,
+                    interactive,
+                    no_conflicts,
+                    tool,
+                };
+                commands::handle_merge(&storage, &source, &target, options, start)?;
+            }
+        }
+        Commands::Apply {
+            patch,
+            to,
+            interactive,
+            preview,
+        } => {
+            commands::handle_apply(&storage, &patch, to.as_deref(), interactive, preview, start)?;
         }
         Commands::Vars { action: _ } => {
             // TODO: Implement vars command
@@ -450,6 +583,9 @@ async fn main() -> Result<()> {
         Commands::Perf { verify } => {
             commands::statistics::handle_perf(&storage, verify, start)?;
         }
+        Commands::Doctor => {
+            commands::doctor::handle_doctor(&storage, &query_io, start)?;
+        }
         Commands::Health {
             format: _,
             simple: _,
@@ -476,12 +612,40 @@ async fn main() -> Result<()> {
             // TODO: Implement values command
             eprintln!("Values command not yet implemented");
         }
-        Commands::Version { name, tag, message } => {
-            commands::versioning::handle_version(&storage, &name, &tag, message.as_deref(), start)?;
+        Commands::Version {
+            name,
+            tag,
+            bump,
+            message,
+        } => {
+            commands::versioning::handle_version(
+                &storage,
+                &name,
+                tag.as_deref(),
+                bump.as_deref(),
+                message.as_deref(),
+                start,
+            )?;
         }
-        Commands::Versions { name, verbose } => {
-            commands::versioning::handle_versions(&storage, &name, verbose, start)?;
+        Commands::Changelog { name } => {
+            commands::versioning::handle_changelog(&storage, &name, &query_io, start)?;
         }
+        Commands::Versions {
+            name,
+            verbose,
+            action,
+        } => match (action, name) {
+            (Some(commands::versioning::VersionsCommands::Prune { dry_run, orphans }), _) => {
+                commands::versioning::handle_versions_prune(&storage, dry_run, orphans, &query_io, start)?;
+            }
+            (None, Some(name)) => {
+                commands::versioning::handle_versions(&storage, &name, verbose, &query_io, start)?;
+            }
+            (None, None) => {
+                eprintln!("Error: Give a prompt name, or use `ph versions prune`");
+                std::process::exit(1);
+            }
+        },
         Commands::Rollback {
             name,
             version,
@@ -490,7 +654,7 @@ async fn main() -> Result<()> {
             commands::versioning::handle_rollback(&storage, &name, &version, backup, start)?;
         }
         Commands::Sync { action } => {
-            commands::sync::handle_sync(&storage, &action, start).await?;
+            commands::sync::handle_sync(&storage, &action, &query_io, start).await?;
         }
         Commands::Teams { action } => {
             commands::teams::handle_teams(&storage, &action, start).await?;
@@ -501,6 +665,7 @@ async fn main() -> Result<()> {
             invite,
             allow_suggestions,
             expires,
+            allow_insecure,
         } => {
             commands::sharing::handle_share(
                 &storage,
@@ -509,6 +674,7 @@ async fn main() -> Result<()> {
                 invite.as_deref(),
                 allow_suggestions,
                 expires,
+                allow_insecure,
                 start,
             )
             .await?;
@@ -520,8 +686,24 @@ async fn main() -> Result<()> {
         Commands::Improve { action } => {
             commands::improvement::handle_improvement_commands(&storage, &action, start).await?;
         }
+        Commands::Similar {
+            prompt,
+            threshold,
+            limit,
+        } => {
+            commands::similarity::handle_similar(&storage, &prompt, threshold, limit, start)?;
+        }
+        Commands::Dedupe { threshold } => {
+            commands::similarity::handle_dedupe(&storage, threshold, start)?;
+        }
+        Commands::Alias { action } => {
+            commands::aliases::handle_alias(&storage, &action, start)?;
+        }
+        Commands::Redact { action } => {
+            commands::redact::handle_redact(&storage, &action, start)?;
+        }
         Commands::Bank { action } => {
-            commands::banks::handle_banks(&storage, &action, start).await?;
+            commands::banks::handle_banks(&storage, &action, &query_io, start).await?;
         }
         Commands::Users { action } => {
             commands::users::handle_users(&storage, &action, start).await?;
@@ -7,6 +7,51 @@
 use colored::*;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Global switch forcing exact name resolution (`--exact` or `matching.exact`)
+static EXACT_MATCHING: AtomicBool = AtomicBool::new(false);
+
+/// Force exact name resolution for every command in this process
+pub fn set_exact_matching(enabled: bool) {
+    EXACT_MATCHING.store(enabled, Ordering::SeqCst);
+}
+
+/// Check whether exact name resolution has been forced
+pub fn exact_matching_enabled() -> bool {
+    EXACT_MATCHING.load(Ordering::SeqCst)
+}
+
+/// How strictly a query has to match before a command acts on a prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionPolicy {
+    /// Accept the best fuzzy match
+    Fuzzy,
+    /// Require an exact name, but offer fuzzy candidates for confirmation
+    Confirm,
+    /// Require an exact name and never act on a fuzzy candidate
+    Exact,
+}
+
+impl ResolutionPolicy {
+    /// Policy for commands that only read prompts (use, show, diff)
+    pub fn for_read() -> Self {
+        if exact_matching_enabled() {
+            ResolutionPolicy::Exact
+        } else {
+            ResolutionPolicy::Fuzzy
+        }
+    }
+
+    /// Policy for commands that overwrite or delete prompts (delete, rename, merge, rollback)
+    pub fn for_write() -> Self {
+        if exact_matching_enabled() {
+            ResolutionPolicy::Exact
+        } else {
+            ResolutionPolicy::Confirm
+        }
+    }
+}
 
 /// A prompt with associated metadata for matching and display
 #[derive(Debug, Clone)]
@@ -88,7 +133,7 @@ impl Matcher {
             .collect();
 
         // Sort by enhanced score (highest first)
-        matches.sort_by(|a, b| b.1.cmp(&a.1));
+        matches.sort_by_key(|m| std::cmp::Reverse(m.1));
 
         // Deduplicate by name (keep highest scoring version)
         matches.dedup_by(|a, b| a.0.name == b.0.name);
@@ -113,6 +158,32 @@ impl Matcher {
         }
     }
 
+    /// Resolve a query according to a resolution policy
+    ///
+    /// With [`ResolutionPolicy::Fuzzy`] this behaves like [`Matcher::find`]. The
+    /// stricter policies only return `Exact` for an exact name or persisted short
+    /// code; any fuzzy hits are returned as `Multiple` candidates so the caller can
+    /// confirm or refuse.
+    pub fn resolve(&self, query: &str, policy: ResolutionPolicy) -> MatchResult {
+        if policy == ResolutionPolicy::Fuzzy {
+            return self.find(query);
+        }
+
+        if let Some(prompt) = self
+            .prompts
+            .iter()
+            .find(|p| p.name == query)
+            .or_else(|| self.prompts.iter().find(|p| p.short_code == query))
+        {
+            return MatchResult::Exact(prompt.clone());
+        }
+
+        match self.find(query) {
+            MatchResult::Exact(prompt) => MatchResult::Multiple(vec![prompt]),
+            other => other,
+        }
+    }
+
     /// Calculate enhanced score for better fuzzy matching
     /// Combines fuzzy score with additional heuristics for relevance
     fn calculate_enhanced_score(&self, prompt_name: &str, query: &str, fuzzy_score: i64) -> i64 {
@@ -286,6 +357,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_strict_resolution_never_returns_fuzzy_match() {
+        let prompts = vec![Prompt {
+            name: "auth-basic".to_string(),
+            short_code: "ab".to_string(),
+            description: "Basic auth".to_string(),
+            version: None,
+            created_at: None,
+            updated_at: None,
+            git_hash: None,
+        }];
+
+        let matcher = Matcher::new(prompts);
+        assert!(matches!(
+            matcher.resolve("auth-basic", ResolutionPolicy::Exact),
+            MatchResult::Exact(_)
+        ));
+        assert!(matches!(
+            matcher.resolve("ab", ResolutionPolicy::Exact),
+            MatchResult::Exact(_)
+        ));
+        assert!(matches!(
+            matcher.resolve("authb", ResolutionPolicy::Fuzzy),
+            MatchResult::Exact(_)
+        ));
+        match matcher.resolve("authb", ResolutionPolicy::Confirm) {
+            MatchResult::Multiple(candidates) => assert_eq!(candidates[0].name, "auth-basic"),
+            _ => panic!("Expected fuzzy candidate, not an exact match"),
+        }
+        assert!(matches!(
+            matcher.resolve("zzz", ResolutionPolicy::Exact),
+            MatchResult::None
+        ));
+    }
+
     #[test]
     fn test_short_code_generation() {
         let existing = vec!["a".to_string(), "ap".to_string()];
@@ -0,0 +1,266 @@
+//! Line diffs and merges for prompt bodies
+//!
+//! [`diff_lines`] is a longest-common-subsequence diff. [`merge3`] merges two
+//! descendants of a common ancestor the way `diff3` does: regions changed on
+//! one side only are taken from that side, and regions changed differently on
+//! both sides become git-style conflicts. [`merge2`] is the fallback when no
+//! ancestor is known.
+
+/// One step of an edit script turning `old` into `new`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp {
+    Equal { old: usize, new: usize },
+    Delete { old: usize },
+    Insert { new: usize },
+}
+
+/// Shortest edit script between two line lists
+pub fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    // Common prefix and suffix need no table
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+    let width = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<DiffOp> = (0..prefix).map(|i| DiffOp::Equal { old: i, new: i }).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            ops.push(DiffOp::Equal {
+                old: prefix + i,
+                new: prefix + j,
+            });
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+            // Removals come before additions, as in unified diffs
+            ops.push(DiffOp::Delete { old: prefix + i });
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert { new: prefix + j });
+            j += 1;
+        }
+    }
+    ops.extend((0..suffix).map(|k| DiffOp::Equal {
+        old: old.len() - suffix + k,
+        new: new.len() - suffix + k,
+    }));
+    ops
+}
+
+/// Result of merging prompt bodies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeResult {
+    pub content: String,
+    /// Number of conflict regions marked in `content`
+    pub conflicts: usize,
+}
+
+/// Labels written after `<<<<<<<` and `>>>>>>>` in conflicts
+#[derive(Debug, Clone, Copy)]
+pub struct MergeLabels<'a> {
+    pub ours: &'a str,
+    pub theirs: &'a str,
+}
+
+struct Output<'a> {
+    lines: Vec<String>,
+    conflicts: usize,
+    labels: MergeLabels<'a>,
+}
+
+impl Output<'_> {
+    fn take(&mut self, lines: &[&str]) {
+        self.lines.extend(lines.iter().map(|l| l.to_string()));
+    }
+
+    fn conflict(&mut self, ours: &[&str], theirs: &[&str]) {
+        self.conflicts += 1;
+        self.lines.push(format!("<<<<<<< {}", self.labels.ours));
+        self.take(ours);
+        self.lines.push("=======".to_string());
+        self.take(theirs);
+        self.lines.push(format!(">>>>>>> {}", self.labels.theirs));
+    }
+
+    fn finish(self, trailing_newline: bool) -> MergeResult {
+        let mut content = self.lines.join("\n");
+        if trailing_newline && !content.is_empty() {
+            content.push('\n');
+        }
+        MergeResult {
+            content,
+            conflicts: self.conflicts,
+        }
+    }
+}
+
+/// For each line of `base`, the line of `other` it is kept as, if any
+fn matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut map = vec![None; base.len()];
+    for op in diff_lines(base, other) {
+        if let DiffOp::Equal { old, new } = op {
+            map[old] = Some(new);
+        }
+    }
+    map
+}
+
+/// Three-way merge of `ours` and `theirs`, which both descend from `base`
+pub fn merge3(base: &str, ours: &str, theirs: &str, labels: MergeLabels) -> MergeResult {
+    let base_lines: Vec<&str> = base.lines().collect();
+    let our_lines: Vec<&str> = ours.lines().collect();
+    let their_lines: Vec<&str> = theirs.lines().collect();
+    let ours_at = matches(&base_lines, &our_lines);
+    let theirs_at = matches(&base_lines, &their_lines);
+
+    let mut out = Output {
+        lines: Vec::new(),
+        conflicts: 0,
+        labels,
+    };
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    loop {
+        // Next base line that both sides kept is a stable point
+        let stable = (b..base_lines.len())
+            .find_map(|i| Some((i, ours_at[i]?, theirs_at[i]?)));
+        let (end_b, end_o, end_t) = stable.unwrap_or((base_lines.len(), our_lines.len(), their_lines.len()));
+
+        let base_chunk = &base_lines[b..end_b];
+        let our_chunk = &our_lines[o..end_o];
+        let their_chunk = &their_lines[t..end_t];
+        if our_chunk == their_chunk || their_chunk == base_chunk {
+            out.take(our_chunk);
+        } else if our_chunk == base_chunk {
+            out.take(their_chunk);
+        } else {
+            out.conflict(our_chunk, their_chunk);
+        }
+
+        match stable {
+            Some((i, oi, ti)) => {
+                out.take(&[base_lines[i]]);
+                b = i + 1;
+                o = oi + 1;
+                t = ti + 1;
+            }
+            None => break,
+        }
+    }
+    out.finish(ours.ends_with('\n'))
+}
+
+/// Merge without a common ancestor
+///
+/// Lines only one side has are kept (additions from either side survive);
+/// regions where the sides replaced each other's lines become conflicts.
+pub fn merge2(ours: &str, theirs: &str, labels: MergeLabels) -> MergeResult {
+    let our_lines: Vec<&str> = ours.lines().collect();
+    let their_lines: Vec<&str> = theirs.lines().collect();
+    let mut out = Output {
+        lines: Vec::new(),
+        conflicts: 0,
+        labels,
+    };
+
+    let mut only_ours = Vec::new();
+    let mut only_theirs = Vec::new();
+    let flush = |out: &mut Output, only_ours: &mut Vec<&str>, only_theirs: &mut Vec<&str>| {
+        if !only_ours.is_empty() && !only_theirs.is_empty() {
+            out.conflict(only_ours, only_theirs);
+        } else {
+            out.take(only_ours);
+            out.take(only_theirs);
+        }
+        only_ours.clear();
+        only_theirs.clear();
+    };
+    for op in diff_lines(&our_lines, &their_lines) {
+        match op {
+            DiffOp::Delete { old } => only_ours.push(our_lines[old]),
+            DiffOp::Insert { new } => only_theirs.push(their_lines[new]),
+            DiffOp::Equal { old, .. } => {
+                flush(&mut out, &mut only_ours, &mut only_theirs);
+                out.take(&[our_lines[old]]);
+            }
+        }
+    }
+    flush(&mut out, &mut only_ours, &mut only_theirs);
+    out.finish(ours.ends_with('\n'))
+}
+
+/// Whether text still contains unresolved conflict markers
+pub fn has_conflict_markers(text: &str) -> bool {
+    text.lines().any(|line| {
+        line.starts_with("<<<<<<< ") || line == "=======" || line.starts_with(">>>>>>> ")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LABELS: MergeLabels = MergeLabels {
+        ours: "target",
+        theirs: "source",
+    };
+
+    #[test]
+    fn test_diff_lines_edit_script() {
+        let old = ["a", "b", "c", "d"];
+        let new = ["a", "x", "c", "d", "e"];
+        let ops = diff_lines(&old, &new);
+        let equal = ops.iter().filter(|op| matches!(op, DiffOp::Equal { .. })).count();
+        assert_eq!(equal, 3);
+        assert!(ops.contains(&DiffOp::Delete { old: 1 }));
+        assert!(ops.contains(&DiffOp::Insert { new: 1 }));
+        assert!(ops.contains(&DiffOp::Insert { new: 4 }));
+    }
+
+    #[test]
+    fn test_merge3_takes_one_sided_changes_and_marks_conflicts() {
+        let base = "intro\nstep one\nstep two\noutro";
+        let ours = "intro\nstep one (ours)\nstep two\noutro";
+        let theirs = "intro\nstep one\nstep two\noutro\nappendix";
+        let merged = merge3(base, ours, theirs, LABELS);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.content, "intro\nstep one (ours)\nstep two\noutro\nappendix");
+
+        let theirs = "intro\nstep one (theirs)\nstep two\noutro";
+        let merged = merge3(base, ours, theirs, LABELS);
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.content,
+            "intro\n<<<<<<< target\nstep one (ours)\n=======\nstep one (theirs)\n>>>>>>> source\nstep two\noutro"
+        );
+        assert!(has_conflict_markers(&merged.content));
+    }
+
+    #[test]
+    fn test_merge2_keeps_additions_from_both_sides() {
+        let merged = merge2("a\nb\nc", "a\nnew\nb\nc\nd", LABELS);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.content, "a\nnew\nb\nc\nd");
+
+        let merged = merge2("a\nmine\nc", "a\nyours\nc", LABELS);
+        assert_eq!(merged.conflicts, 1);
+    }
+}
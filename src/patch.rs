@@ -0,0 +1,491 @@
+//! Unified diff hunks and patches
+//!
+//! [`hunks`] groups a line diff into `@@` hunks with surrounding context. They
+//! are what `ph diff` prints, what `ph merge --interactive` asks about one at a
+//! time, and what [`parse_patch`] reads back from a saved diff so `ph apply`
+//! can replay them with [`apply_hunks`]. [`diff_words`] refines changed lines
+//! into word-level spans for `ph diff --word-diff`.
+
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+
+use crate::merge::{diff_lines, DiffOp};
+
+/// One line of a hunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Delete(String),
+    Insert(String),
+}
+
+impl HunkLine {
+    /// Parse a hunk line by its ` `, `-` or `+` prefix
+    ///
+    /// An empty line is an empty context line; editors often strip the space.
+    pub fn parse(line: &str) -> Option<HunkLine> {
+        match line.chars().next() {
+            None => Some(HunkLine::Context(String::new())),
+            Some(' ') => Some(HunkLine::Context(line[1..].to_string())),
+            Some('-') => Some(HunkLine::Delete(line[1..].to_string())),
+            Some('+') => Some(HunkLine::Insert(line[1..].to_string())),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            HunkLine::Context(text) | HunkLine::Delete(text) | HunkLine::Insert(text) => text,
+        }
+    }
+
+    pub fn is_change(&self) -> bool {
+        !matches!(self, HunkLine::Context(_))
+    }
+
+    /// The line as it appears in a unified diff
+    pub fn render(&self) -> String {
+        let prefix = match self {
+            HunkLine::Context(_) => ' ',
+            HunkLine::Delete(_) => '-',
+            HunkLine::Insert(_) => '+',
+        };
+        format!("{}{}", prefix, self.text())
+    }
+}
+
+/// A run of changes with the unchanged lines around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// Zero-based line of the old text where the hunk starts
+    pub old_start: usize,
+    /// Zero-based line of the new text where the hunk starts
+    pub new_start: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+/// `start,len` of a hunk header; empty ranges name the line before them
+fn header_range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{},0", start)
+    } else {
+        format!("{},{}", start + 1, len)
+    }
+}
+
+impl Hunk {
+    /// Lines the hunk expects in the old text
+    pub fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter(|line| !matches!(line, HunkLine::Insert(_)))
+            .map(HunkLine::text)
+            .collect()
+    }
+
+    /// Lines the hunk leaves in the new text
+    pub fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter(|line| !matches!(line, HunkLine::Delete(_)))
+            .map(HunkLine::text)
+            .collect()
+    }
+
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{} +{} @@",
+            header_range(self.old_start, self.old_lines().len()),
+            header_range(self.new_start, self.new_lines().len())
+        )
+    }
+
+    /// Header and lines, newline-terminated
+    pub fn render(&self) -> String {
+        let mut text = self.header();
+        text.push('\n');
+        for line in &self.lines {
+            text.push_str(&line.render());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Split into one hunk per run of changes
+    ///
+    /// Context between two runs is kept on both sides, as `git add -p` does;
+    /// [`apply_hunks`] accepts hunks that share context with the previous one.
+    pub fn split(&self) -> Vec<Hunk> {
+        let mut runs = Vec::new();
+        let mut i = 0;
+        while i < self.lines.len() {
+            if self.lines[i].is_change() {
+                let start = i;
+                while i < self.lines.len() && self.lines[i].is_change() {
+                    i += 1;
+                }
+                runs.push((start, i));
+            } else {
+                i += 1;
+            }
+        }
+        if runs.len() < 2 {
+            return vec![self.clone()];
+        }
+
+        // Old and new line numbers in front of each hunk line
+        let mut positions = Vec::with_capacity(self.lines.len());
+        let (mut old, mut new) = (self.old_start, self.new_start);
+        for line in &self.lines {
+            positions.push((old, new));
+            match line {
+                HunkLine::Context(_) => {
+                    old += 1;
+                    new += 1;
+                }
+                HunkLine::Delete(_) => old += 1,
+                HunkLine::Insert(_) => new += 1,
+            }
+        }
+
+        (0..runs.len())
+            .map(|k| {
+                let from = if k == 0 { 0 } else { runs[k - 1].1 };
+                let to = runs.get(k + 1).map_or(self.lines.len(), |next| next.0);
+                let (old_start, new_start) = positions[from];
+                Hunk {
+                    old_start,
+                    new_start,
+                    lines: self.lines[from..to].to_vec(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Hunks turning `old` into `new`, with `context` unchanged lines around changes
+pub fn hunks(old: &str, new: &str, context: usize) -> Vec<Hunk> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    // Old and new line numbers in front of each op
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut o, mut n) = (0, 0);
+    for op in &ops {
+        positions.push((o, n));
+        match op {
+            DiffOp::Equal { .. } => {
+                o += 1;
+                n += 1;
+            }
+            DiffOp::Delete { .. } => o += 1,
+            DiffOp::Insert { .. } => n += 1,
+        }
+    }
+
+    // Changes closer than two contexts apart share a hunk
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        if matches!(op, DiffOp::Equal { .. }) {
+            continue;
+        }
+        match groups.last_mut() {
+            Some((_, last)) if i - *last <= 2 * context + 1 => *last = i,
+            _ => groups.push((i, i)),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(first, last)| {
+            let from = first.saturating_sub(context);
+            let to = (last + context + 1).min(ops.len());
+            let (old_start, new_start) = positions[from];
+            let lines = ops[from..to]
+                .iter()
+                .map(|op| match *op {
+                    DiffOp::Equal { old, .. } => HunkLine::Context(old_lines[old].to_string()),
+                    DiffOp::Delete { old } => HunkLine::Delete(old_lines[old].to_string()),
+                    DiffOp::Insert { new } => HunkLine::Insert(new_lines[new].to_string()),
+                })
+                .collect();
+            Hunk {
+                old_start,
+                new_start,
+                lines,
+            }
+        })
+        .collect()
+}
+
+/// Where `expected` occurs in `lines` at or after `min`, nearest to `hint`
+fn locate(lines: &[&str], expected: &[&str], hint: usize, min: usize) -> Option<usize> {
+    let last = lines.len().checked_sub(expected.len())?;
+    (min..=last)
+        .filter(|&at| lines[at..at + expected.len()] == *expected)
+        .min_by_key(|&at| at.abs_diff(hint))
+}
+
+/// Apply hunks, in order, to `old`
+///
+/// Each hunk is placed where its context and removed lines match, preferring
+/// the line its header names, so patches still apply after unrelated edits
+/// shifted the text.
+pub fn apply_hunks(old: &str, hunks: &[Hunk]) -> Result<String> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let mut output: Vec<&str> = Vec::new();
+    let mut cursor: usize = 0;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let expected = hunk.old_lines();
+        let leading = hunk.lines.iter().take_while(|line| !line.is_change()).count();
+        let at = locate(
+            &old_lines,
+            &expected,
+            hunk.old_start,
+            cursor.saturating_sub(leading),
+        )
+        .ok_or_else(|| anyhow!("Hunk #{} ({}) does not apply", index + 1, hunk.header()))?;
+
+        // Context shared with the previous hunk is already in the output
+        let overlap = cursor.saturating_sub(at);
+        if at > cursor {
+            output.extend(&old_lines[cursor..at]);
+        }
+        for line in hunk.lines.iter().skip(overlap) {
+            match line {
+                HunkLine::Context(text) | HunkLine::Insert(text) => output.push(text),
+                HunkLine::Delete(_) => {}
+            }
+        }
+        cursor = at + expected.len();
+    }
+    output.extend(&old_lines[cursor.min(old_lines.len())..]);
+
+    let mut content = output.join("\n");
+    if (old.ends_with('\n') || old.is_empty()) && !content.is_empty() {
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+/// A piece of a word-level diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordSpan {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Words, runs of whitespace and single punctuation marks
+fn words(text: &str) -> Vec<&str> {
+    let token = Regex::new(r"\w+|\s+|[^\w\s]").unwrap();
+    token.find_iter(text).map(|m| m.as_str()).collect()
+}
+
+/// Word-level diff of two pieces of text
+///
+/// Removals come before additions within each changed stretch, and a space
+/// between two changed words stays inside the change rather than splitting it.
+pub fn diff_words(old: &str, new: &str) -> Vec<WordSpan> {
+    let old_words = words(old);
+    let new_words = words(new);
+    let ops = diff_lines(&old_words, &new_words);
+
+    let mut spans = Vec::new();
+    let (mut removed, mut added) = (String::new(), String::new());
+    let flush = |spans: &mut Vec<WordSpan>, removed: &mut String, added: &mut String| {
+        if !removed.is_empty() {
+            spans.push(WordSpan::Removed(std::mem::take(removed)));
+        }
+        if !added.is_empty() {
+            spans.push(WordSpan::Added(std::mem::take(added)));
+        }
+    };
+    for (i, op) in ops.iter().enumerate() {
+        match *op {
+            DiffOp::Delete { old } => removed.push_str(old_words[old]),
+            DiffOp::Insert { new } => added.push_str(new_words[new]),
+            DiffOp::Equal { old, .. } => {
+                let text = old_words[old];
+                let more_changes = matches!(
+                    ops.get(i + 1),
+                    Some(DiffOp::Delete { .. } | DiffOp::Insert { .. })
+                );
+                if text.trim().is_empty() && more_changes && !removed.is_empty() && !added.is_empty() {
+                    removed.push_str(text);
+                    added.push_str(text);
+                    continue;
+                }
+                flush(&mut spans, &mut removed, &mut added);
+                match spans.last_mut() {
+                    Some(WordSpan::Same(same)) => same.push_str(text),
+                    _ => spans.push(WordSpan::Same(text.to_string())),
+                }
+            }
+        }
+    }
+    flush(&mut spans, &mut removed, &mut added);
+    spans
+}
+
+/// A unified diff of one prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    /// Name on the `---` line
+    pub old_name: Option<String>,
+    /// Name on the `+++` line
+    pub new_name: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl Patch {
+    /// Patch turning `old` into `new`
+    pub fn between(old_name: &str, old: &str, new_name: &str, new: &str, context: usize) -> Self {
+        Patch {
+            old_name: Some(old_name.to_string()),
+            new_name: Some(new_name.to_string()),
+            hunks: hunks(old, new, context),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut text = format!(
+            "--- {}\n+++ {}\n",
+            self.old_name.as_deref().unwrap_or("a"),
+            self.new_name.as_deref().unwrap_or("b")
+        );
+        for hunk in &self.hunks {
+            text.push_str(&hunk.render());
+        }
+        text
+    }
+}
+
+/// Parse `start` or `start,len` from a hunk header into a zero-based start and length
+fn parse_range(start: &str, len: Option<&str>) -> Result<(usize, usize)> {
+    let start: usize = start.parse()?;
+    let len: usize = len.map_or(Ok(1), str::parse)?;
+    Ok((if len == 0 { start } else { start.saturating_sub(1) }, len))
+}
+
+/// Read a unified diff, such as one saved from `ph diff`
+///
+/// Colour codes are ignored, and so is any text after the last hunk.
+pub fn parse_patch(text: &str) -> Result<Patch> {
+    let ansi = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+    let header = Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@").unwrap();
+    let text = ansi.replace_all(text, "");
+    let mut lines = text.lines().enumerate();
+
+    let mut patch = Patch {
+        old_name: None,
+        new_name: None,
+        hunks: Vec::new(),
+    };
+    let file_name = |rest: &str| rest.split('\t').next().unwrap_or(rest).trim().to_string();
+
+    while let Some((number, line)) = lines.next() {
+        if let Some(rest) = line.strip_prefix("--- ") {
+            if !patch.hunks.is_empty() {
+                bail!("Line {}: patch changes more than one prompt", number + 1);
+            }
+            patch.old_name = Some(file_name(rest));
+        } else if let Some(rest) = line.strip_prefix("+++ ") {
+            patch.new_name = Some(file_name(rest));
+        } else if let Some(captures) = header.captures(line) {
+            let (old_start, mut old_left) =
+                parse_range(&captures[1], captures.get(2).map(|m| m.as_str()))?;
+            let (new_start, mut new_left) =
+                parse_range(&captures[3], captures.get(4).map(|m| m.as_str()))?;
+            let mut hunk = Hunk {
+                old_start,
+                new_start,
+                lines: Vec::new(),
+            };
+            while old_left > 0 || new_left > 0 {
+                let Some((number, line)) = lines.next() else {
+                    bail!("Patch ends in the middle of hunk {}", hunk.header());
+                };
+                if line.starts_with('\\') {
+                    // "\ No newline at end of file"
+                    continue;
+                }
+                let parsed = HunkLine::parse(line)
+                    .ok_or_else(|| anyhow!("Line {}: not a diff line: {}", number + 1, line))?;
+                match parsed {
+                    HunkLine::Context(_) => {
+                        old_left = old_left.saturating_sub(1);
+                        new_left = new_left.saturating_sub(1);
+                    }
+                    HunkLine::Delete(_) => old_left = old_left.saturating_sub(1),
+                    HunkLine::Insert(_) => new_left = new_left.saturating_sub(1),
+                }
+                hunk.lines.push(parsed);
+            }
+            patch.hunks.push(hunk);
+        }
+    }
+
+    if patch.hunks.is_empty() {
+        bail!("No hunks found in patch");
+    }
+    Ok(patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\n";
+    const NEW: &str = "one\n2\nthree\nfour\nfive\nsix\n7\neight\nnine\n";
+
+    #[test]
+    fn test_hunks_round_trip_through_patch() {
+        let patch = Patch::between("old", OLD, "new", NEW, 1);
+        assert_eq!(patch.hunks.len(), 2);
+        assert_eq!(patch.hunks[0].header(), "@@ -1,3 +1,3 @@");
+
+        let parsed = parse_patch(&patch.render()).unwrap();
+        assert_eq!(parsed, patch);
+        assert_eq!(apply_hunks(OLD, &parsed.hunks).unwrap(), NEW);
+
+        // Hunks still apply when unrelated lines were added above them
+        let shifted = format!("zero\n{}", OLD);
+        assert_eq!(apply_hunks(&shifted, &parsed.hunks).unwrap(), format!("zero\n{}", NEW));
+        assert!(apply_hunks("unrelated\n", &parsed.hunks).is_err());
+    }
+
+    #[test]
+    fn test_diff_words_keeps_changed_phrases_together() {
+        let spans = diff_words("Be brief and polite.", "Be thorough and kind, always.");
+        assert_eq!(
+            spans,
+            vec![
+                WordSpan::Same("Be ".to_string()),
+                WordSpan::Removed("brief".to_string()),
+                WordSpan::Added("thorough".to_string()),
+                WordSpan::Same(" and ".to_string()),
+                WordSpan::Removed("polite".to_string()),
+                WordSpan::Added("kind, always".to_string()),
+                WordSpan::Same(".".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_hunks_apply_independently() {
+        let hunk = hunks(OLD, NEW, 3).remove(0);
+        let parts = hunk.split();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(apply_hunks(OLD, &parts).unwrap(), NEW);
+
+        // Accept only the first and last change
+        let picked = [parts[0].clone(), parts[2].clone()];
+        assert_eq!(
+            apply_hunks(OLD, &picked).unwrap(),
+            "one\n2\nthree\nfour\nfive\nsix\nseven\neight\nnine\n"
+        );
+    }
+}
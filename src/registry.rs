@@ -534,6 +534,7 @@ impl RegistryClient {
         storage: &Storage,
         package_name: &str,
         version: Option<&str>,
+        allow_insecure: bool,
     ) -> Result<InstallResult> {
         // Download package
         let package = self.download(package_name, version).await?;
@@ -541,6 +542,32 @@ impl RegistryClient {
         let mut installed = Vec::new();
         let mut conflicts = Vec::new();
 
+        // Security gate: a package with a Critical finding is not installed at all
+        let scanner = crate::SecurityScanner::for_library(storage.base_dir())?;
+        let mut blocked = Vec::new();
+        for prompt in &package.prompts {
+            let gate = scanner.gate("install", &prompt.name, &prompt.content, allow_insecure);
+            if gate.decision == crate::GateDecision::Blocked {
+                blocked.push(BlockedPrompt {
+                    name: prompt.name.clone(),
+                    findings: gate
+                        .blocking_issues()
+                        .iter()
+                        .map(|issue| issue.description.clone())
+                        .collect(),
+                });
+            }
+        }
+        if !blocked.is_empty() {
+            return Ok(InstallResult {
+                package_name: package.metadata.name,
+                version: package.metadata.version,
+                installed,
+                conflicts,
+                blocked,
+            });
+        }
+
         // Install each prompt
         for prompt in &package.prompts {
             let prompt_path = storage.prompt_path(&prompt.name);
@@ -561,6 +588,7 @@ impl RegistryClient {
                 version: Some(package.metadata.version.clone()),
                 git_hash: None,
                 parent_version: None,
+                source: None,
             };
 
             // Parse prompt content to extract body (skip frontmatter if present)
@@ -589,6 +617,7 @@ impl RegistryClient {
             version: package.metadata.version,
             installed,
             conflicts,
+            blocked: Vec::new(),
         })
     }
 
@@ -1201,6 +1230,15 @@ pub struct InstallResult {
     pub version: String,
     pub installed: Vec<String>,
     pub conflicts: Vec<String>,
+    /// Prompts that failed the security gate; nothing is installed when set
+    pub blocked: Vec<BlockedPrompt>,
+}
+
+/// A package prompt stopped by the security gate
+#[derive(Debug, Clone)]
+pub struct BlockedPrompt {
+    pub name: String,
+    pub findings: Vec<String>,
 }
 
 impl InstallResult {
@@ -1226,6 +1264,21 @@ impl InstallResult {
             }
             println!("\nUse --force to overwrite existing prompts");
         }
+
+        if !self.blocked.is_empty() {
+            println!(
+                "🛑 Package {}@{} was not installed: security scan found critical issues",
+                self.package_name.bold(),
+                self.version.dimmed()
+            );
+            for prompt in &self.blocked {
+                println!("  ✗ {}", prompt.name.red());
+                for finding in &prompt.findings {
+                    println!("      {}", finding);
+                }
+            }
+            println!("\nUse --allow-insecure to install anyway");
+        }
     }
 }
 
@@ -1374,6 +1427,7 @@ mod tests {
             version: "1.0.0".to_string(),
             installed: vec!["prompt1".to_string(), "prompt2".to_string()],
             conflicts: vec!["existing-prompt".to_string()],
+            blocked: Vec::new(),
         };
 
         assert_eq!(result.installed.len(), 2);
@@ -1396,6 +1450,7 @@ mod tests {
             version: None,
             git_hash: None,
             parent_version: None,
+            source: None,
         };
         storage
             .write_prompt("test-prompt-1", &metadata1, "Content 1")
@@ -1410,6 +1465,7 @@ mod tests {
             version: None,
             git_hash: None,
             parent_version: None,
+            source: None,
         };
         storage
             .write_prompt("test-prompt-2", &metadata2, "Content 2")
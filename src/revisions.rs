@@ -0,0 +1,1027 @@
+//! Automatic revision history for prompts
+//!
+//! Every write through [`Storage`] records a revision: the full prompt file is
+//! stored once as a sha256-addressed blob under `objects/`, and an entry is
+//! appended to the prompt's log in `revisions/<prompt file>/log.jsonl`.
+//! Writing identical content twice adds nothing. Tags (`ph version`) are names
+//! in `tags.json` that point at revision numbers.
+//!
+//! A prompt's history is keyed by its file path relative to the base
+//! directory (`prompts/review.md`, `banks/essentials/commit.md`,
+//! `teams/acme/review.md`), so root, bank and team prompts share one layout
+//! and a bank prompt `ops/deploy` never collides with prompts under `ops/deploy/`.
+//! Renames move the history along with the prompt. History already sitting at
+//! the destination belonged to a deleted prompt; it is set aside under
+//! `revisions/archive/<time>/` and shows up as orphaned until pruned.
+//!
+//! Histories from before revisions existed (`<name>.versions/<tag>.md` full
+//! copies) are migrated into the store the first time a prompt's history is
+//! opened.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::storage::PromptMetadata;
+use crate::Storage;
+
+static OPERATION: OnceLock<String> = OnceLock::new();
+
+/// Label revisions recorded by this process (normally the command name)
+pub fn set_revision_operation(operation: &str) {
+    let _ = OPERATION.set(operation.to_string());
+}
+
+/// Operation recorded for writes made by this process
+pub fn current_operation() -> &'static str {
+    OPERATION.get().map(String::as_str).unwrap_or("write")
+}
+
+/// sha256 of prompt content, hex encoded
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// One entry in a prompt's revision log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    /// 1-based, increasing with every change
+    pub number: u64,
+    /// sha256 of the prompt file, naming its blob
+    pub hash: String,
+    pub created_at: String,
+    /// Command that made the change (edit, append, import, ...)
+    pub operation: String,
+    pub size: usize,
+}
+
+impl Revision {
+    /// Short display form, e.g. `r4`
+    pub fn label(&self) -> String {
+        format!("r{}", self.number)
+    }
+
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(8)]
+    }
+}
+
+/// A name pointing at a revision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub revision: u64,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Blob store and per-prompt revision logs under the storage base directory
+pub struct RevisionStore {
+    storage: Storage,
+}
+
+impl RevisionStore {
+    pub fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+
+    fn objects_dir(&self) -> PathBuf {
+        self.storage.base_dir().join("objects")
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.objects_dir().join(&hash[..2]).join(&hash[2..])
+    }
+
+    /// Canonical identity of a prompt: its file path relative to the base directory
+    pub fn key(&self, name: &str) -> PathBuf {
+        let prompt_path = self.storage.prompt_path(name);
+        match prompt_path.strip_prefix(self.storage.base_dir()) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => PathBuf::from(prompt_path.file_name().unwrap_or_default()),
+        }
+    }
+
+    /// Directory holding the log and tags of one prompt
+    pub fn history_dir(&self, name: &str) -> PathBuf {
+        self.storage.base_dir().join("revisions").join(self.key(name))
+    }
+
+    /// Move a prompt's history to a new name (rename, or a move between banks)
+    pub fn move_history(&self, old: &str, new: &str) -> Result<()> {
+        self.migrate_legacy(old)?;
+        let from = self.history_dir(old);
+        let to = self.history_dir(new);
+        if !from.exists() || from == to {
+            return Ok(());
+        }
+        self.replace_history(&from, &to)
+    }
+
+    /// Move the histories of every prompt in a bank when the bank is renamed
+    pub fn rename_bank(&self, old_bank: &str, new_bank: &str) -> Result<()> {
+        let banks = self.storage.base_dir().join("revisions").join("banks");
+        let from = banks.join(old_bank);
+        let to = banks.join(new_bank);
+        if !from.exists() {
+            return Ok(());
+        }
+        if !to.exists() {
+            fs::rename(&from, &to)?;
+            return Ok(());
+        }
+
+        // A deleted bank of the same name left history behind: move prompt by prompt
+        let dirs: Vec<PathBuf> = walkdir::WalkDir::new(&from)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name() == "log.jsonl")
+            .filter_map(|entry| entry.path().parent().map(Path::to_path_buf))
+            .collect();
+        for dir in dirs {
+            let relative = dir.strip_prefix(&from)?;
+            self.replace_history(&dir, &to.join(relative))?;
+        }
+        for entry in walkdir::WalkDir::new(&from).contents_first(true) {
+            // Only empty directories are left; anything else stays put
+            let _ = fs::remove_dir(entry?.path());
+        }
+        Ok(())
+    }
+
+    /// Move a history directory, archiving whatever history is at the destination
+    fn replace_history(&self, from: &Path, to: &Path) -> Result<()> {
+        if to.exists() {
+            let root = self.storage.base_dir().join("revisions");
+            let key = to.strip_prefix(&root)?;
+            let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.6f").to_string();
+            let archived = root.join("archive").join(stamp).join(key);
+            fs::create_dir_all(archived.parent().unwrap())?;
+            fs::rename(to, &archived)?;
+        }
+        fs::create_dir_all(to.parent().unwrap())?;
+        fs::rename(from, to)?;
+        Ok(())
+    }
+
+    /// Store content as a blob, returning its hash; existing blobs are reused
+    pub fn store_blob(&self, content: &str) -> Result<String> {
+        let hash = content_hash(content);
+        let path = self.blob_path(&hash);
+        if !path.exists() {
+            let dir = path.parent().unwrap();
+            fs::create_dir_all(dir)?;
+            // Write then rename so a crash never leaves a truncated blob
+            let temp = dir.join(format!(".{}.tmp", &hash[2..]));
+            fs::write(&temp, content)?;
+            fs::rename(&temp, &path)?;
+        }
+        Ok(hash)
+    }
+
+    pub fn read_blob(&self, hash: &str) -> Result<String> {
+        fs::read_to_string(self.blob_path(hash))
+            .with_context(|| format!("Missing revision blob {}", hash))
+    }
+
+    /// All revisions of a prompt, oldest first
+    pub fn log(&self, name: &str) -> Result<Vec<Revision>> {
+        self.migrate_legacy(name)?;
+        self.read_log(name)
+    }
+
+    fn read_log(&self, name: &str) -> Result<Vec<Revision>> {
+        read_log_in(&self.history_dir(name))
+    }
+
+    pub fn head(&self, name: &str) -> Result<Option<Revision>> {
+        Ok(self.log(name)?.pop())
+    }
+
+    /// Record `content` as the newest revision, unless it matches the head
+    pub fn record(&self, name: &str, content: &str, operation: &str) -> Result<Option<Revision>> {
+        let log = self.log(name)?;
+        let hash = self.store_blob(content)?;
+        if log.last().is_some_and(|head| head.hash == hash) {
+            return Ok(None);
+        }
+        let revision = Revision {
+            number: log.last().map_or(1, |head| head.number + 1),
+            hash,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            operation: operation.to_string(),
+            size: content.len(),
+        };
+        self.append(name, &revision)?;
+        Ok(Some(revision))
+    }
+
+    fn append(&self, name: &str, revision: &Revision) -> Result<()> {
+        let dir = self.history_dir(name);
+        fs::create_dir_all(&dir)?;
+        let mut log = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("log.jsonl"))?;
+        writeln!(log, "{}", serde_json::to_string(revision)?)?;
+        Ok(())
+    }
+
+    pub fn tags(&self, name: &str) -> Result<BTreeMap<String, Tag>> {
+        self.migrate_legacy(name)?;
+        self.read_tags(name)
+    }
+
+    fn read_tags(&self, name: &str) -> Result<BTreeMap<String, Tag>> {
+        read_tags_in(&self.history_dir(name))
+    }
+
+    fn write_tags(&self, name: &str, tags: &BTreeMap<String, Tag>) -> Result<()> {
+        let dir = self.history_dir(name);
+        fs::create_dir_all(&dir)?;
+        write_tags_in(&dir, tags)
+    }
+
+    /// Point `tag` at a revision, replacing any previous target
+    pub fn set_tag(&self, name: &str, tag: &str, revision: u64, message: Option<&str>) -> Result<()> {
+        let mut tags = self.tags(name)?;
+        tags.insert(
+            tag.to_string(),
+            Tag {
+                revision,
+                created_at: chrono::Utc::now().to_rfc3339(),
+                message: message.filter(|m| !m.is_empty()).map(str::to_string),
+            },
+        );
+        self.write_tags(name, &tags)
+    }
+
+    /// Find a revision by tag, `r<N>` label, `~N` (N revisions before the
+    /// head) or hash prefix (at least 6 characters)
+    pub fn find(&self, name: &str, selector: &str) -> Result<Option<Revision>> {
+        let log = self.log(name)?;
+        let by_number = |n: u64| log.iter().find(|r| r.number == n).cloned();
+
+        if let Some(back) = selector.strip_prefix('~') {
+            let back: usize = if back.is_empty() {
+                1
+            } else {
+                back.parse()
+                    .map_err(|_| anyhow!("Invalid version '{}'. Use ~N, e.g. ~2", selector))?
+            };
+            return Ok(log.len().checked_sub(back + 1).map(|i| log[i].clone()));
+        }
+
+        if let Some(tag) = self.tags(name)?.get(selector) {
+            return Ok(by_number(tag.revision));
+        }
+        if let Some(n) = selector.strip_prefix('r').and_then(|n| n.parse().ok()) {
+            return Ok(by_number(n));
+        }
+        if selector.len() >= 6 && selector.chars().all(|c| c.is_ascii_hexdigit()) {
+            let mut matches = log.iter().rev().filter(|r| r.hash.starts_with(selector));
+            return Ok(matches.next().cloned());
+        }
+        Ok(None)
+    }
+
+    /// Import `<name>.versions/<tag>.md` copies written by older versions, and
+    /// logs kept under the extension-less prompt path
+    fn migrate_legacy(&self, name: &str) -> Result<()> {
+        let history = self.history_dir(name);
+        let unkeyed = history.with_extension("");
+        if unkeyed != history && unkeyed.join("log.jsonl").is_file() && !history.exists() {
+            fs::create_dir_all(&history)?;
+            for file in ["log.jsonl", "tags.json"] {
+                if unkeyed.join(file).is_file() {
+                    fs::rename(unkeyed.join(file), history.join(file))?;
+                }
+            }
+            let _ = fs::remove_dir(&unkeyed);
+        }
+
+        let prompt_path = self.storage.prompt_path(name);
+        let Some(parent) = prompt_path.parent() else {
+            return Ok(());
+        };
+        let stem = prompt_path.file_stem().unwrap_or_default().to_string_lossy();
+        // Older releases joined the full name, so bank prompts ended up one level deeper
+        let candidates = [
+            parent.join(format!("{}.versions", name)),
+            parent.join(format!("{}.versions", stem)),
+        ];
+        for legacy_dir in candidates.iter().filter(|dir| dir.is_dir()) {
+            self.import_legacy_dir(name, legacy_dir)?;
+        }
+        Ok(())
+    }
+
+    fn import_legacy_dir(&self, name: &str, legacy_dir: &Path) -> Result<()> {
+        let mut versions = Vec::new();
+        for entry in fs::read_dir(legacy_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let tag = path.file_stem().unwrap().to_string_lossy().to_string();
+            let file = fs::read_to_string(&path)?;
+            let Some((header, content)) = file
+                .strip_prefix("---\n")
+                .and_then(|rest| rest.split_once("\n---\n"))
+            else {
+                continue;
+            };
+            let field = |key: &str| {
+                header
+                    .lines()
+                    .find_map(|line| line.strip_prefix(key)?.strip_prefix(": "))
+                    .map(|v| v.trim().to_string())
+            };
+            versions.push((
+                field("created_at").unwrap_or_default(),
+                tag,
+                field("message"),
+                content.trim_start_matches('\n').to_string(),
+            ));
+        }
+        versions.sort();
+
+        let mut log = self.read_log(name)?;
+        let mut tags = self.read_tags(name)?;
+        for (created_at, tag, message, content) in versions {
+            let hash = self.store_blob(&content)?;
+            let revision = match log.iter().find(|r| r.hash == hash) {
+                Some(existing) => existing.clone(),
+                None => {
+                    let revision = Revision {
+                        number: log.last().map_or(1, |head| head.number + 1),
+                        hash,
+                        created_at: created_at.clone(),
+                        operation: "version".to_string(),
+                        size: content.len(),
+                    };
+                    self.append(name, &revision)?;
+                    log.push(revision.clone());
+                    revision
+                }
+            };
+            tags.entry(tag).or_insert(Tag {
+                revision: revision.number,
+                created_at,
+                message: message.filter(|m| !m.is_empty()),
+            });
+        }
+        self.write_tags(name, &tags)?;
+
+        // Everything now lives in the store
+        fs::remove_dir_all(legacy_dir)?;
+        Ok(())
+    }
+}
+
+/// Retention rules for `ph versions prune`, from `[versions]` in config.toml
+///
+/// A revision is pruned only when no rule keeps it; the head (the live
+/// prompt) is always kept. With no `keep_last` or `max_age_days` set nothing
+/// is pruned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Keep this many of the newest revisions of every prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<usize>,
+    /// Keep tagged revisions forever
+    #[serde(default = "default_keep_tagged")]
+    pub keep_tagged: bool,
+    /// Keep untagged revisions younger than this many days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u64>,
+}
+
+fn default_keep_tagged() -> bool {
+    true
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            keep_last: None,
+            keep_tagged: true,
+            max_age_days: None,
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Read the `[versions]` section from `config.toml` in the base directory
+    pub fn load(base_dir: &Path) -> Self {
+        fs::read_to_string(base_dir.join("config.toml"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Value>().ok())
+            .and_then(|config| config.get("versions").cloned())
+            .and_then(|table| table.try_into().ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether any rule limits history; without one, pruning keeps everything
+    pub fn is_configured(&self) -> bool {
+        self.keep_last.is_some() || self.max_age_days.is_some()
+    }
+
+    /// Change one rule: `keep-last 20`, `max-age 90d`, `keep-tagged off`
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let off = matches!(value, "off" | "none");
+        match key {
+            "keep-last" if off => self.keep_last = None,
+            "keep-last" => match value.parse() {
+                Ok(n) if n > 0 => self.keep_last = Some(n),
+                _ => return Err(anyhow!("keep-last takes a number of revisions, e.g. 20")),
+            },
+            "max-age" if off => self.max_age_days = None,
+            "max-age" => match value.trim_end_matches('d').parse() {
+                Ok(days) => self.max_age_days = Some(days),
+                Err(_) => return Err(anyhow!("max-age takes a number of days, e.g. 90d")),
+            },
+            "keep-tagged" => match value {
+                "on" | "true" | "yes" => self.keep_tagged = true,
+                "off" | "false" | "no" => self.keep_tagged = false,
+                _ => return Err(anyhow!("keep-tagged takes on or off")),
+            },
+            _ => {
+                return Err(anyhow!(
+                    "Unknown retention rule '{}'. Use: keep-last <N|off>, max-age <days|off>, keep-tagged <on|off>",
+                    key
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// One-line summary, e.g. "keep last 20, keep 90 days, keep tagged"
+    pub fn describe(&self) -> String {
+        let mut rules = Vec::new();
+        if let Some(n) = self.keep_last {
+            rules.push(format!("keep last {}", n));
+        }
+        if let Some(days) = self.max_age_days {
+            rules.push(format!("keep {} days", days));
+        }
+        if self.keep_tagged {
+            rules.push("keep tagged".to_string());
+        }
+        if !self.is_configured() {
+            return "none (history is kept forever)".to_string();
+        }
+        rules.join(", ")
+    }
+
+    /// Whether a revision survives pruning; `position` 0 is the head
+    pub fn retains(
+        &self,
+        revision: &Revision,
+        position: usize,
+        tagged: bool,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        if position == 0 || !self.is_configured() || (tagged && self.keep_tagged) {
+            return true;
+        }
+        if self.keep_last.is_some_and(|n| position < n) {
+            return true;
+        }
+        self.max_age_days.is_some_and(|days| {
+            chrono::DateTime::parse_from_rfc3339(&revision.created_at)
+                .map(|created| now.signed_duration_since(created) < chrono::Duration::days(days as i64))
+                .unwrap_or(true)
+        })
+    }
+}
+
+/// One prompt's history directory under `revisions/`
+#[derive(Debug, Clone)]
+pub struct History {
+    /// Prompt name the history belongs to
+    pub name: String,
+    pub dir: PathBuf,
+    /// The prompt file no longer exists
+    pub orphan: bool,
+}
+
+/// What `ph versions prune` removed, or would remove with `--dry-run`
+#[derive(Debug, Default, Serialize)]
+pub struct PruneReport {
+    pub histories: Vec<PrunedHistory>,
+    /// Blobs no remaining revision refers to
+    pub blobs: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrunedHistory {
+    pub name: String,
+    pub revisions: Vec<u64>,
+    pub tags: Vec<String>,
+    pub orphan: bool,
+}
+
+/// Prompt name for a history key (`banks/ops/deploy.md` → `ops/deploy`)
+fn name_for_key(key: &Path) -> String {
+    if let Ok(archived) = key.strip_prefix("archive") {
+        let mut parts = archived.iter();
+        let stamp = parts.next().unwrap_or_default().to_string_lossy();
+        return format!("{} (archived {})", name_for_key(parts.as_path()), stamp);
+    }
+    let parts: Vec<String> = key
+        .with_extension("")
+        .iter()
+        .map(|part| part.to_string_lossy().to_string())
+        .collect();
+    let rest = parts.get(1..).unwrap_or_default().join("/");
+    match parts.first().map(String::as_str) {
+        Some("teams") => format!("@{}", rest),
+        _ => rest,
+    }
+}
+
+fn read_log_in(dir: &Path) -> Result<Vec<Revision>> {
+    let path = dir.join("log.jsonl");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    fs::read_to_string(&path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).context("Corrupt revision log"))
+        .collect()
+}
+
+fn write_log_in(dir: &Path, log: &[Revision]) -> Result<()> {
+    let mut content = String::new();
+    for revision in log {
+        content.push_str(&serde_json::to_string(revision)?);
+        content.push('\n');
+    }
+    let temp = dir.join(".log.jsonl.tmp");
+    fs::write(&temp, content)?;
+    fs::rename(&temp, dir.join("log.jsonl"))?;
+    Ok(())
+}
+
+fn read_tags_in(dir: &Path) -> Result<BTreeMap<String, Tag>> {
+    let path = dir.join("tags.json");
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    serde_json::from_str(&fs::read_to_string(&path)?).context("Corrupt tags file")
+}
+
+fn write_tags_in(dir: &Path, tags: &BTreeMap<String, Tag>) -> Result<()> {
+    fs::write(dir.join("tags.json"), serde_json::to_string_pretty(tags)?)?;
+    Ok(())
+}
+
+impl RevisionStore {
+    /// Bring every live prompt's history into the current layout
+    fn migrate_all(&self) -> Result<()> {
+        for name in self.storage.list_prompts()? {
+            self.migrate_legacy(&name)?;
+        }
+        Ok(())
+    }
+
+    /// Every prompt history on disk, including those of deleted prompts
+    pub fn histories(&self) -> Result<Vec<History>> {
+        self.migrate_all()?;
+        let root = self.storage.base_dir().join("revisions");
+        let mut histories: Vec<History> = walkdir::WalkDir::new(&root)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name() == "log.jsonl")
+            .filter_map(|entry| {
+                let dir = entry.path().parent()?.to_path_buf();
+                let key = dir.strip_prefix(&root).ok()?.to_path_buf();
+                Some(History {
+                    name: name_for_key(&key),
+                    orphan: !self.storage.base_dir().join(&key).is_file(),
+                    dir,
+                })
+            })
+            .collect();
+        histories.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(histories)
+    }
+
+    /// `<name>.versions` directories left behind by prompts that no longer exist
+    pub fn orphaned_legacy_dirs(&self) -> Result<Vec<PathBuf>> {
+        // Live prompts' directories are migrated (and removed) first
+        self.migrate_all()?;
+        let base = self.storage.base_dir();
+        let mut dirs: Vec<PathBuf> = ["prompts", "banks", "teams"]
+            .iter()
+            .flat_map(|root| walkdir::WalkDir::new(base.join(root)).into_iter())
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry.file_type().is_dir() && entry.file_name().to_string_lossy().ends_with(".versions")
+            })
+            .map(|entry| entry.into_path())
+            .collect();
+        dirs.sort();
+        Ok(dirs)
+    }
+
+    /// Apply retention rules to every history and delete blobs nothing refers to
+    ///
+    /// `remove_orphans` also deletes the histories of prompts that no longer exist.
+    pub fn prune(
+        &self,
+        config: &RetentionConfig,
+        remove_orphans: bool,
+        dry_run: bool,
+    ) -> Result<PruneReport> {
+        let now = chrono::Utc::now();
+        let mut report = PruneReport::default();
+        let mut referenced = std::collections::HashSet::new();
+
+        for history in self.histories()? {
+            let log = read_log_in(&history.dir)?;
+            let mut tags = read_tags_in(&history.dir)?;
+
+            if history.orphan && remove_orphans {
+                if !dry_run {
+                    fs::remove_dir_all(&history.dir)?;
+                }
+                report.histories.push(PrunedHistory {
+                    name: history.name,
+                    revisions: log.iter().map(|r| r.number).collect(),
+                    tags: tags.into_keys().collect(),
+                    orphan: true,
+                });
+                continue;
+            }
+
+            let tagged: Vec<u64> = tags.values().map(|tag| tag.revision).collect();
+            let mut kept = Vec::new();
+            let mut dropped = Vec::new();
+            for (position, revision) in log.into_iter().rev().enumerate() {
+                if config.retains(&revision, position, tagged.contains(&revision.number), now) {
+                    kept.push(revision);
+                } else {
+                    dropped.push(revision.number);
+                }
+            }
+            kept.reverse();
+            dropped.reverse();
+            referenced.extend(kept.iter().map(|r| r.hash.clone()));
+            if dropped.is_empty() {
+                continue;
+            }
+
+            let dropped_tags: Vec<String> = tags
+                .iter()
+                .filter(|(_, tag)| dropped.contains(&tag.revision))
+                .map(|(name, _)| name.clone())
+                .collect();
+            if !dry_run {
+                write_log_in(&history.dir, &kept)?;
+                if !dropped_tags.is_empty() {
+                    tags.retain(|name, _| !dropped_tags.contains(name));
+                    write_tags_in(&history.dir, &tags)?;
+                }
+            }
+            report.histories.push(PrunedHistory {
+                name: history.name,
+                revisions: dropped,
+                tags: dropped_tags,
+                orphan: history.orphan,
+            });
+        }
+
+        if remove_orphans {
+            for dir in self.orphaned_legacy_dirs()? {
+                if !dry_run {
+                    fs::remove_dir_all(&dir)?;
+                }
+                let relative = dir.strip_prefix(self.storage.base_dir()).unwrap_or(&dir);
+                report.histories.push(PrunedHistory {
+                    name: relative.display().to_string(),
+                    revisions: Vec::new(),
+                    tags: Vec::new(),
+                    orphan: true,
+                });
+            }
+        }
+
+        // Blobs are shared between prompts, so collect only what no log uses
+        for entry in walkdir::WalkDir::new(self.objects_dir())
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+        {
+            let file = entry.file_name().to_string_lossy();
+            let Some(prefix) = entry.path().parent().and_then(|p| p.file_name()) else {
+                continue;
+            };
+            if file.starts_with('.') {
+                continue;
+            }
+            let hash = format!("{}{}", prefix.to_string_lossy(), file);
+            if !referenced.contains(&hash) {
+                report.blobs += 1;
+                report.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                if !dry_run {
+                    fs::remove_file(entry.path())?;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+impl Storage {
+    /// Revision history for prompts in this storage
+    pub fn revisions(&self) -> RevisionStore {
+        RevisionStore::new(self.clone())
+    }
+
+    /// Record the file on disk before it is overwritten
+    ///
+    /// The first snapshot of a prompt is its baseline; later ones only add a
+    /// revision when the file was changed outside of PromptHive.
+    pub fn snapshot_revision(&self, name: &str) -> Result<Option<Revision>> {
+        let path = self.prompt_path(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let store = self.revisions();
+        let operation = if store.log(name)?.is_empty() {
+            "baseline"
+        } else {
+            "external"
+        };
+        store.record(name, &fs::read_to_string(&path)?, operation)
+    }
+
+    /// Carry a prompt's history to its new name before the prompt is moved there
+    pub fn move_revisions(&self, old: &str, new: &str) -> Result<()> {
+        self.snapshot_revision(old)?;
+        self.revisions().move_history(old, new)
+    }
+
+    /// Record the file on disk as written by the current command
+    pub fn record_revision(&self, name: &str) -> Result<Option<Revision>> {
+        let path = self.prompt_path(name);
+        if !path.exists() {
+            return Err(anyhow!("Prompt '{}' does not exist", name));
+        }
+        self.revisions()
+            .record(name, &fs::read_to_string(&path)?, current_operation())
+    }
+
+    /// Read a prompt as it was at a version, without touching the live file
+    pub fn read_prompt_version(&self, name: &str, selector: &str) -> Result<(PromptMetadata, String)> {
+        if !self.prompt_exists(name) {
+            return Err(anyhow!("Prompt '{}' not found", name));
+        }
+        let content = match selector.strip_prefix('~') {
+            Some(back) => self.read_back_from_live(name, back, selector)?,
+            None => self.read_revision(name, selector)?.map(|(_, content)| content),
+        };
+        match content {
+            Some(content) => self.parse_prompt_content(&content),
+            None => {
+                let tags: Vec<String> = self.revisions().tags(name)?.into_keys().collect();
+                let hint = if tags.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (tags: {})", tags.join(", "))
+                };
+                Err(anyhow!("No version '{}' of '{}'{}", selector, name, hint))
+            }
+        }
+    }
+
+    /// Content `back` steps before the live file, read without recording anything
+    ///
+    /// An edit made outside ph that is not in the log yet counts as the
+    /// newest version, so `~1` is then the head revision.
+    fn read_back_from_live(&self, name: &str, back: &str, selector: &str) -> Result<Option<String>> {
+        let back: usize = if back.is_empty() {
+            1
+        } else {
+            back.parse()
+                .map_err(|_| anyhow!("Invalid version '{}'. Use ~N, e.g. ~2", selector))?
+        };
+        let live = fs::read_to_string(self.prompt_path(name))?;
+        let store = self.revisions();
+        let drifted = store.head(name)?.is_none_or(|head| head.hash != content_hash(&live));
+        if !drifted {
+            return Ok(self.read_revision(name, selector)?.map(|(_, content)| content));
+        }
+        match back {
+            0 => Ok(Some(live)),
+            back => Ok(self
+                .read_revision(name, &format!("~{}", back - 1))?
+                .map(|(_, content)| content)),
+        }
+    }
+
+    /// Content of a revision found by tag, `r<N>`, `~N` or hash prefix
+    pub fn read_revision(&self, name: &str, selector: &str) -> Result<Option<(Revision, String)>> {
+        let store = self.revisions();
+        match store.find(name, selector)? {
+            Some(revision) => {
+                let content = store.read_blob(&revision.hash)?;
+                Ok(Some((revision, content)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(name: &str) -> PromptMetadata {
+        PromptMetadata {
+            id: name.to_string(),
+            description: "test".to_string(),
+            tags: None,
+            created_at: None,
+            updated_at: None,
+            version: None,
+            git_hash: None,
+            parent_version: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_writes_record_deduplicated_revisions() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new_with_base(temp.path().to_path_buf()).unwrap();
+
+        storage.write_prompt("review", &metadata("review"), "v1").unwrap();
+        storage.write_prompt("review", &metadata("review"), "v1").unwrap();
+        storage.write_prompt("review", &metadata("review"), "v2").unwrap();
+        storage.write_prompt("copy", &metadata("review"), "v2").unwrap();
+
+        let log = storage.revisions().log("review").unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[1].number, 2);
+
+        // Same file content in another prompt shares the blob
+        let copy = storage.revisions().head("copy").unwrap().unwrap();
+        assert_eq!(copy.hash, log[1].hash);
+        let blobs = walkdir::WalkDir::new(temp.path().join("objects"))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .count();
+        assert_eq!(blobs, 2);
+
+        // A change made outside PromptHive is captured before the next write
+        fs::write(storage.prompt_path("review"), "edited by hand").unwrap();
+        storage.write_prompt("review", &metadata("review"), "v3").unwrap();
+        let log = storage.revisions().log("review").unwrap();
+        assert_eq!(log[2].operation, "external");
+        assert_eq!(log.len(), 4);
+    }
+
+    #[test]
+    fn test_tags_and_legacy_migration() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new_with_base(temp.path().to_path_buf()).unwrap();
+        storage.write_prompt("api", &metadata("api"), "old body").unwrap();
+
+        let legacy = temp.path().join("prompts").join("api.versions");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(
+            legacy.join("v0.md"),
+            "---\nversion: v0\ngit_hash: abc\ncreated_at: 2020-01-01T00:00:00Z\nmessage: first\n---\n\n---\nid: api\n---\n\nlegacy body",
+        )
+        .unwrap();
+
+        let store = storage.revisions();
+        let (revision, content) = storage.read_revision("api", "v0").unwrap().unwrap();
+        assert_eq!(content, "---\nid: api\n---\n\nlegacy body");
+        assert_eq!(store.tags("api").unwrap()["v0"].message.as_deref(), Some("first"));
+        assert!(!legacy.exists());
+
+        store.set_tag("api", "stable", 1, None).unwrap();
+        assert_eq!(store.find("api", "stable").unwrap().unwrap().number, 1);
+        assert_eq!(store.find("api", &revision.label()).unwrap().unwrap().hash, revision.hash);
+        assert!(store.find("api", "r99").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_history_is_keyed_by_prompt_file_and_follows_renames() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new_with_base(temp.path().to_path_buf()).unwrap();
+        let store = storage.revisions();
+        storage.write_prompt("ops/deploy", &metadata("deploy"), "deploy").unwrap();
+        storage.write_prompt("ops/deploy/canary", &metadata("canary"), "canary").unwrap();
+        store.set_tag("ops/deploy", "v1", 1, None).unwrap();
+
+        // A bank prompt and the prompts nested under its name stay apart
+        assert_eq!(store.key("ops/deploy"), Path::new("banks/ops/deploy.md"));
+        assert_eq!(store.key("@acme/review"), Path::new("teams/acme/review.md"));
+        assert_eq!(store.log("ops/deploy/canary").unwrap().len(), 1);
+
+        storage.move_revisions("ops/deploy", "ops/release").unwrap();
+        assert!(store.tags("ops/release").unwrap().contains_key("v1"));
+        assert!(store.log("ops/deploy").unwrap().is_empty());
+
+        fs::rename(temp.path().join("banks/ops"), temp.path().join("banks/infra")).unwrap();
+        store.rename_bank("ops", "infra").unwrap();
+        assert_eq!(store.find("infra/release", "v1").unwrap().unwrap().number, 1);
+        assert_eq!(store.log("infra/deploy/canary").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_reading_back_from_live_file_records_nothing() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new_with_base(temp.path().to_path_buf()).unwrap();
+        let store = storage.revisions();
+        storage.write_prompt("api", &metadata("api"), "one").unwrap();
+        storage.write_prompt("api", &metadata("api"), "two").unwrap();
+        assert_eq!(storage.read_prompt_version("api", "~1").unwrap().1, "one");
+
+        // An edit made outside ph is the newest version until something records it
+        let path = storage.prompt_path("api");
+        fs::write(&path, fs::read_to_string(&path).unwrap().replace("two", "three")).unwrap();
+        assert_eq!(storage.read_prompt_version("api", "~0").unwrap().1, "three");
+        assert_eq!(storage.read_prompt_version("api", "~").unwrap().1, "two");
+        assert_eq!(storage.read_prompt_version("api", "~2").unwrap().1, "one");
+        assert_eq!(store.log("api").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_renames_archive_history_left_by_deleted_prompts() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new_with_base(temp.path().to_path_buf()).unwrap();
+        let store = storage.revisions();
+        // A deleted bank `infra` left the history of its `deploy` prompt behind
+        storage.write_prompt("infra/deploy", &metadata("deploy"), "old").unwrap();
+        storage.write_prompt("infra/deploy", &metadata("deploy"), "older").unwrap();
+        fs::remove_dir_all(temp.path().join("banks/infra")).unwrap();
+        storage.write_prompt("ops/deploy", &metadata("deploy"), "new").unwrap();
+        storage.write_prompt("ops/notes", &metadata("notes"), "notes").unwrap();
+
+        storage.rename_bank("ops", "infra").unwrap();
+        assert_eq!(store.log("infra/deploy").unwrap().len(), 1);
+        assert_eq!(store.log("infra/notes").unwrap().len(), 1);
+        assert!(!temp.path().join("revisions/banks/ops").exists());
+
+        storage.write_prompt("ops/notes", &metadata("notes"), "other").unwrap();
+        fs::remove_file(storage.prompt_path("ops/notes")).unwrap();
+        storage.move_revisions("infra/notes", "ops/notes").unwrap();
+        fs::rename(storage.prompt_path("infra/notes"), storage.prompt_path("ops/notes")).unwrap();
+        assert_eq!(store.log("ops/notes").unwrap().len(), 1);
+
+        let archived: Vec<History> = store.histories().unwrap().into_iter().filter(|h| h.orphan).collect();
+        assert_eq!(archived.len(), 2);
+        assert!(archived.iter().all(|h| h.name.contains(" (archived ")));
+        assert!(archived.iter().any(|h| h.name.starts_with("infra/deploy ") && read_log_in(&h.dir).unwrap().len() == 2));
+    }
+
+    #[test]
+    fn test_prune_keeps_last_tagged_and_collects_blobs() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new_with_base(temp.path().to_path_buf()).unwrap();
+        let store = storage.revisions();
+        for body in ["one", "two", "three", "four", "five"] {
+            storage.write_prompt("api", &metadata("api"), body).unwrap();
+        }
+        store.set_tag("api", "v1", 1, None).unwrap();
+        storage.write_prompt("gone", &metadata("gone"), "bye").unwrap();
+        fs::remove_file(storage.prompt_path("gone")).unwrap();
+
+        // Nothing configured: nothing pruned
+        let report = store.prune(&RetentionConfig::default(), false, false).unwrap();
+        assert!(report.histories.is_empty());
+
+        let config = RetentionConfig {
+            keep_last: Some(2),
+            ..Default::default()
+        };
+        let preview = store.prune(&config, true, true).unwrap();
+        assert_eq!(store.log("api").unwrap().len(), 5);
+
+        let report = store.prune(&config, true, false).unwrap();
+        assert_eq!(report.histories.len(), preview.histories.len());
+        assert_eq!(report.histories[0].revisions, vec![2, 3]);
+        assert!(report.histories[1].orphan);
+        assert_eq!(report.blobs, 3);
+
+        let kept: Vec<u64> = store.log("api").unwrap().iter().map(|r| r.number).collect();
+        assert_eq!(kept, vec![1, 4, 5]);
+        assert!(storage.read_revision("api", "v1").unwrap().is_some());
+        assert!(store.histories().unwrap().iter().all(|h| !h.orphan));
+    }
+}
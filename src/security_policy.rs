@@ -0,0 +1,346 @@
+//! Security policy loaded from `security.toml`
+//!
+//! The policy tunes the built-in scanner rules without touching code. It is
+//! read from the library (`<library>/security.toml`) and from the nearest
+//! project (`.prompthive/security.toml` in the current directory or a parent),
+//! with project settings taking precedence:
+//!
+//! ```toml
+//! disabled_rules = ["email"]
+//! min_confidence = "medium"
+//!
+//! [severity]
+//! command-run-instruction = "high"
+//!
+//! [[rules]]
+//! id = "internal-host"
+//! pattern = "corp\\.internal"
+//! severity = "high"
+//! description = "Internal hostname"
+//!
+//! [[suppress]]
+//! rule = "password"
+//! prompts = ["onboarding/*"]
+//! ```
+//!
+//! Single findings can be ignored inline with
+//! `<!-- ph-security-ignore: rule-id -->` on the same line or the line above.
+//! A baseline file records accepted findings so audits only report new ones.
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::security::Confidence;
+use crate::{SecurityIssue, SecuritySeverity};
+
+/// File name of the policy in the library and in `.prompthive/` of a project
+pub const POLICY_FILE: &str = "security.toml";
+
+/// Default baseline file name inside the library
+pub const BASELINE_FILE: &str = "security-baseline.json";
+
+static INLINE_IGNORE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<!--\s*ph-security-ignore:\s*(.*?)\s*-->").unwrap());
+
+/// A rule defined in `security.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRule {
+    pub id: String,
+    /// Regex matched against each line
+    pub pattern: String,
+    #[serde(default = "default_custom_severity")]
+    pub severity: SecuritySeverity,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub remediation: Option<String>,
+}
+
+fn default_custom_severity() -> SecuritySeverity {
+    SecuritySeverity::Medium
+}
+
+/// Findings to ignore for some prompts or paths
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Suppression {
+    /// Rule to suppress; all rules when omitted
+    #[serde(default)]
+    pub rule: Option<String>,
+    /// Globs matched against prompt names (e.g. `contacts/*`)
+    #[serde(default)]
+    pub prompts: Vec<String>,
+    /// Globs matched against file paths (e.g. `**/banks/team/*.md`)
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+impl Suppression {
+    fn applies(&self, rule_id: &str, prompt: Option<&str>, path: Option<&str>) -> bool {
+        if self.rule.as_deref().is_some_and(|rule| rule != rule_id) {
+            return false;
+        }
+        if self.prompts.is_empty() && self.paths.is_empty() {
+            return true;
+        }
+        let matches = |globs: &[String], value: Option<&str>| {
+            value.is_some_and(|value| {
+                globs.iter().any(|g| {
+                    glob::Pattern::new(g)
+                        .map(|p| p.matches(value))
+                        .unwrap_or(false)
+                })
+            })
+        };
+        matches(&self.prompts, prompt) || matches(&self.paths, path)
+    }
+}
+
+/// Scanner policy from `security.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecurityPolicy {
+    /// Rule ids that never report findings
+    #[serde(default)]
+    pub disabled_rules: Vec<String>,
+    /// Severity overrides by rule id
+    #[serde(default)]
+    pub severity: BTreeMap<String, SecuritySeverity>,
+    /// Additional rules
+    #[serde(default)]
+    pub rules: Vec<CustomRule>,
+    /// Prompt and path suppressions
+    #[serde(default)]
+    pub suppress: Vec<Suppression>,
+    /// Baseline file, relative to the library (defaults to security-baseline.json)
+    #[serde(default)]
+    pub baseline: Option<String>,
+    /// Findings below this confidence are not reported
+    #[serde(default)]
+    pub min_confidence: Option<Confidence>,
+}
+
+impl SecurityPolicy {
+    /// Load and merge the library and project policies
+    pub fn load(base_dir: &Path) -> Result<Self> {
+        let mut policy = Self::load_file(&base_dir.join(POLICY_FILE))?;
+
+        if let Some(project) = Self::find_project_policy() {
+            let same_file = match (project.canonicalize(), base_dir.join(POLICY_FILE).canonicalize()) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false,
+            };
+            if !same_file {
+                policy.merge(Self::load_file(&project)?);
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// Load a single policy file; a missing file is an empty policy
+    pub fn load_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read security policy from {:?}", path))?;
+        let policy: Self = toml::from_str(&content)
+            .with_context(|| format!("Invalid security policy {:?}", path))?;
+
+        for rule in &policy.rules {
+            Regex::new(&rule.pattern)
+                .with_context(|| format!("Invalid pattern for rule '{}' in {:?}", rule.id, path))?;
+        }
+
+        Ok(policy)
+    }
+
+    /// Nearest `.prompthive/security.toml` from the current directory upwards
+    fn find_project_policy() -> Option<PathBuf> {
+        let cwd = std::env::current_dir().ok()?;
+        cwd.ancestors()
+            .map(|dir| dir.join(".prompthive").join(POLICY_FILE))
+            .find(|path| path.exists())
+    }
+
+    /// Merge another policy on top of this one (the other one wins)
+    pub fn merge(&mut self, other: Self) {
+        for rule in other.disabled_rules {
+            if !self.disabled_rules.contains(&rule) {
+                self.disabled_rules.push(rule);
+            }
+        }
+        self.severity.extend(other.severity);
+        self.rules.retain(|r| !other.rules.iter().any(|o| o.id == r.id));
+        self.rules.extend(other.rules);
+        self.suppress.extend(other.suppress);
+        if other.baseline.is_some() {
+            self.baseline = other.baseline;
+        }
+        if other.min_confidence.is_some() {
+            self.min_confidence = other.min_confidence;
+        }
+    }
+
+    pub fn is_disabled(&self, rule_id: &str) -> bool {
+        self.disabled_rules.iter().any(|r| r == rule_id)
+    }
+
+    pub fn is_suppressed(&self, rule_id: &str, prompt: Option<&str>, path: Option<&str>) -> bool {
+        self.suppress.iter().any(|s| s.applies(rule_id, prompt, path))
+    }
+
+    /// Whether a finding is confident enough to report
+    pub fn reports(&self, confidence: Confidence) -> bool {
+        self.min_confidence.is_none_or(|min| confidence >= min)
+    }
+
+    /// Severity for a rule after overrides
+    pub fn severity_for(&self, rule_id: &str, default: SecuritySeverity) -> SecuritySeverity {
+        self.severity.get(rule_id).cloned().unwrap_or(default)
+    }
+
+    /// Path of the baseline file for a library
+    pub fn baseline_path(&self, base_dir: &Path) -> PathBuf {
+        base_dir.join(self.baseline.as_deref().unwrap_or(BASELINE_FILE))
+    }
+}
+
+/// Rule ids ignored inline, by 1-based line number
+///
+/// A `<!-- ph-security-ignore: a, b -->` comment covers its own line and the
+/// line below it. `all` ignores every rule.
+pub fn inline_ignores(content: &str) -> HashMap<usize, Vec<String>> {
+    let mut ignores: HashMap<usize, Vec<String>> = HashMap::new();
+
+    for (index, line) in content.lines().enumerate() {
+        for captures in INLINE_IGNORE.captures_iter(line) {
+            let ids: Vec<String> = captures[1]
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string())
+                .collect();
+            for line_number in [index + 1, index + 2] {
+                ignores.entry(line_number).or_default().extend(ids.clone());
+            }
+        }
+    }
+
+    ignores
+}
+
+/// Findings accepted as known, so audits only report new ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecurityBaseline {
+    /// Fingerprints of accepted findings
+    pub findings: BTreeSet<String>,
+}
+
+impl SecurityBaseline {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read security baseline from {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| "Failed to parse security baseline JSON")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .with_context(|| "Failed to serialize security baseline")?;
+
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write security baseline to {:?}", path))?;
+
+        Ok(())
+    }
+
+    pub fn contains(&self, fingerprint: &str) -> bool {
+        self.findings.contains(fingerprint)
+    }
+}
+
+/// Stable fingerprint of a finding
+///
+/// Built from the rule, the prompt and the text of the offending line, so it
+/// survives lines moving around but changes when the line itself changes.
+pub fn fingerprint(issue: &SecurityIssue, prompt: &str, content: &str) -> String {
+    let line = issue
+        .line_number
+        .and_then(|n| content.lines().nth(n.saturating_sub(1)))
+        .unwrap_or("")
+        .trim();
+
+    let mut hasher = Sha256::new();
+    hasher.update(issue.rule_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(prompt.as_bytes());
+    hasher.update([0]);
+    hasher.update(line.as_bytes());
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_parses_and_merges() {
+        let library: SecurityPolicy = toml::from_str(
+            r#"
+            disabled_rules = ["email"]
+
+            [severity]
+            password = "low"
+
+            [[suppress]]
+            rule = "api-key"
+            prompts = ["docs/*"]
+            "#,
+        )
+        .unwrap();
+        let project: SecurityPolicy = toml::from_str(
+            r#"
+            [severity]
+            password = "Critical"
+
+            [[rules]]
+            id = "internal-host"
+            pattern = "corp\\.internal"
+            severity = "high"
+            "#,
+        )
+        .unwrap();
+
+        let mut policy = library;
+        policy.merge(project);
+
+        assert!(policy.is_disabled("email"));
+        assert_eq!(
+            policy.severity_for("password", SecuritySeverity::High),
+            SecuritySeverity::Critical
+        );
+        assert_eq!(policy.rules[0].severity, SecuritySeverity::High);
+        assert!(policy.is_suppressed("api-key", Some("docs/setup"), None));
+        assert!(!policy.is_suppressed("api-key", Some("deploy"), None));
+        assert!(!policy.is_suppressed("password", Some("docs/setup"), None));
+    }
+
+    #[test]
+    fn test_inline_ignores_cover_same_and_next_line() {
+        let content = "intro\n<!-- ph-security-ignore: email, password -->\nmail: a@b.co\nlast";
+        let ignores = inline_ignores(content);
+
+        assert_eq!(ignores[&2], vec!["email", "password"]);
+        assert_eq!(ignores[&3], vec!["email", "password"]);
+        assert!(!ignores.contains_key(&4));
+    }
+}
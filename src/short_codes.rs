@@ -0,0 +1,247 @@
+//! Persistent short codes for prompts
+//!
+//! Short codes are assigned once and stored in the library, so a prompt keeps
+//! its code when other prompts are added or removed. Custom codes can be set
+//! with `ph alias set`, and collisions are reported at assignment time instead
+//! of silently shifting another prompt's code.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{Matcher, Storage};
+
+/// A short code assignment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortCodeEntry {
+    /// Full prompt name the code points at
+    pub prompt: String,
+    /// Whether the code was chosen by the user (`ph alias set`)
+    #[serde(default)]
+    pub custom: bool,
+    /// Timestamp when the code was assigned
+    pub assigned_at: DateTime<Utc>,
+}
+
+/// Library-wide registry of short codes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShortCodeRegistry {
+    /// Map of short codes to their assignments
+    pub codes: BTreeMap<String, ShortCodeEntry>,
+}
+
+impl ShortCodeRegistry {
+    fn registry_path(storage: &Storage) -> PathBuf {
+        storage.base_dir().join("short_codes.json")
+    }
+
+    /// Load the short code registry from storage
+    pub fn load(storage: &Storage) -> Result<Self> {
+        let path = Self::registry_path(storage);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read short codes from {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| "Failed to parse short codes JSON")
+    }
+
+    /// Save the short code registry to storage
+    pub fn save(&self, storage: &Storage) -> Result<()> {
+        let path = Self::registry_path(storage);
+        let content = serde_json::to_string_pretty(self)
+            .with_context(|| "Failed to serialize short codes")?;
+
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write short codes to {:?}", path))?;
+
+        Ok(())
+    }
+
+    /// Load the registry and make sure every listed prompt has a code
+    ///
+    /// New prompts get a generated code, codes of prompts that no longer exist
+    /// are released, and the registry is only written back when it changed.
+    pub fn load_for(storage: &Storage, prompts: &[String]) -> Result<Self> {
+        let mut registry = Self::load(storage).unwrap_or_default();
+        if registry.assign_missing(prompts) {
+            // A read-only library should not break listing or matching
+            let _ = registry.save(storage);
+        }
+        Ok(registry)
+    }
+
+    /// Get the short code assigned to a prompt
+    pub fn code_for(&self, prompt: &str) -> Option<&str> {
+        self.codes
+            .iter()
+            .find(|(_, entry)| entry.prompt == prompt)
+            .map(|(code, _)| code.as_str())
+    }
+
+    /// Get the prompt a short code points at
+    pub fn prompt_for(&self, code: &str) -> Option<&str> {
+        self.codes.get(code).map(|entry| entry.prompt.as_str())
+    }
+
+    /// Assign a code to a prompt, replacing the prompt's previous code
+    ///
+    /// Fails if the code already belongs to a different prompt.
+    pub fn assign(&mut self, code: &str, prompt: &str, custom: bool) -> Result<()> {
+        validate_code(code)?;
+
+        if let Some(existing) = self.codes.get(code) {
+            if existing.prompt != prompt {
+                return Err(anyhow!(
+                    "Short code '{}' is already assigned to '{}'",
+                    code,
+                    existing.prompt
+                ));
+            }
+        }
+
+        self.remove_prompt(prompt);
+        self.codes.insert(
+            code.to_string(),
+            ShortCodeEntry {
+                prompt: prompt.to_string(),
+                custom,
+                assigned_at: Utc::now(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Remove a code, returning its assignment
+    pub fn remove_code(&mut self, code: &str) -> Option<ShortCodeEntry> {
+        self.codes.remove(code)
+    }
+
+    /// Remove the code of a prompt, returning the released code
+    pub fn remove_prompt(&mut self, prompt: &str) -> Option<String> {
+        let code = self.code_for(prompt)?.to_string();
+        self.codes.remove(&code);
+        Some(code)
+    }
+
+    /// Move a prompt's code to its new name after a rename
+    pub fn rename_prompt(&mut self, old_name: &str, new_name: &str) {
+        for entry in self.codes.values_mut() {
+            if entry.prompt == old_name {
+                entry.prompt = new_name.to_string();
+            }
+        }
+    }
+
+    /// Generate codes for prompts without one and release codes of missing prompts
+    ///
+    /// Returns true if the registry changed.
+    pub fn assign_missing(&mut self, prompts: &[String]) -> bool {
+        let known: HashSet<&str> = prompts.iter().map(|p| p.as_str()).collect();
+        let before = self.codes.len();
+        self.codes
+            .retain(|_, entry| known.contains(entry.prompt.as_str()));
+        let mut changed = self.codes.len() != before;
+
+        let assigned: HashSet<String> = self.codes.values().map(|e| e.prompt.clone()).collect();
+
+        // Codes must not shadow full prompt names either
+        let mut taken: Vec<String> = self.codes.keys().cloned().collect();
+        taken.extend(prompts.iter().cloned());
+
+        for prompt in prompts {
+            if assigned.contains(prompt) {
+                continue;
+            }
+
+            // Bank prompts are coded by their own name, not the bank path
+            let base = prompt.rsplit('/').next().unwrap_or(prompt);
+            let code = Matcher::generate_short_code(base, &taken);
+            if self.codes.contains_key(&code) {
+                // Generation fell back to the full name and it is taken; leave unassigned
+                continue;
+            }
+
+            taken.push(code.clone());
+            self.codes.insert(
+                code,
+                ShortCodeEntry {
+                    prompt: prompt.clone(),
+                    custom: false,
+                    assigned_at: Utc::now(),
+                },
+            );
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+/// Check that a custom code can be typed as a single CLI argument
+fn validate_code(code: &str) -> Result<()> {
+    if code.is_empty() {
+        return Err(anyhow!("Short code cannot be empty"));
+    }
+
+    if code.starts_with('@')
+        || code
+            .chars()
+            .any(|c| c.is_whitespace() || c == '/' || c == ',')
+    {
+        return Err(anyhow!(
+            "Invalid short code '{}': codes cannot contain whitespace, '/', ',' or start with '@'",
+            code
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_stable_when_prompts_change() {
+        let mut registry = ShortCodeRegistry::default();
+        registry.assign_missing(&["auth-basic".to_string(), "zebra".to_string()]);
+        let original = registry.code_for("zebra").unwrap().to_string();
+
+        // Adding a prompt that would have claimed the same code keeps the old one
+        registry.assign_missing(&[
+            "auth-basic".to_string(),
+            "zeta".to_string(),
+            "zebra".to_string(),
+        ]);
+        assert_eq!(registry.code_for("zebra"), Some(original.as_str()));
+        assert_ne!(registry.code_for("zeta"), Some(original.as_str()));
+
+        // Removing a prompt releases only its own code
+        registry.assign_missing(&["zebra".to_string()]);
+        assert_eq!(registry.code_for("zebra"), Some(original.as_str()));
+        assert!(registry.code_for("auth-basic").is_none());
+    }
+
+    #[test]
+    fn test_assign_reports_collisions() {
+        let mut registry = ShortCodeRegistry::default();
+        registry.assign("rv", "review", true).unwrap();
+
+        let err = registry.assign("rv", "revert", true).unwrap_err();
+        assert!(err.to_string().contains("already assigned to 'review'"));
+
+        // Reassigning a prompt moves its code
+        registry.assign("r", "review", true).unwrap();
+        assert_eq!(registry.code_for("review"), Some("r"));
+        assert!(registry.prompt_for("rv").is_none());
+
+        assert!(registry.assign("bad code", "review", true).is_err());
+    }
+}
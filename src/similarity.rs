@@ -0,0 +1,280 @@
+//! Local near-duplicate detection for prompt bodies
+//!
+//! Prompts are compared by word shingling: each body is normalized to lowercase
+//! words, split into overlapping two-word shingles, and two prompts are scored
+//! by the share of shingles they have in common (Dice coefficient), so two
+//! prompts that are 90% the same text score close to 0.9. Everything runs locally.
+
+use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::Storage;
+
+/// Number of consecutive words per shingle
+const SHINGLE_SIZE: usize = 2;
+
+/// Similarity above which `ph new` and `ph import` warn about a near-duplicate
+pub const NEAR_DUPLICATE_THRESHOLD: f64 = 0.8;
+
+/// A prompt similar to the query, with its similarity score (0.0 - 1.0)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarPrompt {
+    pub name: String,
+    pub score: f64,
+}
+
+/// Two prompts above the clustering threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarPair {
+    pub first: String,
+    pub second: String,
+    pub score: f64,
+}
+
+/// A group of prompts connected by near-duplicate pairs
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    /// Prompt names in the cluster, sorted
+    pub prompts: Vec<String>,
+    /// Pairs inside the cluster, highest score first
+    pub pairs: Vec<SimilarPair>,
+}
+
+impl Cluster {
+    /// Highest pairwise score in the cluster
+    pub fn max_score(&self) -> f64 {
+        self.pairs.first().map(|p| p.score).unwrap_or(0.0)
+    }
+}
+
+/// Shingle sets for a collection of prompts
+#[derive(Debug, Default)]
+pub struct SimilarityIndex {
+    documents: Vec<(String, HashSet<u64>)>,
+}
+
+impl SimilarityIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index every prompt body in storage, skipping merge backups
+    pub fn from_storage(storage: &Storage) -> Result<Self> {
+        let mut index = Self::new();
+
+        for name in storage.list_prompts()? {
+            if name.ends_with(".backup") {
+                continue;
+            }
+            if let Ok((_, body)) = storage.read_prompt(&name) {
+                index.add(&name, &body);
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Add a prompt body to the index
+    pub fn add(&mut self, name: &str, text: &str) {
+        let shingles = shingles(text);
+        if !shingles.is_empty() {
+            self.documents.push((name.to_string(), shingles));
+        }
+    }
+
+    /// Check whether a prompt is indexed (prompts with empty bodies are not)
+    pub fn contains(&self, name: &str) -> bool {
+        self.documents.iter().any(|(n, _)| n == name)
+    }
+
+    /// Prompts similar to an arbitrary text, best first
+    ///
+    /// `exclude` skips a prompt by name, typically the one being compared.
+    pub fn similar_to_text(
+        &self,
+        text: &str,
+        exclude: Option<&str>,
+        threshold: f64,
+    ) -> Vec<SimilarPrompt> {
+        let query = shingles(text);
+        self.rank(&query, exclude, threshold)
+    }
+
+    /// Prompts similar to an indexed prompt, best first
+    pub fn similar_to(&self, name: &str, threshold: f64) -> Vec<SimilarPrompt> {
+        match self.documents.iter().find(|(n, _)| n == name) {
+            Some((_, query)) => self.rank(query, Some(name), threshold),
+            None => Vec::new(),
+        }
+    }
+
+    /// Group prompts into clusters of near-duplicates
+    ///
+    /// Prompts end up in the same cluster when they are connected by a chain
+    /// of pairs scoring at least `threshold`. Clusters are ordered by their
+    /// highest score.
+    pub fn clusters(&self, threshold: f64) -> Vec<Cluster> {
+        let count = self.documents.len();
+        let mut parent: Vec<usize> = (0..count).collect();
+        let mut pairs = Vec::new();
+
+        for i in 0..count {
+            for j in (i + 1)..count {
+                let score = similarity(&self.documents[i].1, &self.documents[j].1);
+                if score >= threshold {
+                    let (root_i, root_j) = (find_root(&mut parent, i), find_root(&mut parent, j));
+                    parent[root_j] = root_i;
+                    pairs.push((i, j, score));
+                }
+            }
+        }
+
+        let mut groups: BTreeMap<usize, Cluster> = BTreeMap::new();
+        for (i, j, score) in pairs {
+            let root = find_root(&mut parent, i);
+            let cluster = groups.entry(root).or_insert_with(|| Cluster {
+                prompts: Vec::new(),
+                pairs: Vec::new(),
+            });
+            for index in [i, j] {
+                let name = &self.documents[index].0;
+                if !cluster.prompts.contains(name) {
+                    cluster.prompts.push(name.clone());
+                }
+            }
+            cluster.pairs.push(SimilarPair {
+                first: self.documents[i].0.clone(),
+                second: self.documents[j].0.clone(),
+                score,
+            });
+        }
+
+        let mut clusters: Vec<Cluster> = groups.into_values().collect();
+        for cluster in &mut clusters {
+            cluster.prompts.sort();
+            cluster.pairs.sort_by(|a, b| b.score.total_cmp(&a.score));
+        }
+        clusters.sort_by(|a, b| b.max_score().total_cmp(&a.max_score()));
+        clusters
+    }
+
+    fn rank(
+        &self,
+        query: &HashSet<u64>,
+        exclude: Option<&str>,
+        threshold: f64,
+    ) -> Vec<SimilarPrompt> {
+        let mut results: Vec<SimilarPrompt> = self
+            .documents
+            .iter()
+            .filter(|(name, _)| Some(name.as_str()) != exclude)
+            .map(|(name, doc)| SimilarPrompt {
+                name: name.clone(),
+                score: similarity(query, doc),
+            })
+            .filter(|s| s.score > 0.0 && s.score >= threshold)
+            .collect();
+
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results
+    }
+}
+
+/// Hash the overlapping word shingles of a text
+///
+/// Texts shorter than one shingle are treated as a single shingle so very
+/// short prompts can still match each other.
+pub fn shingles(text: &str) -> HashSet<u64> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+
+    if words.is_empty() {
+        return HashSet::new();
+    }
+
+    if words.len() < SHINGLE_SIZE {
+        return std::iter::once(hash_words(&words)).collect();
+    }
+
+    words.windows(SHINGLE_SIZE).map(hash_words).collect()
+}
+
+/// Share of shingles two sets have in common (Dice coefficient)
+pub fn similarity(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let shared = a.intersection(b).count();
+    (2 * shared) as f64 / (a.len() + b.len()) as f64
+}
+
+fn hash_words(words: &[String]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    words.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn find_root(parent: &mut [usize], mut index: usize) -> usize {
+    while parent[index] != index {
+        parent[index] = parent[parent[index]];
+        index = parent[index];
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity_scores() {
+        let base =
+            "Review this code for bugs, security issues and performance problems. Suggest fixes.";
+        let near = "Review this code for bugs, security issues and performance problems. Suggest concrete fixes.";
+        let other = "Write a haiku about the ocean at night";
+
+        let score = similarity(&shingles(base), &shingles(near));
+        assert!(score > 0.7 && score < 1.0, "score was {}", score);
+        assert_eq!(similarity(&shingles(base), &shingles(base)), 1.0);
+        assert_eq!(similarity(&shingles(base), &shingles(other)), 0.0);
+        assert_eq!(similarity(&shingles(""), &shingles(base)), 0.0);
+    }
+
+    #[test]
+    fn test_clusters_group_transitive_near_duplicates() {
+        let mut index = SimilarityIndex::new();
+        index.add(
+            "review",
+            "please review the following code carefully for bugs and style issues",
+        );
+        index.add(
+            "review-v2",
+            "please review the following code carefully for bugs and style issues today",
+        );
+        index.add(
+            "review-v3",
+            "please review the following code carefully for bugs and style issues today thanks",
+        );
+        index.add("haiku", "write a haiku about autumn leaves falling slowly");
+
+        let clusters = index.clusters(0.7);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(
+            clusters[0].prompts,
+            vec!["review", "review-v2", "review-v3"]
+        );
+        assert!(clusters[0].max_score() >= 0.7);
+
+        let similar = index.similar_to("review", 0.5);
+        assert_eq!(similar[0].name, "review-v2");
+        assert!(similar
+            .iter()
+            .all(|s| s.name != "review" && s.name != "haiku"));
+    }
+}
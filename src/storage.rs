@@ -38,6 +38,53 @@ pub struct PromptMetadata {
     /// Previous version for tracking prompt evolution
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_version: Option<String>,
+    /// Where the content came from (e.g. the transcript a prompt was extracted from)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Split `name@selector` into the prompt name and a version selector
+///
+/// Selectors name a tag (`review@v1.0`, `review@stable`), a revision
+/// (`review@r3`, `review@~2` for two revisions back) or a hash prefix. A
+/// leading `@` marks a team prompt (`@team/review`), not a version.
+pub fn split_version(query: &str) -> (&str, Option<&str>) {
+    match query.rfind('@') {
+        Some(i) if i > 0 && i + 1 < query.len() => (&query[..i], Some(&query[i + 1..])),
+        _ => (query, None),
+    }
+}
+
+/// Re-attach a version selector to a resolved prompt name
+pub fn with_version(name: &str, selector: Option<&str>) -> String {
+    match selector {
+        Some(selector) => format!("{}@{}", name, selector),
+        None => name.to_string(),
+    }
+}
+
+/// Whether a compose or `--with` reference names a file rather than a prompt
+///
+/// Paths start with `~` or `/`, or have an extension; `review@v1.0` is a prompt.
+pub fn is_file_reference(reference: &str) -> bool {
+    reference.starts_with('~')
+        || reference.starts_with('/')
+        || split_version(reference).0.contains('.')
+}
+
+/// Refuse to write through a versioned name
+fn ensure_writable(name: &str) -> Result<()> {
+    if let (base, Some(selector)) = split_version(name) {
+        return Err(anyhow!(
+            "'{}' refers to version '{}' of '{}'; versions are read-only (use `ph rollback {} {}` to restore it)",
+            name,
+            selector,
+            base,
+            base,
+            selector
+        ));
+    }
+    Ok(())
 }
 
 /// Core storage system for prompts and metadata
@@ -305,6 +352,9 @@ Provide the refactored code with explanations."#;
     }
 
     pub fn prompt_path(&self, name: &str) -> PathBuf {
+        // A versioned name lives in the revision store; its path is the live file
+        let name = split_version(name).0;
+
         // Check if this is a team prompt (starts with @)
         if let Some(without_at) = name.strip_prefix('@') {
             if let Some(slash_pos) = without_at.find('/') {
@@ -505,6 +555,16 @@ Provide the refactored code with explanations."#;
         use fuzzy_matcher::skim::SkimMatcherV2;
         use fuzzy_matcher::FuzzyMatcher;
 
+        // Resolve the name and keep the version selector
+        if let (base, Some(selector)) = split_version(query) {
+            return Ok(with_version(&self.resolve_prompt(base)?, Some(selector)));
+        }
+
+        // Exact matching (--exact) never falls back to fuzzy resolution
+        if crate::matching::exact_matching_enabled() {
+            return self.resolve_exact(query);
+        }
+
         // Check if query contains bank syntax (bank/prompt)
         if query.contains('/') {
             let parts: Vec<&str> = query.splitn(2, '/').collect();
@@ -557,6 +617,11 @@ Provide the refactored code with explanations."#;
             return Ok(query.to_string());
         }
 
+        // Then a persisted short code
+        if let Some(name) = self.resolve_short_code(query) {
+            return Ok(name);
+        }
+
         // Fuzzy match across all prompts
         let all_prompts = self.list_prompts()?;
         let fuzzy = SkimMatcherV2::default();
@@ -580,6 +645,10 @@ Provide the refactored code with explanations."#;
     }
 
     pub fn read_prompt(&self, name: &str) -> Result<(PromptMetadata, String)> {
+        if let (base, Some(selector)) = split_version(name) {
+            return self.read_prompt_version(base, selector);
+        }
+
         let path = self.prompt_path(name);
         let content = match fs::read_to_string(&path) {
             Ok(content) => content,
@@ -606,11 +675,19 @@ Provide the refactored code with explanations."#;
 
     /// Fast prompt resolution that avoids loading all prompts
     pub fn resolve_prompt_fast(&self, query: &str) -> Result<String> {
+        if let (base, Some(selector)) = split_version(query) {
+            return Ok(with_version(&self.resolve_prompt_fast(base)?, Some(selector)));
+        }
+
         // Try exact match first - this is the fastest path
         if self.prompt_exists(query) {
             return Ok(query.to_string());
         }
 
+        if crate::matching::exact_matching_enabled() {
+            return self.resolve_exact(query);
+        }
+
         // If query contains bank syntax, try exact bank/prompt match
         if query.contains('/') {
             let parts: Vec<&str> = query.splitn(2, '/').collect();
@@ -646,6 +723,29 @@ Provide the refactored code with explanations."#;
         self.resolve_prompt(query)
     }
 
+    /// Resolve a query only if it names an existing prompt exactly
+    fn resolve_exact(&self, query: &str) -> Result<String> {
+        if self.prompt_exists(query) {
+            Ok(query.to_string())
+        } else if let Some(name) = self.resolve_short_code(query) {
+            Ok(name)
+        } else {
+            Err(anyhow!(
+                "No prompt named '{}' (exact matching is enabled)",
+                query
+            ))
+        }
+    }
+
+    /// Look up a persisted short code, ignoring codes of deleted prompts
+    fn resolve_short_code(&self, query: &str) -> Option<String> {
+        let registry = crate::short_codes::ShortCodeRegistry::load(self).ok()?;
+        registry
+            .prompt_for(query)
+            .filter(|name| self.prompt_exists(name))
+            .map(|name| name.to_string())
+    }
+
     /// Helper for fuzzy matching within a specific list
     fn fuzzy_match_in_list(&self, prompts: &[String], query: &str) -> Option<String> {
         use fuzzy_matcher::skim::SkimMatcherV2;
@@ -669,6 +769,7 @@ Provide the refactored code with explanations."#;
     }
 
     pub fn write_prompt(&self, name: &str, metadata: &PromptMetadata, body: &str) -> Result<()> {
+        ensure_writable(name)?;
         let path = self.prompt_path(name);
 
         // Ensure the parent directory exists
@@ -684,8 +785,15 @@ Provide the refactored code with explanations."#;
         // Format as markdown with frontmatter
         let content = format!("---\n{}---\n\n{}", yaml_metadata, body);
 
-        match fs::write(&path, content) {
-            Ok(_) => Ok(()),
+        // Keep whatever is on disk now in the revision history
+        self.snapshot_revision(name)?;
+
+        match fs::write(&path, &content) {
+            Ok(_) => {
+                self.revisions()
+                    .record(name, &content, crate::revisions::current_operation())?;
+                Ok(())
+            }
             Err(e) => {
                 if e.kind() == std::io::ErrorKind::PermissionDenied {
                     use crate::error_help;
@@ -703,6 +811,7 @@ Provide the refactored code with explanations."#;
     }
 
     pub fn delete_prompt(&self, name: &str) -> Result<()> {
+        ensure_writable(name)?;
         let path = self.prompt_path(name);
         if !path.exists() {
             return Err(anyhow!("Prompt '{}' does not exist", name));
@@ -749,6 +858,7 @@ Provide the refactored code with explanations."#;
         }
 
         fs::rename(old_path, new_path)?;
+        self.revisions().rename_bank(old_name, new_name)?;
         Ok(())
     }
 
@@ -786,6 +896,7 @@ Provide the refactored code with explanations."#;
                 version: None,
                 git_hash: None,
                 parent_version: None,
+                source: None,
             };
 
             Ok((metadata, content.to_string()))
@@ -793,6 +904,10 @@ Provide the refactored code with explanations."#;
     }
 
     pub fn read_prompt_metadata(&self, name: &str) -> Result<PromptMetadata> {
+        if split_version(name).1.is_some() {
+            return self.read_prompt(name).map(|(metadata, _)| metadata);
+        }
+
         let prompt_path = self.prompt_path(name);
         let content = fs::read_to_string(&prompt_path)
             .with_context(|| format!("Failed to read prompt: {}", name))?;
@@ -802,6 +917,7 @@ Provide the refactored code with explanations."#;
     }
 
     pub fn write_prompt_metadata(&self, name: &str, metadata: &PromptMetadata) -> Result<()> {
+        ensure_writable(name)?;
         let prompt_path = self.prompt_path(name);
         let content = fs::read_to_string(&prompt_path)
             .with_context(|| format!("Failed to read prompt: {}", name))?;
@@ -811,8 +927,11 @@ Provide the refactored code with explanations."#;
         // Reconstruct the file with updated metadata
         let new_content = format!("---\n{}---\n\n{}", serde_yaml::to_string(metadata)?, body);
 
-        fs::write(&prompt_path, new_content)
+        self.snapshot_revision(name)?;
+        fs::write(&prompt_path, &new_content)
             .with_context(|| format!("Failed to write prompt: {}", name))?;
+        self.revisions()
+            .record(name, &new_content, crate::revisions::current_operation())?;
 
         Ok(())
     }
@@ -949,4 +1068,28 @@ Design a REST API with these requirements:
         assert_eq!(metadata.description, "REST API design");
         assert!(body.contains("Design a REST API"));
     }
+
+    #[test]
+    fn test_versioned_names_read_history_without_touching_live_file() {
+        assert_eq!(split_version("review@v1.0"), ("review", Some("v1.0")));
+        assert_eq!(split_version("@team/review"), ("@team/review", None));
+        assert_eq!(split_version("@team/review@~2"), ("@team/review", Some("~2")));
+        assert!(!is_file_reference("review@v1.0"));
+        assert!(is_file_reference("notes.md"));
+
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new_with_base(temp.path().to_path_buf()).unwrap();
+        let (metadata, _) = storage.parse_prompt("plain").unwrap();
+        for body in ["one", "two", "three"] {
+            storage.write_prompt("review", &metadata, body).unwrap();
+        }
+        storage.revisions().set_tag("review", "stable", 1, None).unwrap();
+
+        assert_eq!(storage.read_prompt("review@stable").unwrap().1, "one");
+        assert_eq!(storage.read_prompt("review@~1").unwrap().1, "two");
+        assert_eq!(storage.resolve_prompt("rev@r3").unwrap(), "review@r3");
+        assert!(storage.read_prompt("review@v9").is_err());
+        assert!(storage.write_prompt("review@stable", &metadata, "x").is_err());
+        assert_eq!(storage.read_prompt("review").unwrap().1, "three");
+    }
 }
@@ -20,6 +20,12 @@ pub struct SyncMetadata {
     pub prompt_name: String,
     /// SHA-256 hash of last synced content
     pub content_hash: String,
+    /// Hash of the file PromptHive would write for the prompt at the last sync
+    ///
+    /// Links without one were last written from the prompt, so `content_hash`
+    /// stands in for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_hash: Option<String>,
     /// Timestamp of last successful sync
     pub last_sync: DateTime<Utc>,
     /// Timestamp when sync relationship was created
@@ -89,6 +95,7 @@ impl SyncRegistry {
             local_path: local_path.clone(),
             prompt_name: prompt_name.clone(),
             content_hash,
+            prompt_hash: None,
             last_sync: Utc::now(),
             created: Utc::now(),
             active: true,
@@ -138,9 +145,36 @@ impl SyncRegistry {
     pub fn update_sync(&mut self, prompt_name: &str, content_hash: String) {
         if let Some(sync_meta) = self.syncs.get_mut(prompt_name) {
             sync_meta.content_hash = content_hash;
+            sync_meta.prompt_hash = None;
             sync_meta.last_sync = Utc::now();
         }
     }
+
+    /// Record the state of both sides after they were brought in line
+    pub fn update_hashes(&mut self, prompt_name: &str, content_hash: String, prompt_hash: String) {
+        if let Some(sync_meta) = self.syncs.get_mut(prompt_name) {
+            sync_meta.content_hash = content_hash;
+            sync_meta.prompt_hash = Some(prompt_hash);
+            sync_meta.last_sync = Utc::now();
+        }
+    }
+}
+
+/// What [`SyncManager::reconcile`] found for one synced prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    /// Neither side changed since the last sync
+    Unchanged,
+    /// The file changed and was copied into PromptHive
+    FromFile,
+    /// The prompt changed and was written to the file
+    ToFile,
+    /// Both sides changed differently; neither was overwritten
+    Conflict,
+    /// The synced file no longer exists
+    MissingFile,
+    /// The prompt no longer exists
+    MissingPrompt,
 }
 
 /// Core bidirectional sync manager
@@ -180,7 +214,7 @@ impl SyncManager {
                 // Smart default: ./prompt-name.md (with team prefix stripping)
                 let file_name = if prompt_name.starts_with('@') {
                     // Strip team prefix: @team/api-design -> api-design.md
-                    prompt_name.split('/').last().unwrap_or(prompt_name)
+                    prompt_name.split('/').next_back().unwrap_or(prompt_name)
                 } else {
                     prompt_name
                 };
@@ -211,13 +245,7 @@ impl SyncManager {
         let (metadata, content) = self.storage.read_prompt(prompt_name)?;
         
         // Create file content with frontmatter
-        let file_content = format!(
-            "---\nprompt: {}\ndescription: {}\ntags: {}\n---\n\n{}",
-            prompt_name,
-            metadata.description,
-            metadata.tags.unwrap_or_default().join(", "),
-            content
-        );
+        let file_content = render_file_content(prompt_name, &metadata, &content);
         
         // Create directory if needed
         if let Some(parent) = resolved_path.parent() {
@@ -241,6 +269,81 @@ impl SyncManager {
         Ok(resolved_path)
     }
     
+    /// Register a file that was just written from a prompt
+    pub fn record_link(&mut self, prompt_name: &str, local_path: PathBuf) -> Result<()> {
+        let file_content = fs::read_to_string(&local_path)
+            .with_context(|| format!("Failed to read file {:?}", local_path))?;
+        self.registry.add_sync(
+            prompt_name.to_string(),
+            local_path,
+            calculate_content_hash(&file_content),
+        );
+        self.registry.save(&self.storage)
+    }
+
+    /// Bring a synced file and its prompt in line, whichever side changed
+    ///
+    /// Each side's hash is compared with the one recorded at the last sync. A
+    /// change on one side is copied to the other; changes on both sides are a
+    /// conflict unless they left the same body.
+    pub fn reconcile(&mut self, prompt_name: &str) -> Result<SyncAction> {
+        let sync_meta = self.registry.get_sync(prompt_name)
+            .ok_or_else(|| anyhow::anyhow!("Prompt '{}' is not synced", prompt_name))?
+            .clone();
+
+        if !self.storage.prompt_exists(prompt_name) {
+            return Ok(SyncAction::MissingPrompt);
+        }
+        if !sync_meta.local_path.exists() {
+            return Ok(SyncAction::MissingFile);
+        }
+
+        let file_content = fs::read_to_string(&sync_meta.local_path)
+            .with_context(|| format!("Failed to read file {:?}", sync_meta.local_path))?;
+        let (metadata, content) = self.storage.read_prompt(prompt_name)?;
+        let rendered = render_file_content(prompt_name, &metadata, &content);
+
+        let file_changed = calculate_content_hash(&file_content) != sync_meta.content_hash;
+        let last_prompt_hash = sync_meta.prompt_hash.as_ref().unwrap_or(&sync_meta.content_hash);
+        let prompt_changed = calculate_content_hash(&rendered) != *last_prompt_hash;
+
+        let action = match (file_changed, prompt_changed) {
+            (false, false) => return Ok(SyncAction::Unchanged),
+            (true, false) => {
+                // Keep the prompt's own metadata; the file carries description and tags
+                let (file_metadata, body) = parse_file_content(&file_content)?;
+                let mut metadata = metadata;
+                metadata.description = file_metadata.description;
+                metadata.tags = file_metadata.tags;
+                metadata.updated_at = Some(Utc::now().to_rfc3339());
+                self.storage.write_prompt(prompt_name, &metadata, &body)?;
+                SyncAction::FromFile
+            }
+            (false, true) => {
+                fs::write(&sync_meta.local_path, &rendered)
+                    .with_context(|| format!("Failed to write to file {:?}", sync_meta.local_path))?;
+                SyncAction::ToFile
+            }
+            (true, true) => {
+                let (_, body) = parse_file_content(&file_content)?;
+                if body.trim() != content.trim() {
+                    return Ok(SyncAction::Conflict);
+                }
+                SyncAction::Unchanged
+            }
+        };
+
+        let file_content = fs::read_to_string(&sync_meta.local_path)?;
+        let (metadata, content) = self.storage.read_prompt(prompt_name)?;
+        self.registry.update_hashes(
+            prompt_name,
+            calculate_content_hash(&file_content),
+            calculate_content_hash(&render_file_content(prompt_name, &metadata, &content)),
+        );
+        self.registry.save(&self.storage)?;
+        Ok(action)
+    }
+
     /// Remove sync relationship
     pub fn unsync_prompt(&mut self, prompt_name: &str) -> Result<Option<PathBuf>> {
         if let Some(sync_meta) = self.registry.remove_sync(prompt_name) {
@@ -299,13 +402,7 @@ impl SyncManager {
         let (metadata, content) = self.storage.read_prompt(prompt_name)?;
         
         // Create file content
-        let file_content = format!(
-            "---\nprompt: {}\ndescription: {}\ntags: {}\n---\n\n{}",
-            prompt_name,
-            metadata.description,
-            metadata.tags.unwrap_or_default().join(", "),
-            content
-        );
+        let file_content = render_file_content(prompt_name, &metadata, &content);
         
         // Calculate hash
         let new_hash = calculate_content_hash(&file_content);
@@ -387,6 +484,17 @@ pub enum SyncStatusType {
     BrokenSync,
 }
 
+/// The synced file for a prompt: a small frontmatter header and the body
+pub fn render_file_content(prompt_name: &str, metadata: &crate::PromptMetadata, content: &str) -> String {
+    format!(
+        "---\nprompt: {}\ndescription: {}\ntags: {}\n---\n\n{}",
+        prompt_name,
+        metadata.description,
+        metadata.tags.clone().unwrap_or_default().join(", "),
+        content
+    )
+}
+
 /// Calculate SHA-256 hash of content for change detection
 fn calculate_content_hash(content: &str) -> String {
     use std::collections::hash_map::DefaultHasher;
@@ -412,6 +520,7 @@ fn parse_file_content(content: &str) -> Result<(crate::PromptMetadata, String)>
             version: None,
             git_hash: None,
             parent_version: None,
+            source: None,
         };
         return Ok((metadata, content.to_string()));
     }
@@ -440,7 +549,7 @@ fn parse_file_content(content: &str) -> Result<(crate::PromptMetadata, String)>
                 "prompt" => prompt_name = value.to_string(),
                 "description" => description = value.to_string(),
                 "tags" => {
-                    tags = value.split(',').map(|s| s.trim().to_string()).collect();
+                    tags = value.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
                 }
                 _ => {} // Ignore unknown fields
             }
@@ -456,6 +565,7 @@ fn parse_file_content(content: &str) -> Result<(crate::PromptMetadata, String)>
         version: None,
         git_hash: None,
         parent_version: None,
+        source: None,
     };
     
     // Extract content (everything after frontmatter)
@@ -463,4 +573,66 @@ fn parse_file_content(content: &str) -> Result<(crate::PromptMetadata, String)>
     let content = content_lines.join("\n").trim().to_string();
     
     Ok((metadata, content))
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Storage;
+
+    fn linked(temp: &tempfile::TempDir) -> (SyncManager, PathBuf) {
+        let storage = Storage::new_with_base(temp.path().join("base")).unwrap();
+        let (metadata, _) = storage.parse_prompt_content("original").unwrap();
+        storage.write_prompt("notes", &metadata, "original").unwrap();
+
+        let file = temp.path().join("notes.md");
+        let (metadata, content) = storage.read_prompt("notes").unwrap();
+        fs::write(&file, render_file_content("notes", &metadata, &content)).unwrap();
+
+        let mut manager = SyncManager::new(storage).unwrap();
+        manager.record_link("notes", file.clone()).unwrap();
+        (manager, file)
+    }
+
+    fn edit_prompt(manager: &SyncManager, body: &str) {
+        let (metadata, _) = manager.storage.read_prompt("notes").unwrap();
+        manager.storage.write_prompt("notes", &metadata, body).unwrap();
+    }
+
+    #[test]
+    fn test_reconcile_copies_whichever_side_changed() {
+        let temp = tempfile::tempdir().unwrap();
+        let (mut manager, file) = linked(&temp);
+        assert_eq!(manager.reconcile("notes").unwrap(), SyncAction::Unchanged);
+
+        let edited = fs::read_to_string(&file).unwrap().replace("original", "from the file");
+        fs::write(&file, edited).unwrap();
+        assert_eq!(manager.reconcile("notes").unwrap(), SyncAction::FromFile);
+        assert_eq!(manager.storage.read_prompt("notes").unwrap().1, "from the file");
+        assert_eq!(manager.reconcile("notes").unwrap(), SyncAction::Unchanged);
+
+        edit_prompt(&manager, "from the library");
+        assert_eq!(manager.reconcile("notes").unwrap(), SyncAction::ToFile);
+        assert!(fs::read_to_string(&file).unwrap().ends_with("from the library"));
+        assert_eq!(manager.reconcile("notes").unwrap(), SyncAction::Unchanged);
+    }
+
+    #[test]
+    fn test_reconcile_reports_conflicts_without_writing() {
+        let temp = tempfile::tempdir().unwrap();
+        let (mut manager, file) = linked(&temp);
+
+        let edited = fs::read_to_string(&file).unwrap().replace("original", "file edit");
+        fs::write(&file, &edited).unwrap();
+        edit_prompt(&manager, "library edit");
+
+        assert_eq!(manager.reconcile("notes").unwrap(), SyncAction::Conflict);
+        assert_eq!(fs::read_to_string(&file).unwrap(), edited);
+        assert_eq!(manager.storage.read_prompt("notes").unwrap().1, "library edit");
+
+        // Making both sides agree settles the conflict
+        edit_prompt(&manager, "file edit");
+        assert_eq!(manager.reconcile("notes").unwrap(), SyncAction::Unchanged);
+        fs::remove_file(&file).unwrap();
+        assert_eq!(manager.reconcile("notes").unwrap(), SyncAction::MissingFile);
+    }
+}
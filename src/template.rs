@@ -5,7 +5,7 @@
 //! and custom user-defined variables.
 
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::process::Command;
 
@@ -125,6 +125,30 @@ impl TemplateProcessor {
         &self.custom_variables
     }
 
+    /// Resolve the variables a template references, for recording in history
+    ///
+    /// Input placeholders are skipped since the input is recorded separately.
+    /// Placeholders that are not variables (e.g. JSON braces) are ignored.
+    pub fn resolve_variables(&self, template: &str) -> Result<BTreeMap<String, String>> {
+        let placeholder_regex = regex::Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*(?::[^}]+)?)\}").unwrap();
+        let mut vars = BTreeMap::new();
+
+        for caps in placeholder_regex.captures_iter(template) {
+            let name = &caps[1];
+            if matches!(name, "input" | "INPUT" | "content" | "CONTENT") || vars.contains_key(name) {
+                continue;
+            }
+
+            let placeholder = format!("{{{}}}", name);
+            let value = self.process(&placeholder, "")?;
+            if value != placeholder {
+                vars.insert(name.to_string(), value);
+            }
+        }
+
+        Ok(vars)
+    }
+
     /// Process legacy input variables for backward compatibility
     fn process_input_variables(&self, template: &str, input: &str) -> String {
         let mut result = template.to_string();
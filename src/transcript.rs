@@ -0,0 +1,498 @@
+//! Transcript parsing for AI chat logs
+//!
+//! Splits a conversation into turns so a good exchange can be saved as a
+//! prompt. Three shapes are understood: terminal captures of AI CLIs (`>`
+//! prompts and `⏺` replies), JSONL session files (Claude Code sessions and
+//! plain `{"role", "content"}` lines, or a JSON array of them) and markdown
+//! exports with `## User` / `**Assistant:**` headers.
+
+use crate::clean::{clean_with, is_cli_chrome, CleanMode, CleanOptions};
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+// "## User", "### Assistant:"
+static MD_HEADING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^#{1,6}\s+\**(user|human|you|assistant|claude|chatgpt|gpt|ai|model|system|tool)\**\s*:?\s*$")
+        .unwrap()
+});
+
+// "**User:** text", "**Assistant**: text"
+static MD_BOLD_LABEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\*\*(user|human|you|assistant|claude|chatgpt|gpt|ai|model|system|tool)(?::\*\*|\*\*\s*:)\s*(.*)$")
+        .unwrap()
+});
+
+// "Human: text", "Assistant: text"
+static MD_PLAIN_LABEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(User|Human|Assistant|AI|Claude|ChatGPT|System):\s*(.*)$").unwrap()
+});
+
+// "> prompt", "⏺ reply", "⎿ tool output"
+static TERMINAL_MARKER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*([>❯›]|⏺|●|⎿)(?:\s|$)(.*)$").unwrap());
+
+// "⏺ Bash(ls -la)" - a tool call rather than prose
+static TOOL_CALL: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Z][A-Za-z]*\(.*\)?$").unwrap());
+
+/// Who produced a turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+    System,
+    Tool,
+}
+
+impl Role {
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+            Role::Tool => "tool",
+        }
+    }
+
+    /// Map the labels used by different tools onto a role
+    fn from_label(label: &str) -> Option<Role> {
+        match label.to_lowercase().as_str() {
+            "user" | "human" | "you" | "me" => Some(Role::User),
+            "assistant" | "claude" | "chatgpt" | "gpt" | "ai" | "model" => Some(Role::Assistant),
+            "system" | "developer" => Some(Role::System),
+            "tool" | "function" => Some(Role::Tool),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Role::from_label(s)
+            .ok_or_else(|| anyhow!("Unknown role '{}'. Use: user, assistant, system, tool", s))
+    }
+}
+
+/// One message in a conversation
+#[derive(Debug, Clone, Serialize)]
+pub struct Turn {
+    /// 1-based position in the whole transcript
+    pub index: usize,
+    pub role: Role,
+    pub content: String,
+}
+
+/// Shape of a transcript
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Terminal,
+    Jsonl,
+    Markdown,
+}
+
+impl TranscriptFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            TranscriptFormat::Terminal => "terminal",
+            TranscriptFormat::Jsonl => "jsonl",
+            TranscriptFormat::Markdown => "markdown",
+        }
+    }
+
+    /// Guess the format from the content
+    pub fn detect(content: &str) -> Self {
+        let trimmed = content.trim_start();
+        let first_line = trimmed.lines().next().unwrap_or("");
+        if serde_json::from_str::<Value>(first_line).is_ok_and(|v| v.is_object())
+            || (trimmed.starts_with('[') && serde_json::from_str::<Vec<Value>>(trimmed).is_ok())
+        {
+            return TranscriptFormat::Jsonl;
+        }
+        if content.lines().any(|line| markdown_header(line).is_some()) {
+            return TranscriptFormat::Markdown;
+        }
+        TranscriptFormat::Terminal
+    }
+}
+
+impl std::str::FromStr for TranscriptFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "terminal" | "term" => Ok(TranscriptFormat::Terminal),
+            "jsonl" | "json" => Ok(TranscriptFormat::Jsonl),
+            "markdown" | "md" => Ok(TranscriptFormat::Markdown),
+            _ => Err(anyhow!(
+                "Unknown transcript format '{}'. Use: auto, terminal, jsonl, markdown",
+                s
+            )),
+        }
+    }
+}
+
+/// Split a transcript into numbered turns
+pub fn parse_transcript(content: &str, format: TranscriptFormat) -> Vec<Turn> {
+    let turns = match format {
+        TranscriptFormat::Terminal => parse_terminal(content),
+        TranscriptFormat::Jsonl => parse_jsonl(content),
+        TranscriptFormat::Markdown => parse_markdown(content),
+    };
+    turns
+        .into_iter()
+        .filter(|(_, content)| !content.is_empty())
+        .enumerate()
+        .map(|(i, (role, content))| Turn {
+            index: i + 1,
+            role,
+            content,
+        })
+        .collect()
+}
+
+/// Pick turns by role, then by position among the remaining turns
+///
+/// `spec` is a comma-separated list of 1-based positions (`2`), ranges
+/// (`3-5`, `2..`, `-3..-1`) and negative positions counted from the end
+/// (`-1` is the last turn).
+pub fn select_turns<'a>(
+    turns: &'a [Turn],
+    roles: &[Role],
+    spec: Option<&str>,
+) -> Result<Vec<&'a Turn>> {
+    let filtered: Vec<&Turn> = turns
+        .iter()
+        .filter(|turn| roles.is_empty() || roles.contains(&turn.role))
+        .collect();
+    let Some(spec) = spec else {
+        return Ok(filtered);
+    };
+
+    let count = filtered.len() as i64;
+    let resolve = |n: i64| -> Result<usize> {
+        let position = if n < 0 { count + 1 + n } else { n };
+        if n == 0 || position < 1 || position > count {
+            return Err(anyhow!("Turn {} out of range (1-{})", n, count));
+        }
+        Ok(position as usize)
+    };
+    let number = |s: &str| -> Result<i64> {
+        s.trim()
+            .parse()
+            .map_err(|_| anyhow!("Invalid turn '{}'. Use positions like 1,3-5,-1", s))
+    };
+
+    let mut selected: Vec<&Turn> = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (from, to) = if let Some((a, b)) = part.split_once("..") {
+            let from = if a.is_empty() { 1 } else { resolve(number(a)?)? };
+            let to = if b.is_empty() { count.max(1) as usize } else { resolve(number(b)?)? };
+            (from, to)
+        } else if let Some((a, b)) = part.split_once('-').filter(|(a, _)| !a.is_empty()) {
+            (resolve(number(a)?)?, resolve(number(b)?)?)
+        } else {
+            let position = resolve(number(part)?)?;
+            (position, position)
+        };
+        for turn in filtered.iter().take(to).skip(from - 1) {
+            if !selected.iter().any(|t| t.index == turn.index) {
+                selected.push(turn);
+            }
+        }
+    }
+    Ok(selected)
+}
+
+/// Render selected turns, with role headings when more than one role is present
+pub fn render_turns(turns: &[&Turn]) -> String {
+    let single_role = turns.windows(2).all(|pair| pair[0].role == pair[1].role);
+    turns
+        .iter()
+        .map(|turn| {
+            if single_role {
+                turn.content.clone()
+            } else {
+                let mut heading = turn.role.name().to_string();
+                heading[..1].make_ascii_uppercase();
+                format!("## {}\n\n{}", heading, turn.content)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn markdown_header(line: &str) -> Option<(Role, String)> {
+    if let Some(caps) = MD_HEADING.captures(line) {
+        return Role::from_label(&caps[1]).map(|role| (role, String::new()));
+    }
+    let caps = MD_BOLD_LABEL
+        .captures(line)
+        .or_else(|| MD_PLAIN_LABEL.captures(line))?;
+    Role::from_label(&caps[1]).map(|role| (role, caps[2].to_string()))
+}
+
+fn parse_markdown(content: &str) -> Vec<(Role, String)> {
+    let mut turns: Vec<(Role, Vec<String>)> = Vec::new();
+    let mut in_fence = false;
+
+    for line in content.lines() {
+        let is_fence = line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~");
+        if !in_fence && !is_fence {
+            if let Some((role, rest)) = markdown_header(line) {
+                turns.push((role, vec![rest]));
+                continue;
+            }
+        }
+        if is_fence {
+            in_fence = !in_fence;
+        }
+        // Text before the first header (titles, export banners) is dropped
+        if let Some((_, lines)) = turns.last_mut() {
+            lines.push(line.to_string());
+        }
+    }
+
+    turns
+        .into_iter()
+        .map(|(role, mut lines)| {
+            // Separators between turns belong to neither side
+            while lines
+                .last()
+                .is_some_and(|l| matches!(l.trim(), "" | "---" | "***" | "___"))
+            {
+                lines.pop();
+            }
+            (role, tidy(&lines, false))
+        })
+        .collect()
+}
+
+fn parse_jsonl(content: &str) -> Vec<(Role, String)> {
+    let trimmed = content.trim();
+    let values: Vec<Value> = if trimmed.starts_with('[') {
+        serde_json::from_str(trimmed).unwrap_or_default()
+    } else {
+        trimmed
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    };
+    values.iter().filter_map(json_turn).collect()
+}
+
+/// Read one message from a JSONL entry
+///
+/// Claude Code sessions wrap the message (`{"type": "user", "message": {...}}`)
+/// and use content blocks; tool results sent back as user messages become
+/// tool turns. Plain `{"role", "content"}` entries are read directly.
+fn json_turn(entry: &Value) -> Option<(Role, String)> {
+    if entry.get("isMeta").and_then(Value::as_bool) == Some(true) {
+        return None;
+    }
+    let message = entry.get("message").unwrap_or(entry);
+    let label = message
+        .get("role")
+        .or_else(|| entry.get("type"))
+        .and_then(Value::as_str)?;
+    let mut role = Role::from_label(label)?;
+
+    let text = match message.get("content")? {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => {
+            let texts: Vec<String> = blocks.iter().filter_map(block_text).collect();
+            if texts.is_empty() {
+                let results: Vec<String> = blocks
+                    .iter()
+                    .filter(|b| b.get("type").and_then(Value::as_str) == Some("tool_result"))
+                    .filter_map(|b| match b.get("content")? {
+                        Value::String(text) => Some(text.clone()),
+                        Value::Array(inner) => Some(
+                            inner.iter().filter_map(block_text).collect::<Vec<_>>().join("\n"),
+                        ),
+                        _ => None,
+                    })
+                    .collect();
+                if !results.is_empty() {
+                    role = Role::Tool;
+                }
+                results.join("\n\n")
+            } else {
+                texts.join("\n\n")
+            }
+        }
+        _ => return None,
+    };
+    Some((role, text.trim().to_string()))
+}
+
+fn block_text(block: &Value) -> Option<String> {
+    match block {
+        Value::String(text) => Some(text.clone()),
+        _ if block.get("type").and_then(Value::as_str) == Some("text") => {
+            block.get("text").and_then(Value::as_str).map(str::to_string)
+        }
+        _ => None,
+    }
+}
+
+fn parse_terminal(content: &str) -> Vec<(Role, String)> {
+    let cleaned = clean_with(
+        content,
+        &CleanOptions {
+            mode: CleanMode::Code,
+            keep_blank_lines: true,
+        },
+    );
+    let mut turns: Vec<(Role, Vec<String>)> = Vec::new();
+
+    for line in cleaned.lines() {
+        if is_cli_chrome(line) {
+            continue;
+        }
+        if let Some(caps) = TERMINAL_MARKER.captures(line) {
+            let rest = caps[2].to_string();
+            let last_role = turns.last().map(|(role, _)| *role);
+            match &caps[1] {
+                "⏺" | "●" if TOOL_CALL.is_match(rest.trim()) => turns.push((Role::Tool, vec![rest])),
+                "⏺" | "●" if last_role == Some(Role::Assistant) => {
+                    // Consecutive reply blocks are one assistant turn
+                    let lines = &mut turns.last_mut().unwrap().1;
+                    lines.push(String::new());
+                    lines.push(rest);
+                }
+                "⏺" | "●" => turns.push((Role::Assistant, vec![rest])),
+                "⎿" if last_role == Some(Role::Tool) => turns.last_mut().unwrap().1.push(rest),
+                "⎿" => turns.push((Role::Tool, vec![rest])),
+                _ => turns.push((Role::User, vec![rest])),
+            }
+            continue;
+        }
+        // Banners before the first prompt are dropped
+        if let Some((_, lines)) = turns.last_mut() {
+            lines.push(line.to_string());
+        }
+    }
+
+    turns
+        .into_iter()
+        .map(|(role, lines)| (role, tidy(&lines, true)))
+        .collect()
+}
+
+/// Join turn lines, dropping surrounding blank lines
+///
+/// With `dedent_continuation`, lines after the first lose their common
+/// indentation (the width of the marker that started the turn).
+fn tidy(lines: &[String], dedent_continuation: bool) -> String {
+    let mut lines: Vec<String> = lines.iter().map(|l| l.trim_end().to_string()).collect();
+    if dedent_continuation && lines.len() > 1 {
+        let common = lines[1..]
+            .iter()
+            .filter(|l| !l.is_empty())
+            .map(|l| l.len() - l.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        for line in lines[1..].iter_mut().filter(|l| !l.is_empty()) {
+            line.drain(..common);
+        }
+    }
+    let first = lines.iter().position(|l| !l.is_empty());
+    let last = lines.iter().rposition(|l| !l.is_empty());
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].join("\n"),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_terminal_capture() {
+        let capture = "╭──────────────────────────────╮
+│ > Write a haiku about rust   │
+╰──────────────────────────────╯
+
+⏺ Read(poems.md)
+  ⎿  Read 12 lines
+
+⏺ Here it is:
+
+  Memory is safe
+    borrowed, never owned
+
+✻ Thinking… (esc to interrupt)
+
+> thanks";
+        let turns = parse_transcript(capture, TranscriptFormat::detect(capture));
+        let roles: Vec<Role> = turns.iter().map(|t| t.role).collect();
+        assert_eq!(roles, vec![Role::User, Role::Tool, Role::Assistant, Role::User]);
+        assert_eq!(turns[0].content, "Write a haiku about rust");
+        assert_eq!(
+            turns[2].content,
+            "Here it is:\n\nMemory is safe\n  borrowed, never owned"
+        );
+    }
+
+    #[test]
+    fn test_parse_jsonl_and_markdown() {
+        let jsonl = r#"{"type":"user","message":{"role":"user","content":"Review this diff"}}
+{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Looks good."},{"type":"tool_use","name":"Bash"}]}}
+{"type":"user","message":{"role":"user","content":[{"type":"tool_result","content":"ok"}]}}
+{"type":"summary","summary":"Code review"}"#;
+        assert_eq!(TranscriptFormat::detect(jsonl), TranscriptFormat::Jsonl);
+        let turns = parse_transcript(jsonl, TranscriptFormat::Jsonl);
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[1].content, "Looks good.");
+        assert_eq!(turns[2].role, Role::Tool);
+
+        let markdown = "# Chat export\n\n## User\n\nExplain lifetimes\n\n---\n\n**Assistant:** They tie references\nto scopes.\n\n```rust\n## User\n```";
+        assert_eq!(TranscriptFormat::detect(markdown), TranscriptFormat::Markdown);
+        let turns = parse_transcript(markdown, TranscriptFormat::Markdown);
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].content, "Explain lifetimes");
+        assert!(turns[1].content.ends_with("```rust\n## User\n```"));
+    }
+
+    #[test]
+    fn test_select_turns_by_role_and_position() {
+        let turns: Vec<Turn> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Turn {
+                index: i + 1,
+                role: if i % 2 == 0 { Role::User } else { Role::Assistant },
+                content: text.to_string(),
+            })
+            .collect();
+
+        let picked = |roles: &[Role], spec| {
+            select_turns(&turns, roles, spec)
+                .unwrap()
+                .iter()
+                .map(|t| t.content.as_str())
+                .collect::<Vec<_>>()
+                .join("")
+        };
+        assert_eq!(picked(&[Role::User], None), "ace");
+        assert_eq!(picked(&[Role::User], Some("-1")), "e");
+        assert_eq!(picked(&[], Some("2-3,5,2")), "bce");
+        assert_eq!(picked(&[], Some("-2..")), "de");
+        assert!(select_turns(&turns, &[Role::Assistant], Some("3")).is_err());
+
+        let selected = select_turns(&turns, &[], Some("1-2")).unwrap();
+        assert_eq!(render_turns(&selected), "## User\n\na\n\n## Assistant\n\nb");
+    }
+}
@@ -218,6 +218,7 @@ impl ClipboardEntry {
             version: None,
             git_hash: None,
             parent_version: None,
+            source: None,
         };
 
         Self {
@@ -13,12 +13,14 @@ use ratatui::{
     Frame, Terminal,
 };
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     io,
     time::{Duration, Instant},
 };
 
-use crate::{Clipboard, Storage};
+use crate::{
+    Clipboard, SecurityIssue, SecurityScanner, SecuritySeverity, ShortCodeRegistry, Storage,
+};
 
 pub mod banks;
 pub mod clipboard;
@@ -39,6 +41,49 @@ pub struct InstallPreview {
     pub install_options: InstallOptions,
     pub stage: InstallStage,
     pub cursor_index: usize, // Track selected prompt in dialog
+    pub findings: BTreeMap<String, Vec<SecurityIssue>>, // Security findings per prompt
+}
+
+impl InstallPreview {
+    /// Preview a bank install, scanning every prompt for security findings
+    ///
+    /// Prompts with critical findings start deselected.
+    pub fn for_bank(bank: &Bank) -> Self {
+        let mut findings = BTreeMap::new();
+        if let Ok(scanner) = SecurityScanner::new() {
+            for prompt in &bank.prompts {
+                let result = scanner.scan_content(&prompt.content, &prompt.name);
+                if !result.issues.is_empty() {
+                    findings.insert(prompt.name.clone(), result.issues);
+                }
+            }
+        }
+
+        let is_critical = |name: &str| {
+            findings.get(name).is_some_and(|issues: &Vec<SecurityIssue>| {
+                issues.iter().any(|i| i.severity == SecuritySeverity::Critical)
+            })
+        };
+        let selected_prompts: HashSet<String> = bank
+            .prompts
+            .iter()
+            .filter(|p| !is_critical(&p.name))
+            .map(|p| p.name.clone())
+            .collect();
+
+        Self {
+            install_options: InstallOptions {
+                install_all: selected_prompts.len() == bank.prompts.len(),
+                create_bank: true,
+                merge_with_existing: false,
+            },
+            bank: bank.clone(),
+            selected_prompts,
+            stage: InstallStage::Preview,
+            cursor_index: 0,
+            findings,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -76,6 +121,7 @@ pub struct PromptTui {
     pub new_bank_input: Option<NewBankInput>,
     waiting_for_bookmark_key: Option<String>, // Path to bookmark when key is pressed
     waiting_for_goto_key: bool, // Waiting for key to jump to bookmark
+    short_codes: ShortCodeRegistry,
 }
 
 #[derive(Debug, Clone)]
@@ -123,6 +169,7 @@ impl PromptTui {
             new_bank_input: None,
             waiting_for_bookmark_key: None,
             waiting_for_goto_key: false,
+            short_codes: ShortCodeRegistry::default(),
         };
 
         tui.load_data(storage)?;
@@ -141,6 +188,10 @@ impl PromptTui {
         let (banks, local_prompts) = Bank::load_all_banks(storage)?;
         self.banks = banks;
         self.local_prompts = local_prompts;
+
+        // Short codes are persisted, so the TUI shows the same codes as `ph ls`
+        let prompt_names = storage.list_prompts().unwrap_or_default();
+        self.short_codes = ShortCodeRegistry::load_for(storage, &prompt_names).unwrap_or_default();
         Ok(())
     }
 
@@ -323,7 +374,7 @@ impl PromptTui {
         }
 
         // Sort by score (highest first)
-        matches.sort_by(|a, b| b.0.cmp(&a.0));
+        matches.sort_by_key(|m| std::cmp::Reverse(m.0));
 
         // Add to filtered items
         for (_, item) in matches {
@@ -390,7 +441,7 @@ impl PromptTui {
         }
 
         // Sort by usage (highest first)
-        recent_items.sort_by(|a, b| b.0.cmp(&a.0));
+        recent_items.sort_by_key(|item| std::cmp::Reverse(item.0));
 
         // Take top 20
         for (_, item) in recent_items.into_iter().take(20) {
@@ -1247,11 +1298,10 @@ impl PromptTui {
             KeyCode::Char('Z') => self.sort_by_modified(), // Using Z for time-based sorting
 
             // Registry actions
-            KeyCode::Char('i') => {
-                if self.state.view_mode == ViewMode::Registry {
+            KeyCode::Char('i')
+                if self.state.view_mode == ViewMode::Registry => {
                     self.handle_install_preview()?;
                 }
-            }
 
             _ => {}
         }
@@ -1264,19 +1314,16 @@ impl PromptTui {
                 match item {
                     TreeItem::Bank { bank, .. } => {
                         // Create install preview for the selected bank
-                        let preview = InstallPreview {
-                            bank: bank.clone(),
-                            selected_prompts: bank.prompts.iter().map(|p| p.name.clone()).collect(),
-                            install_options: InstallOptions {
-                                install_all: true,
-                                create_bank: true,
-                                merge_with_existing: false,
-                            },
-                            stage: InstallStage::Preview,
-                            cursor_index: 0,
-                        };
+                        let preview = InstallPreview::for_bank(bank);
+                        if preview.findings.is_empty() {
+                            self.set_status("Press Enter to confirm, Esc to cancel");
+                        } else {
+                            self.set_status(&format!(
+                                "⚠ Security findings in {} prompt(s), review before installing",
+                                preview.findings.len()
+                            ));
+                        }
                         self.install_preview = Some(preview);
-                        self.set_status("Press Enter to confirm, Esc to cancel");
                     }
                     _ => {
                         self.set_status("Select a bank to install");
@@ -1481,8 +1528,14 @@ impl PromptTui {
                     String::new()
                 };
 
+                let short_code = self
+                    .short_codes
+                    .code_for(&prompt.full_name())
+                    .map(|code| format!(" [{}]", code))
+                    .unwrap_or_default();
+
                 let content = format!(
-                    "{}{}{}{}{}{}  {} - {}",
+                    "{}{}{}{}{}{}  {}{} - {}",
                     indent,
                     selected_indicator,
                     favorite_indicator,
@@ -1490,6 +1543,7 @@ impl PromptTui {
                     cut_indicator,
                     bank_prefix,
                     prompt.name,
+                    short_code,
                     prompt.description
                 );
 
@@ -1645,6 +1699,13 @@ impl PromptTui {
             ]),
         ];
 
+        if let Some(code) = self.short_codes.code_for(&prompt.full_name()) {
+            content.push(Line::from(vec![
+                Span::styled("Short code: ", Style::default().fg(Color::Rgb(140, 150, 160))),
+                Span::styled(code.to_string(), Style::default().fg(Color::Rgb(200, 205, 215))),
+            ]));
+        }
+
         // Add bank info if applicable
         if let Some(bank_name) = &prompt.bank_name {
             content.push(Line::from(vec![
@@ -2330,6 +2391,7 @@ impl PromptTui {
                                     version: None,
                                     git_hash: None,
                                     parent_version: None,
+                                    source: None,
                                 },
                                 prompt.bank_name.clone(),
                             ));
@@ -2368,6 +2430,7 @@ impl PromptTui {
                                     version: None,
                                     git_hash: None,
                                     parent_version: None,
+                                    source: None,
                                 },
                                 prompt.bank_name.clone(),
                             ));
@@ -2415,7 +2478,7 @@ impl PromptTui {
                                     name: prompt.name.clone(),
                                     bank: prompt.bank_name.clone(),
                                     content: prompt.content.clone(),
-                                    metadata: crate::storage::PromptMetadata {
+                                    metadata: Box::new(crate::storage::PromptMetadata {
                                         id: prompt.name.clone(),
                                         description: prompt.description.clone(),
                                         tags: if prompt.tags.is_empty() {
@@ -2428,7 +2491,8 @@ impl PromptTui {
                                         version: None,
                                         git_hash: None,
                                         parent_version: None,
-                                    },
+                                        source: None,
+                                    }),
                                 });
 
                                 // Remember current selection position
@@ -2505,6 +2569,7 @@ impl PromptTui {
                                     version: None,
                                     git_hash: None,
                                     parent_version: None,
+                                    source: None,
                                 },
                                 prompt.bank_name.clone(),
                             ));
@@ -2543,6 +2608,7 @@ impl PromptTui {
                                     version: None,
                                     git_hash: None,
                                     parent_version: None,
+                                    source: None,
                                 },
                                 prompt.bank_name.clone(),
                             ));
@@ -2604,19 +2670,23 @@ impl PromptTui {
                             item.name.clone()
                         };
 
+                        let original_name = if let Some(ref source_bank) = item.source_bank {
+                            format!("{}/{}", source_bank, item.name)
+                        } else {
+                            item.name.clone()
+                        };
+
+                        // A moved prompt keeps its version history
+                        if clipboard.is_cut() && original_name != target_name {
+                            storage.move_revisions(&original_name, &target_name)?;
+                        }
+
                         // Write prompt to target location
                         storage.write_prompt(&target_name, &item.metadata, &item.content)?;
                         pasted_items.push(target_name.clone());
 
                         // If this was a cut operation, delete the original and add to undo stack
                         if clipboard.is_cut() {
-                            // Delete the original prompt
-                            let original_name = if let Some(ref source_bank) = item.source_bank {
-                                format!("{}/{}", source_bank, item.name)
-                            } else {
-                                item.name.clone()
-                            };
-
                             // Only delete if source is different from target
                             if original_name != target_name {
                                 storage.delete_prompt(&original_name)?;
@@ -2816,6 +2886,8 @@ impl PromptTui {
                                     Ok((mut metadata, content)) => {
                                         // Update metadata with new name
                                         metadata.id = new_name.clone();
+                                        let _ = storage
+                                            .move_revisions(&original_full_name, &new_full_name);
 
                                         // Write prompt with new name
                                         match storage.write_prompt(
@@ -2827,6 +2899,13 @@ impl PromptTui {
                                                 // Delete old prompt
                                                 match storage.delete_prompt(&original_full_name) {
                                                     Ok(_) => {
+                                                        // Keep the short code with the prompt
+                                                        self.short_codes.rename_prompt(
+                                                            &original_full_name,
+                                                            &new_full_name,
+                                                        );
+                                                        let _ = self.short_codes.save(storage);
+
                                                         self.status_message = Some((
                                                             format!(
                                                                 "✓ Renamed prompt '{}' to '{}'",
@@ -2858,6 +2937,10 @@ impl PromptTui {
                                                 }
                                             }
                                             Err(e) => {
+                                                let _ = storage.revisions().move_history(
+                                                    &new_full_name,
+                                                    &original_full_name,
+                                                );
                                                 self.status_message = Some((
                                                     format!("Failed to create new prompt: {}", e),
                                                     Instant::now(),
@@ -3183,9 +3266,9 @@ impl PromptTui {
                                     .collect();
                             }
                         }
-                        KeyCode::Char(' ') => {
+                        KeyCode::Char(' ')
                             // Toggle individual prompt selection
-                            if preview.cursor_index < preview.bank.prompts.len() {
+                            if preview.cursor_index < preview.bank.prompts.len() => {
                                 let prompt_name =
                                     preview.bank.prompts[preview.cursor_index].name.clone();
                                 if preview.selected_prompts.contains(&prompt_name) {
@@ -3201,7 +3284,6 @@ impl PromptTui {
                                     }
                                 }
                             }
-                        }
                         KeyCode::Char('b') => {
                             // Toggle create bank option
                             preview.install_options.create_bank =
@@ -3212,18 +3294,16 @@ impl PromptTui {
                             preview.install_options.merge_with_existing =
                                 !preview.install_options.merge_with_existing;
                         }
-                        KeyCode::Down | KeyCode::Char('j') => {
+                        KeyCode::Down | KeyCode::Char('j')
                             // Move cursor down in prompt list
-                            if preview.cursor_index < preview.bank.prompts.len() - 1 {
+                            if preview.cursor_index < preview.bank.prompts.len() - 1 => {
                                 preview.cursor_index += 1;
                             }
-                        }
-                        KeyCode::Up | KeyCode::Char('k') => {
+                        KeyCode::Up | KeyCode::Char('k')
                             // Move cursor up in prompt list
-                            if preview.cursor_index > 0 {
+                            if preview.cursor_index > 0 => {
                                 preview.cursor_index -= 1;
                             }
-                        }
                         _ => {}
                     }
                 }
@@ -3308,6 +3388,7 @@ impl PromptTui {
                                 version: None,
                                 git_hash: None,
                                 parent_version: None,
+                                source: None,
                             };
 
                             // Write the prompt with unique name
@@ -3346,6 +3427,7 @@ impl PromptTui {
                             version: None,
                             git_hash: None,
                             parent_version: None,
+                            source: None,
                         };
 
                         // Write the prompt
@@ -3460,9 +3542,13 @@ impl PromptTui {
                 " "
             };
 
+            let warning = match preview.findings.get(&prompt.name) {
+                Some(issues) => format!(" ⚠ {}", issues.len()),
+                None => String::new(),
+            };
             let line = format!(
-                " {} {} {} - {}",
-                cursor, checkbox, prompt.name, prompt.description
+                " {} {} {} - {}{}",
+                cursor, checkbox, prompt.name, prompt.description, warning
             );
 
             let style = if idx == preview.cursor_index {
@@ -3474,6 +3560,34 @@ impl PromptTui {
             lines.push(Line::from(Span::styled(line, style)));
         }
 
+        if !preview.findings.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![Span::styled(
+                "Security findings:",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )]));
+            for (name, issues) in &preview.findings {
+                for issue in issues {
+                    let color = match issue.severity {
+                        SecuritySeverity::Critical => Color::Red,
+                        SecuritySeverity::High => Color::LightRed,
+                        _ => Color::Yellow,
+                    };
+                    let location = issue
+                        .line_number
+                        .map(|line| format!(":{}", line))
+                        .unwrap_or_default();
+                    lines.push(Line::from(vec![
+                        Span::raw(format!("  {}{} ", name, location)),
+                        Span::styled(
+                            format!("[{:?}] {}", issue.severity, issue.description),
+                            Style::default().fg(color),
+                        ),
+                    ]));
+                }
+            }
+        }
+
         lines.push(Line::from(""));
         lines.push(Line::from(vec![Span::styled(
             "Options:",
@@ -3547,7 +3661,18 @@ impl PromptTui {
             } else {
                 Line::from("  • Install to local prompts")
             },
-            Line::from(""),
+            if preview
+                .selected_prompts
+                .iter()
+                .any(|name| preview.findings.contains_key(name))
+            {
+                Line::from(vec![Span::styled(
+                    "⚠ Selected prompts have security findings",
+                    Style::default().fg(Color::Red),
+                )])
+            } else {
+                Line::from("")
+            },
             Line::from(vec![Span::styled(
                 "Continue with installation?",
                 Style::default().fg(Color::Yellow),
@@ -3708,7 +3833,7 @@ impl PromptTui {
                                     name: prompt.name.clone(),
                                     bank: prompt.bank_name.clone(),
                                     content: prompt.content.clone(),
-                                    metadata: crate::storage::PromptMetadata {
+                                    metadata: Box::new(crate::storage::PromptMetadata {
                                         id: prompt.name.clone(),
                                         description: prompt.description.clone(),
                                         tags: if prompt.tags.is_empty() {
@@ -3721,7 +3846,8 @@ impl PromptTui {
                                         version: None,
                                         git_hash: None,
                                         parent_version: None,
-                                    },
+                                        source: None,
+                                    }),
                                 });
                             }
 
@@ -4059,6 +4185,7 @@ impl PromptTui {
                             version: None,
                             git_hash: None,
                             parent_version: None,
+                            source: None,
                         };
 
                         let content = format!(
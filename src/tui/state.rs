@@ -74,7 +74,7 @@ pub enum UndoOperation {
         name: String,
         bank: Option<String>,
         content: String,
-        metadata: crate::storage::PromptMetadata,
+        metadata: Box<crate::storage::PromptMetadata>,
     },
     MovePrompt {
         name: String,
@@ -354,7 +354,7 @@ impl TuiState {
 
     /// Add tag to item
     pub fn add_tag(&mut self, item: &str, tag: &str) {
-        self.tags.entry(item.to_string()).or_insert_with(HashSet::new).insert(tag.to_string());
+        self.tags.entry(item.to_string()).or_default().insert(tag.to_string());
     }
 
     /// Remove tag from item